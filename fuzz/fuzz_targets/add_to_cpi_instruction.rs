@@ -0,0 +1,411 @@
+//! Feeds arbitrary extra account meta lists and account sets to
+//! `sol_sdk::add_to_cpi_instruction` and checks it against the upstream
+//! `ExtraAccountMetaList::add_to_cpi_instruction`: same result, same accounts appended,
+//! and no account of the original instruction gains a privilege it didn't have.
+
+#![no_main]
+
+use std::cell::Cell;
+use std::future::{ready, Future};
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use solana_program::account_info::AccountInfo;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::program_stubs::{self, SyscallStubs};
+use solana_program::pubkey::{Pubkey, MAX_SEED_LEN};
+use spl_tlv_account_resolution::account::ExtraAccountMeta;
+use spl_tlv_account_resolution::pubkey_data::PubkeyData;
+use spl_tlv_account_resolution::seeds::Seed;
+use spl_tlv_account_resolution::state::ExtraAccountMetaList;
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+use transfer_extensions::sol_sdk;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    meta_list: MetaList,
+    program: u8,
+    instruction_data: Vec<u8>,
+    accounts: Vec<Account>,
+    /// Accounts of the CPI instruction, taken from the start of `accounts`
+    instruction_accounts: u8,
+    /// Leave the accounts the metas resolve to out of the account infos
+    without_resolved_accounts: bool,
+}
+
+#[derive(Arbitrary, Debug)]
+enum MetaList {
+    /// Unchecked TLV data
+    Raw(Vec<u8>),
+    Metas(Vec<Meta>),
+}
+
+#[derive(Arbitrary, Debug)]
+enum Meta {
+    Raw {
+        discriminator: u8,
+        address_config: [u8; 32],
+        is_signer: bool,
+        is_writable: bool,
+    },
+    Pubkey {
+        key: u8,
+        is_signer: bool,
+        is_writable: bool,
+    },
+    Pda {
+        seeds: Vec<ArbitrarySeed>,
+        is_signer: bool,
+        is_writable: bool,
+    },
+    ExternalPda {
+        program_index: u8,
+        seeds: Vec<ArbitrarySeed>,
+        is_signer: bool,
+        is_writable: bool,
+    },
+    InstructionDataPubkey {
+        index: u8,
+        is_signer: bool,
+        is_writable: bool,
+    },
+    AccountDataPubkey {
+        account_index: u8,
+        data_index: u8,
+        is_signer: bool,
+        is_writable: bool,
+    },
+}
+
+#[derive(Arbitrary, Debug)]
+enum ArbitrarySeed {
+    Literal(Vec<u8>),
+    InstructionData {
+        index: u8,
+        length: u8,
+    },
+    AccountKey {
+        index: u8,
+    },
+    AccountData {
+        account_index: u8,
+        data_index: u8,
+        length: u8,
+    },
+}
+
+#[derive(Arbitrary, Debug)]
+struct Account {
+    key: u8,
+    data: Vec<u8>,
+    is_signer: bool,
+    is_writable: bool,
+}
+
+/// Backing storage of an `AccountInfo`
+struct StoredAccount {
+    key: Pubkey,
+    owner: Pubkey,
+    lamports: u64,
+    data: Vec<u8>,
+    is_signer: bool,
+    is_writable: bool,
+}
+
+/// `msg!` prints to stdout off-chain, which slows fuzzing down
+struct SilentStubs;
+
+impl SyscallStubs for SilentStubs {
+    fn sol_log(&self, _message: &str) {}
+}
+
+thread_local! {
+    static CATCHING_PANIC: Cell<bool> = const { Cell::new(false) };
+}
+
+/// `None` when `f` panicked. `Pubkey::find_program_address` panics off-chain on seeds the
+/// runtime rejects, more than 16 or longer than 32 bytes, where the program aborts on-chain.
+fn catch_panic<T>(f: impl FnOnce() -> T) -> Option<T> {
+    CATCHING_PANIC.set(true);
+    let result = panic::catch_unwind(AssertUnwindSafe(f)).ok();
+    CATCHING_PANIC.set(false);
+    result
+}
+
+fn key(seed: u8) -> Pubkey {
+    Pubkey::new_from_array([seed; 32])
+}
+
+impl From<&ArbitrarySeed> for Seed {
+    fn from(seed: &ArbitrarySeed) -> Self {
+        match *seed {
+            // longer seeds can't derive an address, and upstream packing panics
+            // on literals above 255 bytes
+            ArbitrarySeed::Literal(ref bytes) => Seed::Literal {
+                bytes: bytes[..bytes.len().min(MAX_SEED_LEN)].to_vec(),
+            },
+            ArbitrarySeed::InstructionData { index, length } => {
+                Seed::InstructionData { index, length }
+            }
+            ArbitrarySeed::AccountKey { index } => Seed::AccountKey { index },
+            ArbitrarySeed::AccountData {
+                account_index,
+                data_index,
+                length,
+            } => Seed::AccountData {
+                account_index,
+                data_index,
+                length,
+            },
+        }
+    }
+}
+
+impl Meta {
+    /// `None` when the configuration doesn't fit in an `ExtraAccountMeta`
+    fn to_extra_account_meta(&self) -> Option<ExtraAccountMeta> {
+        let to_seeds = |seeds: &[ArbitrarySeed]| seeds.iter().map(Seed::from).collect::<Vec<_>>();
+        match *self {
+            Meta::Raw {
+                discriminator,
+                address_config,
+                is_signer,
+                is_writable,
+            } => Some(ExtraAccountMeta {
+                discriminator,
+                address_config,
+                is_signer: is_signer.into(),
+                is_writable: is_writable.into(),
+            }),
+            Meta::Pubkey {
+                key: seed,
+                is_signer,
+                is_writable,
+            } => ExtraAccountMeta::new_with_pubkey(&key(seed), is_signer, is_writable).ok(),
+            Meta::Pda {
+                ref seeds,
+                is_signer,
+                is_writable,
+            } => ExtraAccountMeta::new_with_seeds(&to_seeds(seeds), is_signer, is_writable).ok(),
+            Meta::ExternalPda {
+                program_index,
+                ref seeds,
+                is_signer,
+                is_writable,
+            } => ExtraAccountMeta::new_external_pda_with_seeds(
+                program_index,
+                &to_seeds(seeds),
+                is_signer,
+                is_writable,
+            )
+            .ok(),
+            Meta::InstructionDataPubkey {
+                index,
+                is_signer,
+                is_writable,
+            } => ExtraAccountMeta::new_with_pubkey_data(
+                &PubkeyData::InstructionData { index },
+                is_signer,
+                is_writable,
+            )
+            .ok(),
+            Meta::AccountDataPubkey {
+                account_index,
+                data_index,
+                is_signer,
+                is_writable,
+            } => ExtraAccountMeta::new_with_pubkey_data(
+                &PubkeyData::AccountData {
+                    account_index,
+                    data_index,
+                },
+                is_signer,
+                is_writable,
+            )
+            .ok(),
+        }
+    }
+}
+
+fn meta_list_data(meta_list: &MetaList) -> Vec<u8> {
+    match meta_list {
+        MetaList::Raw(data) => data.clone(),
+        MetaList::Metas(metas) => {
+            let metas: Vec<_> = metas
+                .iter()
+                .filter_map(Meta::to_extra_account_meta)
+                .collect();
+            let mut data = vec![0; ExtraAccountMetaList::size_of(metas.len()).unwrap()];
+            ExtraAccountMetaList::init::<ExecuteInstruction>(&mut data, &metas).unwrap();
+            data
+        }
+    }
+}
+
+/// Polls `future` once, account fetches are ready right away
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    match future
+        .as_mut()
+        .poll(&mut Context::from_waker(Waker::noop()))
+    {
+        Poll::Ready(output) => output,
+        Poll::Pending => unreachable!("account fetches are ready on first poll"),
+    }
+}
+
+/// Highest privileges of `pubkey` in `metas`, `None` when it isn't one of them
+fn highest_privileges(metas: &[AccountMeta], pubkey: &Pubkey) -> Option<(bool, bool)> {
+    metas
+        .iter()
+        .filter(|meta| meta.pubkey == *pubkey)
+        .map(|meta| (meta.is_signer, meta.is_writable))
+        .reduce(|acc, x| (acc.0 || x.0, acc.1 || x.1))
+}
+
+fn account_keys(account_infos: &[AccountInfo]) -> Vec<(Pubkey, bool, bool)> {
+    account_infos
+        .iter()
+        .map(|info| (*info.key, info.is_signer, info.is_writable))
+        .collect()
+}
+
+fuzz_target!(
+    init: {
+        program_stubs::set_syscall_stubs(Box::new(SilentStubs));
+        // the hook of libfuzzer-sys aborts on any panic, even a caught one
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !CATCHING_PANIC.get() {
+                hook(info)
+            }
+        }));
+    },
+    |input: Input| {
+        let data = meta_list_data(&input.meta_list);
+        let program_id = key(input.program);
+        let instruction_accounts = (input.instruction_accounts as usize).min(input.accounts.len());
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &input.instruction_data,
+            input.accounts[..instruction_accounts]
+                .iter()
+                .map(|account| AccountMeta {
+                    pubkey: key(account.key),
+                    is_signer: account.is_signer,
+                    is_writable: account.is_writable,
+                })
+                .collect(),
+        );
+
+        let mut accounts: Vec<_> = input
+            .accounts
+            .iter()
+            .map(|account| StoredAccount {
+                key: key(account.key),
+                owner: Pubkey::default(),
+                lamports: 0,
+                data: account.data.clone(),
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+            .collect();
+        // PDAs can't be guessed by the fuzzer, resolve the metas against the accounts off-chain
+        // and hand the resolved addresses to the CPI resolution
+        if !input.without_resolved_accounts {
+            let mut resolved = instruction.clone();
+            catch_panic(|| {
+                block_on(ExtraAccountMetaList::add_to_instruction::<ExecuteInstruction, _, _>(
+                    &mut resolved,
+                    |address| {
+                        ready(Ok(accounts
+                            .iter()
+                            .find(|account| account.key == address)
+                            .map(|account| account.data.clone())))
+                    },
+                    &data,
+                ))
+            });
+            for meta in resolved.accounts.drain(instruction_accounts..) {
+                if accounts.iter().all(|account| account.key != meta.pubkey) {
+                    accounts.push(StoredAccount {
+                        key: meta.pubkey,
+                        owner: Pubkey::default(),
+                        lamports: 0,
+                        data: vec![],
+                        is_signer: meta.is_signer,
+                        is_writable: meta.is_writable,
+                    });
+                }
+            }
+        }
+        let account_infos: Vec<_> = accounts
+            .iter_mut()
+            .map(|account| {
+                AccountInfo::new(
+                    &account.key,
+                    account.is_signer,
+                    account.is_writable,
+                    &mut account.lamports,
+                    &mut account.data,
+                    &account.owner,
+                    false,
+                    0,
+                )
+            })
+            .collect();
+
+        let mut cpi_instruction = instruction.clone();
+        let mut cpi_account_infos = account_infos[..instruction_accounts].to_vec();
+        let result = catch_panic(|| {
+            sol_sdk::add_to_cpi_instruction(
+                &mut cpi_instruction,
+                &mut cpi_account_infos,
+                &data,
+                &account_infos,
+            )
+        });
+
+        let mut expected_instruction = instruction.clone();
+        let mut expected_account_infos = account_infos[..instruction_accounts].to_vec();
+        let expected = catch_panic(|| {
+            ExtraAccountMetaList::add_to_cpi_instruction::<ExecuteInstruction>(
+                &mut expected_instruction,
+                &mut expected_account_infos,
+                &data,
+                &account_infos,
+            )
+        });
+
+        assert_eq!(result, expected);
+        assert_eq!(cpi_instruction, expected_instruction);
+        assert_eq!(
+            account_keys(&cpi_account_infos),
+            account_keys(&expected_account_infos)
+        );
+
+        let (original, appended) = cpi_instruction.accounts.split_at(instruction_accounts);
+        assert_eq!(original, &instruction.accounts[..]);
+        assert_eq!(cpi_account_infos.len(), cpi_instruction.accounts.len());
+        for (meta, account_info) in cpi_instruction.accounts.iter().zip(&cpi_account_infos) {
+            assert_eq!(meta.pubkey, *account_info.key);
+        }
+        for meta in appended {
+            if let Some((is_signer, is_writable)) = highest_privileges(original, &meta.pubkey) {
+                assert!(
+                    is_signer || !meta.is_signer,
+                    "{} escalated to signer",
+                    meta.pubkey
+                );
+                assert!(
+                    is_writable || !meta.is_writable,
+                    "{} escalated to writable",
+                    meta.pubkey
+                );
+            }
+        }
+    }
+);
@@ -0,0 +1,53 @@
+//! `sol_sdk::de_escalate_account_meta` only ever drops privileges, and only those the
+//! account doesn't already have in the instruction.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use solana_program::instruction::AccountMeta;
+use solana_program::pubkey::Pubkey;
+use transfer_extensions::sol_sdk::de_escalate_account_meta;
+
+#[derive(Arbitrary, Debug)]
+struct Meta {
+    key: u8,
+    is_signer: bool,
+    is_writable: bool,
+}
+
+impl From<&Meta> for AccountMeta {
+    fn from(meta: &Meta) -> Self {
+        AccountMeta {
+            pubkey: Pubkey::new_from_array([meta.key; 32]),
+            is_signer: meta.is_signer,
+            is_writable: meta.is_writable,
+        }
+    }
+}
+
+fuzz_target!(|input: (Meta, Vec<Meta>)| {
+    let (meta, metas) = input;
+    let original = AccountMeta::from(&meta);
+    let account_metas: Vec<_> = metas.iter().map(AccountMeta::from).collect();
+
+    let mut account_meta = original.clone();
+    de_escalate_account_meta(&mut account_meta, &account_metas);
+
+    assert_eq!(account_meta.pubkey, original.pubkey);
+    match account_metas
+        .iter()
+        .filter(|x| x.pubkey == original.pubkey)
+        .map(|x| (x.is_signer, x.is_writable))
+        .reduce(|acc, x| (acc.0 || x.0, acc.1 || x.1))
+    {
+        Some((is_signer, is_writable)) => {
+            assert_eq!(account_meta.is_signer, original.is_signer && is_signer);
+            assert_eq!(
+                account_meta.is_writable,
+                original.is_writable && is_writable
+            );
+        }
+        None => assert_eq!(account_meta, original),
+    }
+});
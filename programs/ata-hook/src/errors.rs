@@ -0,0 +1,7 @@
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum AtaHookError {
+    #[msg("Destination is not the associated token account of its owner")]
+    NotAssociatedTokenAccount,
+}
@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::ID as ASSOCIATED_TOKEN_PROGRAM_ID;
+use anchor_spl::{token_2022::ID as TOKEN_2022_PROGRAM_ID, token_interface::Mint};
+use spl_tlv_account_resolution::state::ExtraAccountMetaList;
+use spl_tlv_account_resolution::{account::ExtraAccountMeta, seeds::Seed};
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+
+pub const META_LIST_ACCOUNT_SEED: &[u8] = b"extra-account-metas";
+
+/// Token account data offset of the owner field
+pub const TOKEN_ACCOUNT_OWNER_OFFSET: u8 = 32;
+
+#[derive(Accounts)]
+pub struct InitializeExtraAccountMetaList<'info> {
+    #[account(
+      init,
+      space = ExtraAccountMetaList::size_of(get_extra_account_metas()?.len()).unwrap(),
+      seeds = [
+        META_LIST_ACCOUNT_SEED,
+        mint.key().as_ref(),
+      ],
+      bump,
+      payer = payer,
+    )]
+    /// CHECK: extra metas account
+    pub extra_metas_account: UncheckedAccount<'info>,
+
+    #[account(
+        mint::token_program = TOKEN_2022_PROGRAM_ID,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_extra_account_meta_list(
+    ctx: Context<InitializeExtraAccountMetaList>,
+) -> Result<()> {
+    let metas = get_extra_account_metas()?;
+    let mut data = ctx.accounts.extra_metas_account.try_borrow_mut_data()?;
+    ExtraAccountMetaList::init::<ExecuteInstruction>(&mut data, &metas)?;
+
+    Ok(())
+}
+
+pub fn get_extra_account_metas() -> Result<Vec<ExtraAccountMeta>> {
+    Ok(vec![
+        // [index 5, 0] associated token program id
+        ExtraAccountMeta::new_with_pubkey(
+            &ASSOCIATED_TOKEN_PROGRAM_ID,
+            false, // is_signer
+            false, // is_writable
+        )?,
+        // [index 6, 1] token program id, seed of the associated token account
+        ExtraAccountMeta::new_with_pubkey(&TOKEN_2022_PROGRAM_ID, false, false)?,
+        // [index 7, 2] associated token account of the destination owner
+        ExtraAccountMeta::new_external_pda_with_seeds(
+            5,
+            &[
+                Seed::AccountData {
+                    account_index: 2,
+                    data_index: TOKEN_ACCOUNT_OWNER_OFFSET,
+                    length: 32,
+                },
+                Seed::AccountKey { index: 6 },
+                Seed::AccountKey { index: 1 },
+            ],
+            false,
+            false,
+        )?,
+    ])
+}
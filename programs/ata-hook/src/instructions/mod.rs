@@ -0,0 +1,5 @@
+pub mod handler;
+pub use handler::*;
+
+pub mod initialize_extra_meta_list;
+pub use initialize_extra_meta_list::*;
@@ -0,0 +1,31 @@
+//! Reference transfer hook only allowing transfers to associated token accounts,
+//! resolving the associated token account of the destination owner as a PDA
+//! of the associated token program.
+
+use anchor_lang::prelude::*;
+
+declare_id!("7bGkESq7YYPt4D1s3CuXtKDqjEYgDTngLTJqkAvP6UpG");
+
+pub mod errors;
+pub mod instructions;
+use instructions::*;
+
+#[program]
+pub mod ata_hook {
+    use super::*;
+
+    /// execute transfer hook
+    #[interface(spl_transfer_hook_interface::execute)]
+    pub fn execute_transaction<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteTransferHook<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::handler(ctx, amount)
+    }
+
+    pub fn initialize_extra_account_meta_list(
+        ctx: Context<InitializeExtraAccountMetaList>,
+    ) -> Result<()> {
+        instructions::initialize_extra_account_meta_list(ctx)
+    }
+}
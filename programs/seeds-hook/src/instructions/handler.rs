@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+use spl_tlv_account_resolution::state::ExtraAccountMetaList;
+use spl_transfer_hook_interface::instruction::{ExecuteInstruction, TransferHookInstruction};
+
+use super::META_LIST_ACCOUNT_SEED;
+
+#[derive(Accounts)]
+pub struct ExecuteTransferHook<'info> {
+    /// CHECK: checked by the token program
+    pub source_account: UncheckedAccount<'info>,
+
+    /// CHECK: checked by the token program
+    pub mint: UncheckedAccount<'info>,
+
+    /// CHECK: checked by the token program
+    pub destination_account: UncheckedAccount<'info>,
+
+    /// CHECK: can be any account
+    pub owner_delegate: UncheckedAccount<'info>,
+
+    /// CHECK: meta list account
+    #[account(
+      seeds = [META_LIST_ACCOUNT_SEED, mint.key().as_ref()],
+      bump,
+    )]
+    pub extra_metas_account: UncheckedAccount<'info>,
+    // `remaining_accounts` are the extra accounts of the meta list
+}
+
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, ExecuteTransferHook<'info>>,
+    amount: u64,
+) -> Result<()> {
+    msg!("Executing transfer hook with amount: {:?}", amount);
+    let accounts = &ctx.accounts;
+    let account_infos: Vec<AccountInfo<'info>> = [
+        accounts.source_account.to_account_info(),
+        accounts.mint.to_account_info(),
+        accounts.destination_account.to_account_info(),
+        accounts.owner_delegate.to_account_info(),
+        accounts.extra_metas_account.to_account_info(),
+    ]
+    .into_iter()
+    .chain(ctx.remaining_accounts.iter().cloned())
+    .collect();
+
+    ExtraAccountMetaList::check_account_infos::<ExecuteInstruction>(
+        &account_infos,
+        &TransferHookInstruction::Execute { amount }.pack(),
+        ctx.program_id,
+        &accounts.extra_metas_account.try_borrow_data()?,
+    )?;
+    Ok(())
+}
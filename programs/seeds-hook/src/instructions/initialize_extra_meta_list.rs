@@ -0,0 +1,108 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{token_2022::ID as TOKEN_2022_PROGRAM_ID, token_interface::Mint};
+use spl_tlv_account_resolution::state::ExtraAccountMetaList;
+use spl_tlv_account_resolution::{account::ExtraAccountMeta, pubkey_data::PubkeyData, seeds::Seed};
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+
+pub const META_LIST_ACCOUNT_SEED: &[u8] = b"extra-account-metas";
+
+pub const AMOUNT_SEED: &[u8] = b"amount";
+pub const OWNER_SEED: &[u8] = b"owner";
+pub const ROUTE_SEED: &[u8] = b"route";
+
+/// Token account data offset of the owner field
+pub const TOKEN_ACCOUNT_OWNER_OFFSET: u8 = 32;
+
+/// Execute instruction data offset of the amount, after the discriminator
+pub const AMOUNT_OFFSET: u8 = 8;
+
+#[derive(Accounts)]
+pub struct InitializeExtraAccountMetaList<'info> {
+    #[account(
+      init,
+      space = ExtraAccountMetaList::size_of(get_extra_account_metas()?.len()).unwrap(),
+      seeds = [
+        META_LIST_ACCOUNT_SEED,
+        mint.key().as_ref(),
+      ],
+      bump,
+      payer = payer,
+    )]
+    /// CHECK: extra metas account
+    pub extra_metas_account: UncheckedAccount<'info>,
+
+    #[account(
+        mint::token_program = TOKEN_2022_PROGRAM_ID,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_extra_account_meta_list(
+    ctx: Context<InitializeExtraAccountMetaList>,
+) -> Result<()> {
+    let metas = get_extra_account_metas()?;
+    let mut data = ctx.accounts.extra_metas_account.try_borrow_mut_data()?;
+    ExtraAccountMetaList::init::<ExecuteInstruction>(&mut data, &metas)?;
+
+    Ok(())
+}
+
+pub fn get_extra_account_metas() -> Result<Vec<ExtraAccountMeta>> {
+    Ok(vec![
+        // [index 5, 0] PDA of the transferred amount
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: AMOUNT_SEED.to_vec(),
+                },
+                Seed::InstructionData {
+                    index: AMOUNT_OFFSET,
+                    length: 8,
+                },
+            ],
+            false, // is_signer
+            false, // is_writable
+        )?,
+        // [index 6, 1] PDA of the source owner
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: OWNER_SEED.to_vec(),
+                },
+                Seed::AccountData {
+                    account_index: 0,
+                    data_index: TOKEN_ACCOUNT_OWNER_OFFSET,
+                    length: 32,
+                },
+            ],
+            false,
+            false,
+        )?,
+        // [index 7, 2] destination owner
+        ExtraAccountMeta::new_with_pubkey_data(
+            &PubkeyData::AccountData {
+                account_index: 2,
+                data_index: TOKEN_ACCOUNT_OWNER_OFFSET,
+            },
+            false,
+            false,
+        )?,
+        // [index 8, 3] PDA of the source owner PDA and the destination owner
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: ROUTE_SEED.to_vec(),
+                },
+                Seed::AccountKey { index: 6 },
+                Seed::AccountKey { index: 7 },
+            ],
+            false,
+            false,
+        )?,
+    ])
+}
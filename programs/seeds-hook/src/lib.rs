@@ -0,0 +1,30 @@
+//! Reference transfer hook resolving its extra accounts from the execute instruction data,
+//! the data of the transferred token accounts and previously resolved extra accounts.
+//! It only checks the accounts it is executed with.
+
+use anchor_lang::prelude::*;
+
+declare_id!("Fag66x8roWaV3dZhzMy9oEdSQeusQ99Mp3rVzonDkYKD");
+
+pub mod instructions;
+use instructions::*;
+
+#[program]
+pub mod seeds_hook {
+    use super::*;
+
+    /// execute transfer hook
+    #[interface(spl_transfer_hook_interface::execute)]
+    pub fn execute_transaction<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteTransferHook<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::handler(ctx, amount)
+    }
+
+    pub fn initialize_extra_account_meta_list(
+        ctx: Context<InitializeExtraAccountMetaList>,
+    ) -> Result<()> {
+        instructions::initialize_extra_account_meta_list(ctx)
+    }
+}
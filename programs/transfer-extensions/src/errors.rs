@@ -4,4 +4,60 @@ use anchor_lang::prelude::*;
 pub enum TransferExtensionsError {
   #[msg("Amount must be greater than 0")]
   AmountMustBeGreaterThanZero,
+  #[msg("Account is not a migratable state account")]
+  UnknownAccountType,
+  #[msg("Account is already migrated to the current layout")]
+  AccountAlreadyMigrated,
+  #[msg("Mint has no transfer hook program")]
+  MintWithoutTransferHook,
+  #[msg("Signer is not the authority of the mint transfer hook program")]
+  InvalidHookAuthority,
+  #[msg("Counter isn't a legacy counter of the subject and mint")]
+  InvalidLegacyCounter,
+  #[msg("Stats accounts are missing for a transfer without hook")]
+  MissingStatsAccounts,
+  #[msg("Transfer leg account index is out of remaining accounts")]
+  InvalidLegAccountIndex,
+  #[msg("Mint isn't owned by the Token or Token-2022 program, or it isn't passed")]
+  InvalidTokenProgram,
+  #[msg("Token account has more than one net position")]
+  DuplicateNetPosition,
+  #[msg("Token account moved by a leg has no net position")]
+  MissingNetPosition,
+  #[msg("Balance change doesn't match the net position")]
+  NetPositionMismatch,
+  #[msg("Escrow release condition isn't met")]
+  EscrowReleaseNotAllowed,
+  #[msg("Escrow can only be cancelled by the receiver or the arbiter")]
+  EscrowCancelNotAllowed,
+  #[msg("Stream must end after it starts and stream a non-zero amount")]
+  InvalidStreamSchedule,
+  #[msg("Nothing to withdraw from the stream yet")]
+  NothingToWithdraw,
+  #[msg("Stream amount overflows")]
+  StreamAmountOverflow,
+  #[msg("Scheduled transfer isn't due yet")]
+  ScheduledTransferNotDue,
+  #[msg("Scheduled transfer amount and tip overflow")]
+  ScheduledAmountOverflow,
+  #[msg("Splitter needs 1 to 10 recipients with shares summing to 10000 bps")]
+  InvalidSplitShares,
+  #[msg("Splitter remainder recipient index is out of recipients")]
+  InvalidRemainderIndex,
+  #[msg("Splitter account isn't passed in remaining accounts")]
+  MissingSplitAccount,
+  #[msg("Merkle proof doesn't match the distributor root")]
+  InvalidMerkleProof,
+  #[msg("Airdrop is already claimed")]
+  AlreadyClaimed,
+  #[msg("Royalty must be at most 10000 bps")]
+  InvalidRoyaltyBps,
+  #[msg("Signer is not the mint authority")]
+  NotMintAuthority,
+  #[msg("No royalty owed")]
+  NoRoyaltyOwed,
+  #[msg("Transfer amount is above the limit of the owner policy")]
+  OwnerPolicyLimitExceeded,
+  #[msg("Royalty debt is not initialized, nothing was accrued for the wallet")]
+  RoyaltyDebtNotInitialized,
 }
\ No newline at end of file
@@ -0,0 +1,135 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{self, extension::StateWithExtensions};
+
+use crate::{
+    errors::TransferExtensionsError,
+    instructions::{
+        record_transfer_without_hook, RecordTransferAccounts, TRANSFER_HOOK_PROGRAM_ID,
+    },
+    seeds::TRANSFER_RECORDER_SEED,
+    sol_sdk::{invoke_transfer_checked_with_fee, log_heap_usage, FeeMode},
+};
+
+/// Transfer of a batch, accounts are indexes into `remaining_accounts`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct TransferLeg {
+    pub source_index: u8,
+    pub mint_index: u8,
+    pub destination_index: u8,
+    pub amount: u64,
+    pub fee_mode: FeeMode,
+    /// Required for legacy Token program mints, which have no transfer hook to record stats:
+    /// source stats, destination stats, mint stats and the global stats shard of the source owner.
+    /// The transfer hook program and the transfer recorder PDA must be passed too.
+    pub stats_indexes: Option<[u8; 4]>,
+}
+
+#[derive(Accounts)]
+pub struct BatchTransfers<'info> {
+    /// Authority of every source account
+    #[account(mut)]
+    pub signer: Signer<'info>,
+}
+
+/// `remaining_accounts` hold the accounts of all legs, the token programs of their mints
+/// and the transfer hook extra accounts. Every mint resolves its own transfer hook,
+/// so accounts shared between legs are passed only once.
+pub fn batch_transfers<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BatchTransfers<'info>>,
+    legs: Vec<TransferLeg>,
+) -> Result<()> {
+    msg!("Batch transfers: {}", legs.len());
+    require!(
+        legs.iter().all(|leg| leg.amount > 0),
+        TransferExtensionsError::AmountMustBeGreaterThanZero
+    );
+
+    let authority = ctx.accounts.signer.to_account_info();
+    for leg in legs.iter() {
+        execute_transfer_leg(&authority, ctx.remaining_accounts, leg, &[])?;
+    }
+
+    log_heap_usage();
+    Ok(())
+}
+
+/// Executes one leg, its accounts are taken from `accounts`,
+/// which are also the pool of transfer hook extra accounts
+pub fn execute_transfer_leg<'info>(
+    authority: &AccountInfo<'info>,
+    accounts: &'info [AccountInfo<'info>],
+    leg: &TransferLeg,
+    seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let source = get_leg_account(accounts, leg.source_index)?;
+    let mint = get_leg_account(accounts, leg.mint_index)?;
+    let destination = get_leg_account(accounts, leg.destination_index)?;
+
+    let is_legacy_token = *mint.owner == anchor_spl::token::ID;
+    require!(
+        is_legacy_token || *mint.owner == spl_token_2022::ID,
+        TransferExtensionsError::InvalidTokenProgram
+    );
+    let token_program = accounts
+        .iter()
+        .find(|x| x.key == mint.owner)
+        .ok_or(TransferExtensionsError::InvalidTokenProgram)?;
+    let decimals = {
+        let mint_data = mint.try_borrow_data()?;
+        StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?
+            .base
+            .decimals
+    };
+
+    invoke_transfer_checked_with_fee(
+        token_program.key,
+        source.clone(),
+        mint.clone(),
+        destination.clone(),
+        authority.clone(),
+        accounts,
+        leg.amount,
+        decimals,
+        leg.fee_mode,
+        seeds,
+    )?;
+
+    if is_legacy_token {
+        let [source_stats, destination_stats, mint_stats, global_stats_shard] = leg
+            .stats_indexes
+            .ok_or(TransferExtensionsError::MissingStatsAccounts)?;
+        let transfer_recorder =
+            Pubkey::find_program_address(&[TRANSFER_RECORDER_SEED], &crate::ID).0;
+        let find_account = |key: &Pubkey| {
+            accounts
+                .iter()
+                .find(|x| x.key == key)
+                .ok_or(TransferExtensionsError::MissingStatsAccounts)
+        };
+        record_transfer_without_hook(
+            source,
+            destination,
+            mint,
+            &RecordTransferAccounts {
+                source_stats: get_leg_account(accounts, source_stats)?,
+                destination_stats: get_leg_account(accounts, destination_stats)?,
+                mint_stats: get_leg_account(accounts, mint_stats)?,
+                global_stats_shard: get_leg_account(accounts, global_stats_shard)?,
+                transfer_hook_program: find_account(&TRANSFER_HOOK_PROGRAM_ID)?,
+                transfer_recorder: find_account(&transfer_recorder)?,
+            },
+            leg.amount,
+        )?;
+    }
+
+    Ok(())
+}
+
+pub fn get_leg_account<'info>(
+    accounts: &'info [AccountInfo<'info>],
+    index: u8,
+) -> Result<&'info AccountInfo<'info>> {
+    accounts
+        .get(index as usize)
+        .ok_or(TransferExtensionsError::InvalidLegAccountIndex.into())
+}
@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    errors::TransferExtensionsError,
+    instructions::pay_out_escrow,
+    states::{Escrow, ReleaseCondition},
+};
+
+#[derive(Accounts)]
+pub struct CancelEscrow<'info> {
+    #[account(mut, close = maker,
+        has_one = maker,
+        has_one = mint,
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    #[account(mut,
+      associated_token::mint = mint,
+      associated_token::authority = escrow,
+      associated_token::token_program = token_program,
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+      token::mint = mint,
+      token::authority = maker,
+      token::token_program = token_program,
+    )]
+    pub maker_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Writable to harvest the transfer fees withheld in the vault
+    #[account(mut,
+      mint::token_program = token_program,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Gets the tokens, the rent of the escrow and its vault back
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    /// The receiver or the arbiter
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Returns the vault to the maker and closes the escrow.
+/// `remaining_accounts` are the transfer hook extra accounts of the refund.
+pub fn cancel_escrow<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CancelEscrow<'info>>,
+) -> Result<()> {
+    let escrow = &ctx.accounts.escrow;
+    let authority = ctx.accounts.authority.key();
+    let can_cancel = authority == escrow.receiver
+        || matches!(escrow.condition, ReleaseCondition::Arbiter { arbiter } if authority == arbiter);
+    require!(can_cancel, TransferExtensionsError::EscrowCancelNotAllowed);
+    msg!("Cancel escrow {}", escrow.id);
+
+    pay_out_escrow(
+        escrow,
+        &ctx.accounts.vault,
+        &ctx.accounts.mint,
+        ctx.accounts.maker_token_account.to_account_info(),
+        ctx.accounts.maker.to_account_info(),
+        &ctx.accounts.token_program,
+        ctx.remaining_accounts,
+    )
+}
@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    instructions::close_vault,
+    seeds::SCHEDULED_TRANSFER_SEED,
+    sol_sdk::invoke_transfer_checked,
+    states::ScheduledTransfer,
+};
+
+#[derive(Accounts)]
+pub struct CancelScheduledTransfer<'info> {
+    #[account(mut, close = creator,
+        has_one = creator,
+        has_one = mint,
+    )]
+    pub scheduled_transfer: Box<Account<'info, ScheduledTransfer>>,
+
+    #[account(mut,
+      associated_token::mint = mint,
+      associated_token::authority = scheduled_transfer,
+      associated_token::token_program = token_program,
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+      token::mint = mint,
+      token::authority = creator,
+      token::token_program = token_program,
+    )]
+    pub creator_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Writable to harvest the transfer fees withheld in the vault
+    #[account(mut,
+      mint::token_program = token_program,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Gets the tokens, the rent of the scheduled transfer and its vault back
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Returns the vault to the creator and closes the scheduled transfer, due or not.
+/// `remaining_accounts` are the transfer hook extra accounts of the refund.
+pub fn cancel_scheduled_transfer<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CancelScheduledTransfer<'info>>,
+) -> Result<()> {
+    let scheduled_transfer = &ctx.accounts.scheduled_transfer;
+    msg!("Cancel scheduled transfer {}", scheduled_transfer.id);
+
+    let id = scheduled_transfer.id.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        SCHEDULED_TRANSFER_SEED,
+        scheduled_transfer.creator.as_ref(),
+        &id,
+        &[scheduled_transfer.bump],
+    ]];
+    if ctx.accounts.vault.amount > 0 {
+        invoke_transfer_checked(
+            ctx.accounts.token_program.key,
+            ctx.accounts.vault.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.creator_token_account.to_account_info(),
+            scheduled_transfer.to_account_info(),
+            ctx.remaining_accounts,
+            ctx.accounts.vault.amount,
+            ctx.accounts.mint.decimals,
+            signer_seeds,
+        )?;
+    }
+
+    close_vault(
+        &ctx.accounts.vault,
+        &ctx.accounts.mint,
+        ctx.accounts.creator.to_account_info(),
+        scheduled_transfer.to_account_info(),
+        &ctx.accounts.token_program,
+        signer_seeds,
+    )
+}
@@ -0,0 +1,108 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    errors::TransferExtensionsError,
+    instructions::close_vault,
+    seeds::PAYMENT_STREAM_SEED,
+    sol_sdk::invoke_transfer_checked,
+    states::PaymentStream,
+};
+
+#[derive(Accounts)]
+pub struct CancelStream<'info> {
+    #[account(mut, close = sender,
+        has_one = sender,
+        has_one = mint,
+    )]
+    pub stream: Box<Account<'info, PaymentStream>>,
+
+    #[account(mut,
+      associated_token::mint = mint,
+      associated_token::authority = stream,
+      associated_token::token_program = token_program,
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+      token::mint = mint,
+      token::authority = sender,
+      token::token_program = token_program,
+    )]
+    pub sender_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+      token::mint = mint,
+      token::authority = stream.recipient,
+      token::token_program = token_program,
+    )]
+    pub recipient_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Writable to harvest the transfer fees withheld in the vault
+    #[account(mut,
+      mint::token_program = token_program,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Gets the unstreamed tokens, the rent of the stream and its vault back
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Pays out what is streamed and not yet withdrawn to the recipient,
+/// returns the rest to the sender and closes the stream.
+/// `remaining_accounts` are the transfer hook extra accounts of both transfers.
+pub fn cancel_stream<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CancelStream<'info>>,
+) -> Result<()> {
+    let stream = &ctx.accounts.stream;
+    let recipient_amount = stream
+        .withdrawable_amount(Clock::get()?.unix_timestamp)
+        .ok_or(TransferExtensionsError::StreamAmountOverflow)?;
+    let sender_amount = ctx
+        .accounts
+        .vault
+        .amount
+        .checked_sub(recipient_amount)
+        .ok_or(TransferExtensionsError::StreamAmountOverflow)?;
+    msg!(
+        "Cancel stream {}: {} to recipient, {} to sender",
+        stream.id,
+        recipient_amount,
+        sender_amount
+    );
+
+    let id = stream.id.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] =
+        &[&[PAYMENT_STREAM_SEED, stream.sender.as_ref(), &id, &[stream.bump]]];
+    for (destination, amount) in [
+        (&ctx.accounts.recipient_token_account, recipient_amount),
+        (&ctx.accounts.sender_token_account, sender_amount),
+    ] {
+        if amount == 0 {
+            continue;
+        }
+        invoke_transfer_checked(
+            ctx.accounts.token_program.key,
+            ctx.accounts.vault.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            destination.to_account_info(),
+            stream.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            ctx.accounts.mint.decimals,
+            signer_seeds,
+        )?;
+    }
+
+    close_vault(
+        &ctx.accounts.vault,
+        &ctx.accounts.mint,
+        ctx.accounts.sender.to_account_info(),
+        stream.to_account_info(),
+        &ctx.accounts.token_program,
+        signer_seeds,
+    )
+}
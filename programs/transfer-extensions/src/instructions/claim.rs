@@ -0,0 +1,112 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use solana_program::keccak;
+
+use crate::{
+    errors::TransferExtensionsError,
+    seeds::{CLAIM_BITMAP_SEED, DISTRIBUTOR_SEED},
+    sol_sdk::invoke_transfer_checked,
+    states::{ClaimBitmap, Distributor, CLAIMS_PER_BITMAP},
+};
+
+#[derive(Accounts)]
+#[instruction(index: u64)]
+pub struct Claim<'info> {
+    #[account(mut,
+        has_one = mint,
+    )]
+    pub distributor: Box<Account<'info, Distributor>>,
+
+    #[account(mut,
+        seeds = [
+          CLAIM_BITMAP_SEED,
+          distributor.key().as_ref(),
+          &(index / CLAIMS_PER_BITMAP).to_le_bytes(),
+        ],
+        bump
+    )]
+    pub claim_bitmap: Box<Account<'info, ClaimBitmap>>,
+
+    #[account(mut,
+      associated_token::mint = mint,
+      associated_token::authority = distributor,
+      associated_token::token_program = token_program,
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+      token::mint = mint,
+      token::authority = claimant,
+      token::token_program = token_program,
+    )]
+    pub claimant_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+      mint::token_program = token_program,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub claimant: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Transfers the airdrop of the leaf `keccak(index, claimant, amount)` from the vault.
+/// `remaining_accounts` are the transfer hook extra accounts of the claim.
+pub fn claim<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Claim<'info>>,
+    index: u64,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    require!(amount > 0, TransferExtensionsError::AmountMustBeGreaterThanZero);
+    require!(
+        !ctx.accounts.claim_bitmap.is_claimed(index),
+        TransferExtensionsError::AlreadyClaimed
+    );
+    let leaf = keccak::hashv(&[
+        &index.to_le_bytes(),
+        ctx.accounts.claimant.key().as_ref(),
+        &amount.to_le_bytes(),
+    ]);
+    require!(
+        verify_merkle_proof(&proof, ctx.accounts.distributor.merkle_root, leaf.0),
+        TransferExtensionsError::InvalidMerkleProof
+    );
+    ctx.accounts.claim_bitmap.set_claimed(index);
+    msg!("Claim {} of leaf {}", amount, index);
+
+    let distributor = &ctx.accounts.distributor;
+    let id = distributor.id.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] =
+        &[&[DISTRIBUTOR_SEED, distributor.authority.as_ref(), &id, &[distributor.bump]]];
+    invoke_transfer_checked(
+        ctx.accounts.token_program.key,
+        ctx.accounts.vault.to_account_info(),
+        ctx.accounts.mint.to_account_info(),
+        ctx.accounts.claimant_token_account.to_account_info(),
+        distributor.to_account_info(),
+        ctx.remaining_accounts,
+        amount,
+        ctx.accounts.mint.decimals,
+        signer_seeds,
+    )?;
+
+    let distributor = &mut ctx.accounts.distributor;
+    distributor.claimed_count = distributor.claimed_count.checked_add(1).unwrap();
+    distributor.claimed_amount = distributor.claimed_amount.checked_add(amount).unwrap();
+
+    Ok(())
+}
+
+/// Nodes are hashed as `keccak(min(a, b), max(a, b))`, so proofs don't carry sides
+pub fn verify_merkle_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            keccak::hashv(&[&node, sibling]).0
+        } else {
+            keccak::hashv(&[sibling, &node]).0
+        }
+    });
+    computed == root
+}
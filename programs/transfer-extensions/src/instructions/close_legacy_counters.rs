@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{self, extension::StateWithExtensions};
+use anchor_spl::token_interface::Mint;
+
+use crate::{
+    errors::TransferExtensionsError,
+    instructions::get_hook_authority,
+    seeds::{COUNTER_IN_SEED, COUNTER_OUT_SEED},
+};
+
+
+/// Called by the transfer hook program of the mint once it migrated the in and out counters
+/// of `subject` into its TransferStats, signed with its hook authority PDA
+#[derive(Accounts)]
+pub struct CloseLegacyCounters<'info> {
+    /// CHECK: WalletCounterIn or MintCounterIn of `subject`, checked by `is_legacy_counter_of`
+    #[account(mut, owner = crate::ID)]
+    pub counter_in: UncheckedAccount<'info>,
+
+    /// CHECK: WalletCounterOut or MintCounterOut of `subject`, checked by `is_legacy_counter_of`
+    #[account(mut, owner = crate::ID)]
+    pub counter_out: UncheckedAccount<'info>,
+
+    /// CHECK: token account, owner wallet or the mint the counters were initialized for
+    pub subject: UncheckedAccount<'info>,
+
+    #[account(
+      token::token_program = anchor_spl::token_interface::spl_token_2022::id(),
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+      constraint = hook_authority.key() == get_hook_authority(&mint.to_account_info())?
+        @ TransferExtensionsError::InvalidHookAuthority,
+    )]
+    pub hook_authority: Signer<'info>,
+
+    /// CHECK: receives the rent of the closed counters
+    #[account(mut)]
+    pub receiver: UncheckedAccount<'info>,
+}
+
+
+pub fn close_legacy_counters(
+    ctx: Context<CloseLegacyCounters>,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    let mint = accounts.mint.key();
+    // the hook authority only signs for counters of its own mints
+    require!(
+        is_legacy_counter_of(&accounts.counter_in.key(), COUNTER_IN_SEED, &accounts.subject, &mint)
+            && is_legacy_counter_of(&accounts.counter_out.key(), COUNTER_OUT_SEED, &accounts.subject, &mint),
+        TransferExtensionsError::InvalidLegacyCounter
+    );
+
+    for counter in [&accounts.counter_in, &accounts.counter_out] {
+        close_account(&counter.to_account_info(), &accounts.receiver.to_account_info())?;
+    }
+
+    Ok(())
+}
+
+/// Counters were derived from the token account alone, from the owner wallet and the mint,
+/// or from the mint alone
+fn is_legacy_counter_of(counter: &Pubkey, seed: &[u8], subject: &AccountInfo, mint: &Pubkey) -> bool {
+    let find_address = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &crate::ID).0;
+
+    if *counter == find_address(&[seed, subject.key.as_ref(), mint.as_ref()]) {
+        return true;
+    }
+    if *counter != find_address(&[seed, subject.key.as_ref()]) {
+        return false;
+    }
+    if subject.key == mint {
+        return true;
+    }
+    let Ok(data) = subject.try_borrow_data() else {
+        return false;
+    };
+    *subject.owner == spl_token_2022::ID
+        && StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data)
+            .is_ok_and(|token_account| token_account.base.mint == *mint)
+}
+
+fn close_account<'info>(account: &AccountInfo<'info>, receiver: &AccountInfo<'info>) -> Result<()> {
+    let receiver_lamports = receiver.lamports().checked_add(account.lamports()).unwrap();
+    **receiver.try_borrow_mut_lamports()? = receiver_lamports;
+    **account.try_borrow_mut_lamports()? = 0;
+    account.assign(&System::id());
+    account.realloc(0, false)?;
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    errors::TransferExtensionsError,
+    seeds::ESCROW_SEED,
+    sol_sdk::invoke_transfer_checked,
+    states::{Escrow, ReleaseCondition, CURRENT_ACCOUNT_VERSION},
+};
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct CreateEscrow<'info> {
+    #[account(init, payer = maker, space = 8 + Escrow::INIT_SPACE,
+        seeds = [ESCROW_SEED, maker.key().as_ref(), &id.to_le_bytes()],
+        bump
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    #[account(init, payer = maker,
+      associated_token::mint = mint,
+      associated_token::authority = escrow,
+      associated_token::token_program = token_program,
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+      token::mint = mint,
+      token::authority = maker,
+      token::token_program = token_program,
+    )]
+    pub maker_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+      mint::token_program = token_program,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: receives the tokens on release, doesn't sign the creation
+    pub receiver: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Deposits `amount` into the escrow vault.
+/// `remaining_accounts` are the transfer hook extra accounts of the deposit.
+pub fn create_escrow<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CreateEscrow<'info>>,
+    id: u64,
+    amount: u64,
+    condition: ReleaseCondition,
+) -> Result<()> {
+    require!(amount > 0, TransferExtensionsError::AmountMustBeGreaterThanZero);
+
+    invoke_transfer_checked(
+        ctx.accounts.token_program.key,
+        ctx.accounts.maker_token_account.to_account_info(),
+        ctx.accounts.mint.to_account_info(),
+        ctx.accounts.vault.to_account_info(),
+        ctx.accounts.maker.to_account_info(),
+        ctx.remaining_accounts,
+        amount,
+        ctx.accounts.mint.decimals,
+        &[],
+    )?;
+    ctx.accounts.vault.reload()?;
+
+    let escrow = &mut ctx.accounts.escrow;
+    escrow.maker = ctx.accounts.maker.key();
+    escrow.receiver = ctx.accounts.receiver.key();
+    escrow.mint = ctx.accounts.mint.key();
+    escrow.id = id;
+    escrow.amount = ctx.accounts.vault.amount;
+    escrow.condition = condition;
+    escrow.bump = ctx.bumps.escrow;
+    escrow.version = CURRENT_ACCOUNT_VERSION;
+    msg!("Escrow {} created with {}", escrow.id, escrow.amount);
+
+    Ok(())
+}
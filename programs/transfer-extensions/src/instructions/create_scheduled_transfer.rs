@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    errors::TransferExtensionsError,
+    seeds::SCHEDULED_TRANSFER_SEED,
+    sol_sdk::{invoke_transfer_checked_with_fee, FeeMode},
+    states::{ExecutionTime, ScheduledTransfer, CURRENT_ACCOUNT_VERSION},
+};
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct CreateScheduledTransfer<'info> {
+    #[account(init, payer = creator, space = 8 + ScheduledTransfer::INIT_SPACE,
+        seeds = [SCHEDULED_TRANSFER_SEED, creator.key().as_ref(), &id.to_le_bytes()],
+        bump
+    )]
+    pub scheduled_transfer: Box<Account<'info, ScheduledTransfer>>,
+
+    #[account(init, payer = creator,
+      associated_token::mint = mint,
+      associated_token::authority = scheduled_transfer,
+      associated_token::token_program = token_program,
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+      token::mint = mint,
+      token::authority = creator,
+      token::token_program = token_program,
+    )]
+    pub creator_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+      token::mint = mint,
+      token::token_program = token_program,
+    )]
+    pub destination_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+      mint::token_program = token_program,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Funds the vault with `amount + tip`. The creator pays the transfer fee of the deposit,
+/// so the vault holds the exact amount. `remaining_accounts` are the transfer hook
+/// extra accounts of the deposit.
+pub fn create_scheduled_transfer<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CreateScheduledTransfer<'info>>,
+    id: u64,
+    amount: u64,
+    tip: u64,
+    execution_time: ExecutionTime,
+) -> Result<()> {
+    require!(amount > 0, TransferExtensionsError::AmountMustBeGreaterThanZero);
+
+    let scheduled_transfer = &mut ctx.accounts.scheduled_transfer;
+    scheduled_transfer.creator = ctx.accounts.creator.key();
+    scheduled_transfer.destination = ctx.accounts.destination_account.key();
+    scheduled_transfer.mint = ctx.accounts.mint.key();
+    scheduled_transfer.id = id;
+    scheduled_transfer.amount = amount;
+    scheduled_transfer.tip = tip;
+    scheduled_transfer.execution_time = execution_time;
+    scheduled_transfer.bump = ctx.bumps.scheduled_transfer;
+    scheduled_transfer.version = CURRENT_ACCOUNT_VERSION;
+    msg!("Scheduled transfer {} of {} created", id, amount);
+
+    invoke_transfer_checked_with_fee(
+        ctx.accounts.token_program.key,
+        ctx.accounts.creator_token_account.to_account_info(),
+        ctx.accounts.mint.to_account_info(),
+        ctx.accounts.vault.to_account_info(),
+        ctx.accounts.creator.to_account_info(),
+        ctx.remaining_accounts,
+        amount
+            .checked_add(tip)
+            .ok_or(TransferExtensionsError::ScheduledAmountOverflow)?,
+        ctx.accounts.mint.decimals,
+        FeeMode::Net,
+        &[],
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    errors::TransferExtensionsError,
+    seeds::PAYMENT_STREAM_SEED,
+    sol_sdk::{invoke_transfer_checked_with_fee, FeeMode},
+    states::{PaymentStream, CURRENT_ACCOUNT_VERSION},
+};
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct CreateStream<'info> {
+    #[account(init, payer = sender, space = 8 + PaymentStream::INIT_SPACE,
+        seeds = [PAYMENT_STREAM_SEED, sender.key().as_ref(), &id.to_le_bytes()],
+        bump
+    )]
+    pub stream: Box<Account<'info, PaymentStream>>,
+
+    #[account(init, payer = sender,
+      associated_token::mint = mint,
+      associated_token::authority = stream,
+      associated_token::token_program = token_program,
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+      token::mint = mint,
+      token::authority = sender,
+      token::token_program = token_program,
+    )]
+    pub sender_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+      mint::token_program = token_program,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: withdraws from the stream, doesn't sign the creation
+    pub recipient: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Deposits the whole stream amount, `rate_per_second * (end - start)`, into the vault.
+/// The sender pays the transfer fee of the deposit, so the vault holds the exact amount.
+/// `remaining_accounts` are the transfer hook extra accounts of the deposit.
+pub fn create_stream<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CreateStream<'info>>,
+    id: u64,
+    rate_per_second: u64,
+    start: i64,
+    end: i64,
+) -> Result<()> {
+    require!(
+        end > start && rate_per_second > 0,
+        TransferExtensionsError::InvalidStreamSchedule
+    );
+
+    let stream = &mut ctx.accounts.stream;
+    stream.sender = ctx.accounts.sender.key();
+    stream.recipient = ctx.accounts.recipient.key();
+    stream.mint = ctx.accounts.mint.key();
+    stream.id = id;
+    stream.rate_per_second = rate_per_second;
+    stream.start = start;
+    stream.end = end;
+    stream.withdrawn = 0;
+    stream.bump = ctx.bumps.stream;
+    stream.version = CURRENT_ACCOUNT_VERSION;
+    let total_amount = stream
+        .total_amount()
+        .ok_or(TransferExtensionsError::StreamAmountOverflow)?;
+    msg!("Stream {} created with {}", id, total_amount);
+
+    invoke_transfer_checked_with_fee(
+        ctx.accounts.token_program.key,
+        ctx.accounts.sender_token_account.to_account_info(),
+        ctx.accounts.mint.to_account_info(),
+        ctx.accounts.vault.to_account_info(),
+        ctx.accounts.sender.to_account_info(),
+        ctx.remaining_accounts,
+        total_amount,
+        ctx.accounts.mint.decimals,
+        FeeMode::Net,
+        &[],
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::TransferExtensionsError,
+    instructions::{execute_transfer_leg, TransferLeg},
+    sol_sdk::FeeMode,
+    states::Splitter,
+};
+
+#[derive(Accounts)]
+pub struct Distribute<'info> {
+    pub splitter: Box<Account<'info, Splitter>>,
+
+    /// Authority of the source account
+    #[account(mut)]
+    pub signer: Signer<'info>,
+}
+
+/// Splits `amount` from the source account between the splitter recipients,
+/// one batch transfer leg per recipient.
+/// `remaining_accounts` hold the source account, the mint, the recipient token accounts,
+/// the token program and the transfer hook extra accounts, as for `batch_transfers`.
+/// The source account is the first of them. Legacy Token mints aren't supported,
+/// their legs would need stats accounts.
+pub fn distribute<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Distribute<'info>>,
+    amount: u64,
+) -> Result<()> {
+    require!(amount > 0, TransferExtensionsError::AmountMustBeGreaterThanZero);
+    let splitter = &ctx.accounts.splitter;
+    let accounts = ctx.remaining_accounts;
+    let mint_index = find_account_index(accounts, &splitter.mint)?;
+
+    let authority = ctx.accounts.signer.to_account_info();
+    for (share, share_amount) in splitter.shares.iter().zip(splitter.split(amount)) {
+        if share_amount == 0 {
+            continue;
+        }
+        msg!("Distribute {} to {}", share_amount, share.token_account);
+        let leg = TransferLeg {
+            source_index: 0,
+            mint_index,
+            destination_index: find_account_index(accounts, &share.token_account)?,
+            amount: share_amount,
+            fee_mode: FeeMode::Gross,
+            stats_indexes: None,
+        };
+        execute_transfer_leg(&authority, accounts, &leg, &[])?;
+    }
+
+    Ok(())
+}
+
+fn find_account_index(accounts: &[AccountInfo], key: &Pubkey) -> Result<u8> {
+    accounts
+        .iter()
+        .position(|account| account.key == key)
+        .ok_or(TransferExtensionsError::MissingSplitAccount)
+        .and_then(|index| {
+            u8::try_from(index).map_err(|_| TransferExtensionsError::InvalidLegAccountIndex)
+        })
+        .map_err(Into::into)
+}
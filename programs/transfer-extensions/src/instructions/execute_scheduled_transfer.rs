@@ -0,0 +1,118 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    errors::TransferExtensionsError,
+    instructions::close_vault,
+    seeds::SCHEDULED_TRANSFER_SEED,
+    sol_sdk::invoke_transfer_checked,
+    states::ScheduledTransfer,
+};
+
+#[derive(Accounts)]
+pub struct ExecuteScheduledTransfer<'info> {
+    #[account(mut, close = creator,
+        has_one = creator,
+        has_one = mint,
+    )]
+    pub scheduled_transfer: Box<Account<'info, ScheduledTransfer>>,
+
+    #[account(mut,
+      associated_token::mint = mint,
+      associated_token::authority = scheduled_transfer,
+      associated_token::token_program = token_program,
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+      address = scheduled_transfer.destination,
+    )]
+    pub destination_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Gets the tip
+    #[account(mut,
+      token::mint = mint,
+      token::token_program = token_program,
+    )]
+    pub cranker_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Gets back the tokens sent to the vault on top of the amount and tip
+    #[account(mut,
+      token::mint = mint,
+      token::authority = creator,
+      token::token_program = token_program,
+    )]
+    pub creator_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Writable to harvest the transfer fees withheld in the vault
+    #[account(mut,
+      mint::token_program = token_program,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Gets the rent of the scheduled transfer and its vault back
+    /// CHECK: checked by `has_one`
+    #[account(mut)]
+    pub creator: UncheckedAccount<'info>,
+
+    /// Anyone, once the transfer is due
+    pub cranker: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Executes a due transfer, pays the tip to the cranker and refunds any other vault
+/// balance to the creator, as the vault can only be closed empty.
+/// `remaining_accounts` are the transfer hook extra accounts of the transfers.
+pub fn execute_scheduled_transfer<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ExecuteScheduledTransfer<'info>>,
+) -> Result<()> {
+    let scheduled_transfer = &ctx.accounts.scheduled_transfer;
+    require!(
+        scheduled_transfer.execution_time.is_due(&Clock::get()?),
+        TransferExtensionsError::ScheduledTransferNotDue
+    );
+    msg!("Execute scheduled transfer {}", scheduled_transfer.id);
+
+    let id = scheduled_transfer.id.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        SCHEDULED_TRANSFER_SEED,
+        scheduled_transfer.creator.as_ref(),
+        &id,
+        &[scheduled_transfer.bump],
+    ]];
+    let payouts = scheduled_transfer
+        .amount
+        .checked_add(scheduled_transfer.tip)
+        .ok_or(TransferExtensionsError::ScheduledAmountOverflow)?;
+    let refund = ctx.accounts.vault.amount.saturating_sub(payouts);
+    for (destination, amount) in [
+        (&ctx.accounts.destination_account, scheduled_transfer.amount),
+        (&ctx.accounts.cranker_token_account, scheduled_transfer.tip),
+        (&ctx.accounts.creator_token_account, refund),
+    ] {
+        if amount == 0 {
+            continue;
+        }
+        invoke_transfer_checked(
+            ctx.accounts.token_program.key,
+            ctx.accounts.vault.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            destination.to_account_info(),
+            scheduled_transfer.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            ctx.accounts.mint.decimals,
+            signer_seeds,
+        )?;
+    }
+
+    close_vault(
+        &ctx.accounts.vault,
+        &ctx.accounts.mint,
+        ctx.accounts.creator.to_account_info(),
+        scheduled_transfer.to_account_info(),
+        &ctx.accounts.token_program,
+        signer_seeds,
+    )
+}
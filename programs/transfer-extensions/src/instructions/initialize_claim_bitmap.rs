@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    seeds::CLAIM_BITMAP_SEED,
+    states::{ClaimBitmap, Distributor, CURRENT_ACCOUNT_VERSION},
+};
+
+#[derive(Accounts)]
+#[instruction(chunk: u64)]
+pub struct InitializeClaimBitmap<'info> {
+    #[account(init, payer = payer, space = 8 + ClaimBitmap::INIT_SPACE,
+        seeds = [CLAIM_BITMAP_SEED, distributor.key().as_ref(), &chunk.to_le_bytes()],
+        bump
+    )]
+    pub claim_bitmap: Box<Account<'info, ClaimBitmap>>,
+
+    pub distributor: Box<Account<'info, Distributor>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Permissionless, a chunk tracks `CLAIMS_PER_BITMAP` leaves of the distributor
+pub fn initialize_claim_bitmap(
+    ctx: Context<InitializeClaimBitmap>,
+    chunk: u64,
+) -> Result<()> {
+    let claim_bitmap = &mut ctx.accounts.claim_bitmap;
+    claim_bitmap.distributor = ctx.accounts.distributor.key();
+    claim_bitmap.chunk = chunk;
+    claim_bitmap.version = CURRENT_ACCOUNT_VERSION;
+
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    seeds::DISTRIBUTOR_SEED,
+    states::{Distributor, CURRENT_ACCOUNT_VERSION},
+};
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct InitializeDistributor<'info> {
+    #[account(init, payer = authority, space = 8 + Distributor::INIT_SPACE,
+        seeds = [DISTRIBUTOR_SEED, authority.key().as_ref(), &id.to_le_bytes()],
+        bump
+    )]
+    pub distributor: Box<Account<'info, Distributor>>,
+
+    /// Funded with regular (hook-aware) transfers
+    #[account(init, payer = authority,
+      associated_token::mint = mint,
+      associated_token::authority = distributor,
+      associated_token::token_program = token_program,
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+      mint::token_program = token_program,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_distributor(
+    ctx: Context<InitializeDistributor>,
+    id: u64,
+    merkle_root: [u8; 32],
+) -> Result<()> {
+    let distributor = &mut ctx.accounts.distributor;
+    distributor.authority = ctx.accounts.authority.key();
+    distributor.mint = ctx.accounts.mint.key();
+    distributor.id = id;
+    distributor.merkle_root = merkle_root;
+    distributor.bump = ctx.bumps.distributor;
+    distributor.version = CURRENT_ACCOUNT_VERSION;
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token_interface::Mint;
+
+use crate::{
+    errors::TransferExtensionsError,
+    seeds::OWNER_POLICY_SEED,
+    states::{OwnerPolicy, CURRENT_ACCOUNT_VERSION},
+};
+
+#[derive(Accounts)]
+pub struct InitializeOwnerPolicy<'info> {
+    #[account(init, payer = authority, space = 8 + OwnerPolicy::INIT_SPACE,
+        seeds = [OWNER_POLICY_SEED, wallet.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub owner_policy: Box<Account<'info, OwnerPolicy>>,
+
+    #[account(
+      mint::token_program = anchor_spl::token_interface::spl_token_2022::id(),
+      constraint = mint.mint_authority == COption::Some(authority.key())
+        @ TransferExtensionsError::NotMintAuthority,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: token account owner the policy applies to, doesn't have to sign
+    pub wallet: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Owner policies are only enforced if the extra account meta list of the mint
+/// is initialized with owner policies, wallets without one aren't limited
+pub fn initialize_owner_policy(
+    ctx: Context<InitializeOwnerPolicy>,
+    max_transfer_amount: u64,
+) -> Result<()> {
+    let owner_policy = &mut ctx.accounts.owner_policy;
+    owner_policy.wallet = ctx.accounts.wallet.key();
+    owner_policy.mint = ctx.accounts.mint.key();
+    owner_policy.authority = ctx.accounts.authority.key();
+    owner_policy.max_transfer_amount = max_transfer_amount;
+    owner_policy.version = CURRENT_ACCOUNT_VERSION;
+
+    Ok(())
+}
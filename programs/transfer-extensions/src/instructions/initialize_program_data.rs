@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 
-use crate::{seeds::GLOBAL_PROGRAM_DATA_SEED, states::GlobalProgramData};
+use crate::{seeds::GLOBAL_PROGRAM_DATA_SEED, states::{GlobalProgramData, CURRENT_ACCOUNT_VERSION}};
 
 
 #[derive(Accounts)]
@@ -17,8 +17,9 @@ pub struct InitializeProgramData<'info> {
 
 
 pub fn initialize_program_data(
-    _ctx: Context<InitializeProgramData>,
+    ctx: Context<InitializeProgramData>,
 ) -> Result<()> {
-    msg!("Greetings from: {:?}", _ctx.program_id);
+    msg!("Greetings from: {:?}", ctx.program_id);
+    ctx.accounts.program_counter.version = CURRENT_ACCOUNT_VERSION;
     Ok(())
 }
\ No newline at end of file
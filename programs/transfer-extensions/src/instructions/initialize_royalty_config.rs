@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::{
+    errors::TransferExtensionsError,
+    seeds::ROYALTY_CONFIG_SEED,
+    states::{RoyaltyConfig, CURRENT_ACCOUNT_VERSION},
+};
+
+#[derive(Accounts)]
+pub struct InitializeRoyaltyConfig<'info> {
+    #[account(init, payer = authority, space = 8 + RoyaltyConfig::INIT_SPACE,
+        seeds = [ROYALTY_CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub royalty_config: Box<Account<'info, RoyaltyConfig>>,
+
+    #[account(
+      mint::token_program = anchor_spl::token_interface::spl_token_2022::id(),
+      constraint = mint.mint_authority == COption::Some(authority.key())
+        @ TransferExtensionsError::NotMintAuthority,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+      token::mint = mint,
+    )]
+    pub creator_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Royalties are only accrued if the extra account meta list of the mint
+/// is initialized with royalties
+pub fn initialize_royalty_config(
+    ctx: Context<InitializeRoyaltyConfig>,
+    bps: u16,
+) -> Result<()> {
+    require!(bps <= 10_000, TransferExtensionsError::InvalidRoyaltyBps);
+
+    let royalty_config = &mut ctx.accounts.royalty_config;
+    royalty_config.mint = ctx.accounts.mint.key();
+    royalty_config.authority = ctx.accounts.authority.key();
+    royalty_config.creator_vault = ctx.accounts.creator_vault.key();
+    royalty_config.bps = bps;
+    royalty_config.version = CURRENT_ACCOUNT_VERSION;
+
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::{
+    errors::TransferExtensionsError,
+    seeds::SPLITTER_SEED,
+    states::{SplitShare, Splitter, CURRENT_ACCOUNT_VERSION, MAX_SPLIT_RECIPIENTS, TOTAL_SHARE_BPS},
+};
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct InitializeSplitter<'info> {
+    #[account(init, payer = authority, space = 8 + Splitter::INIT_SPACE,
+        seeds = [SPLITTER_SEED, authority.key().as_ref(), &id.to_le_bytes()],
+        bump
+    )]
+    pub splitter: Box<Account<'info, Splitter>>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_splitter(
+    ctx: Context<InitializeSplitter>,
+    id: u64,
+    shares: Vec<SplitShare>,
+    remainder_index: u8,
+) -> Result<()> {
+    require!(
+        (1..=MAX_SPLIT_RECIPIENTS).contains(&shares.len())
+            && shares.iter().map(|share| share.bps as u32).sum::<u32>() == TOTAL_SHARE_BPS as u32,
+        TransferExtensionsError::InvalidSplitShares
+    );
+    require!(
+        (remainder_index as usize) < shares.len(),
+        TransferExtensionsError::InvalidRemainderIndex
+    );
+
+    let splitter = &mut ctx.accounts.splitter;
+    splitter.authority = ctx.accounts.authority.key();
+    splitter.mint = ctx.accounts.mint.key();
+    splitter.id = id;
+    splitter.shares = shares;
+    splitter.remainder_index = remainder_index;
+    splitter.bump = ctx.bumps.splitter;
+    splitter.version = CURRENT_ACCOUNT_VERSION;
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_lang::Discriminator;
+
+use crate::{
+    errors::TransferExtensionsError,
+    states::{
+        load_versioned, ClaimBitmap, Distributor, Escrow, GlobalProgramData, OwnerPolicy,
+        PaymentStream, RoyaltyConfig, ScheduledTransfer, Splitter, Versioned,
+        CURRENT_ACCOUNT_VERSION,
+    },
+};
+
+
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    /// CHECK: any state account of the program, its type is taken from the discriminator
+    #[account(mut, owner = crate::ID)]
+    pub account: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+
+pub fn migrate_account(
+    ctx: Context<MigrateAccount>,
+) -> Result<()> {
+    let discriminator: [u8; 8] = ctx
+        .accounts
+        .account
+        .try_borrow_data()?
+        .get(..8)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(TransferExtensionsError::UnknownAccountType)?;
+
+    match discriminator {
+        GlobalProgramData::DISCRIMINATOR => migrate::<GlobalProgramData>(ctx.accounts),
+        Escrow::DISCRIMINATOR => migrate::<Escrow>(ctx.accounts),
+        PaymentStream::DISCRIMINATOR => migrate::<PaymentStream>(ctx.accounts),
+        ScheduledTransfer::DISCRIMINATOR => migrate::<ScheduledTransfer>(ctx.accounts),
+        Splitter::DISCRIMINATOR => migrate::<Splitter>(ctx.accounts),
+        Distributor::DISCRIMINATOR => migrate::<Distributor>(ctx.accounts),
+        ClaimBitmap::DISCRIMINATOR => migrate::<ClaimBitmap>(ctx.accounts),
+        RoyaltyConfig::DISCRIMINATOR => migrate::<RoyaltyConfig>(ctx.accounts),
+        OwnerPolicy::DISCRIMINATOR => migrate::<OwnerPolicy>(ctx.accounts),
+        _ => err!(TransferExtensionsError::UnknownAccountType),
+    }
+}
+
+fn migrate<T: Versioned>(accounts: &MigrateAccount) -> Result<()> {
+    let account = accounts.account.to_account_info();
+    let mut state = load_versioned::<T>(&account)?;
+    require!(
+        state.version() < CURRENT_ACCOUNT_VERSION,
+        TransferExtensionsError::AccountAlreadyMigrated
+    );
+    state.migrate();
+
+    let space = 8 + T::INIT_SPACE;
+    if account.data_len() < space {
+        let extra_lamports = Rent::get()?
+            .minimum_balance(space)
+            .saturating_sub(account.lamports());
+        if extra_lamports > 0 {
+            transfer(
+                CpiContext::new(
+                    accounts.system_program.to_account_info(),
+                    Transfer {
+                        from: accounts.payer.to_account_info(),
+                        to: account.clone(),
+                    },
+                ),
+                extra_lamports,
+            )?;
+        }
+        account.realloc(space, true)?;
+    }
+
+    let mut data = account.try_borrow_mut_data()?;
+    state.try_serialize(&mut &mut data[..])?;
+
+    Ok(())
+}
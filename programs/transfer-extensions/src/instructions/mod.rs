@@ -1,20 +1,77 @@
 pub mod initialize_program_data;
 pub use initialize_program_data::*;
 
-pub mod initialize_wallet_counter_in;
-pub use initialize_wallet_counter_in::*;
+pub mod record_transfer;
+pub use record_transfer::*;
 
-pub mod initialize_wallet_counter_out;
-pub use initialize_wallet_counter_out::*;
+pub mod migrate_account;
+pub use migrate_account::*;
 
-pub mod initialize_mint_counter_in;
-pub use initialize_mint_counter_in::*;
-
-pub mod initialize_mint_counter_out;
-pub use initialize_mint_counter_out::*;
+pub mod close_legacy_counters;
+pub use close_legacy_counters::*;
 
 pub mod multi_transfers;
 pub use multi_transfers::*;
 
+pub mod batch_transfers;
+pub use batch_transfers::*;
+
+pub mod settle;
+pub use settle::*;
+
+pub mod create_escrow;
+pub use create_escrow::*;
+
+pub mod release_escrow;
+pub use release_escrow::*;
+
+pub mod cancel_escrow;
+pub use cancel_escrow::*;
+
+pub mod create_stream;
+pub use create_stream::*;
+
+pub mod withdraw_from_stream;
+pub use withdraw_from_stream::*;
+
+pub mod cancel_stream;
+pub use cancel_stream::*;
+
+pub mod create_scheduled_transfer;
+pub use create_scheduled_transfer::*;
+
+pub mod execute_scheduled_transfer;
+pub use execute_scheduled_transfer::*;
+
+pub mod cancel_scheduled_transfer;
+pub use cancel_scheduled_transfer::*;
+
+pub mod initialize_splitter;
+pub use initialize_splitter::*;
+
+pub mod distribute;
+pub use distribute::*;
+
+pub mod initialize_distributor;
+pub use initialize_distributor::*;
+
+pub mod initialize_claim_bitmap;
+pub use initialize_claim_bitmap::*;
+
+pub mod claim;
+pub use claim::*;
+
+pub mod initialize_royalty_config;
+pub use initialize_royalty_config::*;
+
+pub mod settle_royalties;
+pub use settle_royalties::*;
+
+pub mod initialize_owner_policy;
+pub use initialize_owner_policy::*;
+
+pub mod update_owner_policy;
+pub use update_owner_policy::*;
+
 pub mod multi_transfers_heap;
 pub use multi_transfers_heap::*;
@@ -1,8 +1,12 @@
 use anchor_lang::prelude::*;
 // use anchor_spl::token_2022::spl_token_2022::onchain::invoke_transfer_checked;
-use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
-use crate::{errors::TransferExtensionsError, sol_sdk::invoke_transfer_checked};
+use crate::{
+    errors::TransferExtensionsError,
+    instructions::{record_transfer_without_hook, RecordTransferAccounts},
+    sol_sdk::{invoke_transfer_checked_with_fee, log_heap_usage, FeeMode},
+};
 
 #[derive(Accounts)]
 pub struct MultiTransfers<'info> {
@@ -30,16 +34,21 @@ pub struct MultiTransfers<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
 
-    #[account(
-      constraint = token_program.key() == anchor_spl::token_interface::spl_token_2022::id(),
-    )]
-    pub token_program: Program<'info, Token2022>,
+    /// Token-2022 or legacy Token program
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
+/// For Token-2022 mints `remaining_accounts` are the transfer hook extra accounts of both legs,
+/// split in halves. No hook fires for legacy Token mints, so stats are recorded through the
+/// transfer hook program from `remaining_accounts`: source stats, destination 1 stats,
+/// destination 2 stats, mint stats, the global stats shard of the source owner,
+/// the transfer hook program and the transfer recorder PDA.
 pub fn multi_transfers<'info>(
-    ctx: Context<'_, '_, '_, 'info, MultiTransfers<'info>>,
+    ctx: Context<'_, '_, 'info, 'info, MultiTransfers<'info>>,
     amount1: u64,
     amount2: u64,
+    fee_mode1: FeeMode,
+    fee_mode2: FeeMode,
 ) -> Result<()> {
     msg!("Multi transfers");
     require!(
@@ -50,10 +59,15 @@ pub fn multi_transfers<'info>(
     let mint = &ctx.accounts.mint;
     let decimals = mint.decimals;
 
-    let split_at_pos = ctx.remaining_accounts.len() / 2;
+    let is_legacy_token = ctx.accounts.token_program.key() == anchor_spl::token::ID;
+    let split_at_pos = if is_legacy_token {
+        0
+    } else {
+        ctx.remaining_accounts.len() / 2
+    };
     msg!("Invoke transfer 1");
     msg!("Source balance: {}", ctx.accounts.source_account.amount);
-    invoke_transfer_checked(
+    invoke_transfer_checked_with_fee(
         ctx.accounts.token_program.key,
         ctx.accounts.source_account.to_account_info().clone(),
         mint.to_account_info().clone(),
@@ -62,13 +76,18 @@ pub fn multi_transfers<'info>(
         &ctx.remaining_accounts[..split_at_pos],
         amount1,
         decimals,
+        fee_mode1,
         &[],
     )?;
 
-    let remaining_accounts2 = &ctx.remaining_accounts[split_at_pos..];
+    let remaining_accounts2 = if is_legacy_token {
+        &[]
+    } else {
+        &ctx.remaining_accounts[split_at_pos..]
+    };
 
     msg!("Invoke transfer 2");
-    invoke_transfer_checked(
+    invoke_transfer_checked_with_fee(
         ctx.accounts.token_program.key,
         ctx.accounts.source_account.to_account_info().clone(),
         mint.to_account_info().clone(),
@@ -77,8 +96,44 @@ pub fn multi_transfers<'info>(
         remaining_accounts2,
         amount2,
         decimals,
+        fee_mode2,
         &[],
     )?;
 
+    if is_legacy_token {
+        let [
+            source_stats,
+            destination_1_stats,
+            destination_2_stats,
+            mint_stats,
+            global_stats_shard,
+            transfer_hook_program,
+            transfer_recorder,
+        ] = ctx.remaining_accounts
+        else {
+            return err!(TransferExtensionsError::MissingStatsAccounts);
+        };
+        for (destination, destination_stats, amount) in [
+            (&ctx.accounts.destination_account_1, destination_1_stats, amount1),
+            (&ctx.accounts.destination_account_2, destination_2_stats, amount2),
+        ] {
+            record_transfer_without_hook(
+                &ctx.accounts.source_account.to_account_info(),
+                &destination.to_account_info(),
+                &mint.to_account_info(),
+                &RecordTransferAccounts {
+                    source_stats,
+                    destination_stats,
+                    mint_stats,
+                    global_stats_shard,
+                    transfer_hook_program,
+                    transfer_recorder,
+                },
+                amount,
+            )?;
+        }
+    }
+
+    log_heap_usage();
     Ok(())
 }
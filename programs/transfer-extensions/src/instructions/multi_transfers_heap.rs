@@ -1,7 +1,11 @@
 use anchor_lang::prelude::*;
 // use anchor_spl::token_2022::spl_token_2022::onchain::invoke_transfer_checked;
 
-use crate::{errors::TransferExtensionsError, sol_sdk::invoke_transfer_checked, MultiTransfers};
+use crate::{
+    errors::TransferExtensionsError,
+    sol_sdk::{invoke_transfer_checked, log_heap_usage},
+    MultiTransfers,
+};
 
 pub fn multi_transfers_heap<'info>(
     ctx: Context<'_, '_, '_, 'info, MultiTransfers<'info>>,
@@ -36,5 +40,6 @@ pub fn multi_transfers_heap<'info>(
     heap_data[0] = 1;
     heap_data[10333] = 3;
 
+    log_heap_usage();
     Ok(())
 }
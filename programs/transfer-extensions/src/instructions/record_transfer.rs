@@ -0,0 +1,95 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+};
+use anchor_spl::token_2022::spl_token_2022::extension::{transfer_hook, StateWithExtensions};
+
+use crate::{
+    errors::TransferExtensionsError,
+    seeds::{HOOK_AUTHORITY_SEED, TRANSFER_RECORDER_SEED},
+};
+
+
+/// Transfer hook program owning the TransferStats. It depends on this crate,
+/// so its id and the `record_transfer` discriminator are mirrored here.
+pub const TRANSFER_HOOK_PROGRAM_ID: Pubkey = pubkey!("14KA3wb3jtHft5MLy59VCJAAVDbCAduDydUAKDCEnipV");
+pub const RECORD_TRANSFER_DISCRIMINATOR: [u8; 8] = [186, 5, 70, 146, 90, 18, 74, 248];
+
+/// Accounts of the transfer hook `record_transfer`, besides the transferred token accounts
+pub struct RecordTransferAccounts<'a, 'info> {
+    pub source_stats: &'a AccountInfo<'info>,
+    pub destination_stats: &'a AccountInfo<'info>,
+    pub mint_stats: &'a AccountInfo<'info>,
+    pub global_stats_shard: &'a AccountInfo<'info>,
+    pub transfer_hook_program: &'a AccountInfo<'info>,
+    /// PDA of this program signing the record
+    pub transfer_recorder: &'a AccountInfo<'info>,
+}
+
+/// Records a transfer of a mint without transfer hook (legacy Token program). The stats are
+/// owned by the transfer hook program, which checks them against the token accounts.
+/// Token-2022 transfers are recorded by the hook itself, never through this program,
+/// so this program is never re-entered from a transfer it started.
+pub fn record_transfer_without_hook<'info>(
+    source: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    accounts: &RecordTransferAccounts<'_, 'info>,
+    amount: u64,
+) -> Result<()> {
+    require_keys_eq!(
+        accounts.transfer_hook_program.key(),
+        TRANSFER_HOOK_PROGRAM_ID,
+        ErrorCode::InvalidProgramId
+    );
+    let (recorder, bump) = Pubkey::find_program_address(&[TRANSFER_RECORDER_SEED], &crate::ID);
+    require_keys_eq!(accounts.transfer_recorder.key(), recorder, ErrorCode::ConstraintSeeds);
+
+    let mut data = RECORD_TRANSFER_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&amount.to_le_bytes());
+    let instruction = Instruction {
+        program_id: TRANSFER_HOOK_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(accounts.source_stats.key(), false),
+            AccountMeta::new(accounts.destination_stats.key(), false),
+            AccountMeta::new(accounts.mint_stats.key(), false),
+            AccountMeta::new(accounts.global_stats_shard.key(), false),
+            AccountMeta::new_readonly(source.key(), false),
+            AccountMeta::new_readonly(destination.key(), false),
+            AccountMeta::new_readonly(mint.key(), false),
+            AccountMeta::new_readonly(recorder, true),
+        ],
+        data,
+    };
+    invoke_signed(
+        &instruction,
+        &[
+            accounts.source_stats.clone(),
+            accounts.destination_stats.clone(),
+            accounts.mint_stats.clone(),
+            accounts.global_stats_shard.clone(),
+            source.clone(),
+            destination.clone(),
+            mint.clone(),
+            accounts.transfer_recorder.clone(),
+            accounts.transfer_hook_program.clone(),
+        ],
+        &[&[TRANSFER_RECORDER_SEED, &[bump]]],
+    )?;
+
+    Ok(())
+}
+
+/// PDA of the mint transfer hook program which signs its calls to this program
+pub fn get_hook_authority(mint_info: &AccountInfo) -> Result<Pubkey> {
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint =
+        StateWithExtensions::<anchor_spl::token_2022::spl_token_2022::state::Mint>::unpack(
+            &mint_data,
+        )?;
+    let program_id = transfer_hook::get_program_id(&mint)
+        .ok_or(TransferExtensionsError::MintWithoutTransferHook)?;
+
+    Ok(Pubkey::find_program_address(&[HOOK_AUTHORITY_SEED], &program_id).0)
+}
@@ -0,0 +1,168 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{transfer_fee::TransferFeeAmount, BaseStateWithExtensions, StateWithExtensions},
+};
+use anchor_spl::token_2022_extensions::{
+    harvest_withheld_tokens_to_mint, HarvestWithheldTokensToMint,
+};
+use anchor_spl::token_interface::{
+    close_account, CloseAccount, Mint, TokenAccount, TokenInterface,
+};
+
+use crate::{
+    errors::TransferExtensionsError,
+    seeds::ESCROW_SEED,
+    sol_sdk::invoke_transfer_checked,
+    states::{Escrow, ReleaseCondition},
+};
+
+#[derive(Accounts)]
+pub struct ReleaseEscrow<'info> {
+    #[account(mut, close = maker,
+        has_one = maker,
+        has_one = mint,
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    #[account(mut,
+      associated_token::mint = mint,
+      associated_token::authority = escrow,
+      associated_token::token_program = token_program,
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+      token::mint = mint,
+      token::authority = escrow.receiver,
+      token::token_program = token_program,
+    )]
+    pub receiver_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Writable to harvest the transfer fees withheld in the vault
+    #[account(mut,
+      mint::token_program = token_program,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Gets the rent of the escrow and its vault back
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    /// The maker, the arbiter or, past a timelock, anyone
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Transfers the vault to the receiver and closes the escrow.
+/// `remaining_accounts` are the transfer hook extra accounts of the release.
+pub fn release_escrow<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ReleaseEscrow<'info>>,
+) -> Result<()> {
+    let escrow = &ctx.accounts.escrow;
+    let authority = ctx.accounts.authority.key();
+    let can_release = authority == escrow.maker
+        || match escrow.condition {
+            ReleaseCondition::Timelock { release_at } => {
+                Clock::get()?.unix_timestamp >= release_at
+            }
+            ReleaseCondition::CounterpartySignature => false,
+            ReleaseCondition::Arbiter { arbiter } => authority == arbiter,
+        };
+    require!(can_release, TransferExtensionsError::EscrowReleaseNotAllowed);
+    msg!("Release escrow {}", escrow.id);
+
+    pay_out_escrow(
+        escrow,
+        &ctx.accounts.vault,
+        &ctx.accounts.mint,
+        ctx.accounts.receiver_token_account.to_account_info(),
+        ctx.accounts.maker.to_account_info(),
+        &ctx.accounts.token_program,
+        ctx.remaining_accounts,
+    )
+}
+
+/// Transfers the whole vault balance to `destination` with a hook-aware transfer
+/// signed by the escrow PDA, then closes the vault
+pub fn pay_out_escrow<'info>(
+    escrow: &Account<'info, Escrow>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    destination: AccountInfo<'info>,
+    maker: AccountInfo<'info>,
+    token_program: &Interface<'info, TokenInterface>,
+    additional_accounts: &[AccountInfo<'info>],
+) -> Result<()> {
+    let id = escrow.id.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[ESCROW_SEED, escrow.maker.as_ref(), &id, &[escrow.bump]]];
+
+    if vault.amount > 0 {
+        invoke_transfer_checked(
+            token_program.key,
+            vault.to_account_info(),
+            mint.to_account_info(),
+            destination,
+            escrow.to_account_info(),
+            additional_accounts,
+            vault.amount,
+            mint.decimals,
+            signer_seeds,
+        )?;
+    }
+
+    close_vault(
+        vault,
+        mint,
+        maker,
+        escrow.to_account_info(),
+        token_program,
+        signer_seeds,
+    )
+}
+
+/// Closes a PDA-owned vault, harvesting first the transfer fees withheld in it:
+/// a Token-2022 account can't be closed while it holds any
+pub fn close_vault<'info>(
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    destination: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    token_program: &Interface<'info, TokenInterface>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    if withheld_amount(&vault.to_account_info())? > 0 {
+        harvest_withheld_tokens_to_mint(
+            CpiContext::new(
+                token_program.to_account_info(),
+                HarvestWithheldTokensToMint {
+                    token_program_id: token_program.to_account_info(),
+                    mint: mint.to_account_info(),
+                },
+            ),
+            vec![vault.to_account_info()],
+        )?;
+    }
+
+    close_account(CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        CloseAccount {
+            account: vault.to_account_info(),
+            destination,
+            authority,
+        },
+        signer_seeds,
+    ))
+}
+
+fn withheld_amount(vault_info: &AccountInfo) -> Result<u64> {
+    if *vault_info.owner != spl_token_2022::ID {
+        return Ok(0);
+    }
+    let vault_data = vault_info.try_borrow_data()?;
+    let vault = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&vault_data)?;
+    Ok(vault
+        .get_extension::<TransferFeeAmount>()
+        .map_or(0, |fee_amount| u64::from(fee_amount.withheld_amount)))
+}
@@ -0,0 +1,97 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{self, extension::StateWithExtensions};
+
+use crate::{
+    errors::TransferExtensionsError,
+    instructions::{execute_transfer_leg, get_leg_account, TransferLeg},
+};
+
+/// Transfer leg signed by its own authority, an index into `remaining_accounts`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct SettlementLeg {
+    pub authority_index: u8,
+    pub transfer: TransferLeg,
+}
+
+/// Expected balance change of a token account after all legs are executed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct NetPosition {
+    pub token_account_index: u8,
+    pub delta: i64,
+}
+
+/// Leg authorities sign through `remaining_accounts`, anyone can submit the settlement.
+/// Anchor can't generate a CPI client for it, so the `cpi` feature doesn't build.
+#[derive(Accounts)]
+pub struct Settle {}
+
+/// `remaining_accounts` hold the accounts of all legs, including their authorities,
+/// as for `batch_transfers`.
+/// Executes the legs atomically and checks the balance deltas against the netting table.
+/// Every token account moved by a leg must have its net position in the table.
+pub fn settle<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Settle>,
+    legs: Vec<SettlementLeg>,
+    net_positions: Vec<NetPosition>,
+) -> Result<()> {
+    msg!("Settle: {} legs, {} net positions", legs.len(), net_positions.len());
+    require!(
+        legs.iter().all(|leg| leg.transfer.amount > 0),
+        TransferExtensionsError::AmountMustBeGreaterThanZero
+    );
+    let accounts = ctx.remaining_accounts;
+
+    let mut netted_keys: Vec<Pubkey> = Vec::with_capacity(net_positions.len());
+    for position in net_positions.iter() {
+        let key = get_leg_account(accounts, position.token_account_index)?.key();
+        require!(
+            !netted_keys.contains(&key),
+            TransferExtensionsError::DuplicateNetPosition
+        );
+        netted_keys.push(key);
+    }
+    for leg in legs.iter() {
+        for index in [leg.transfer.source_index, leg.transfer.destination_index] {
+            require!(
+                netted_keys.contains(get_leg_account(accounts, index)?.key),
+                TransferExtensionsError::MissingNetPosition
+            );
+        }
+    }
+
+    let balances_before = net_positions
+        .iter()
+        .map(|position| token_balance(get_leg_account(accounts, position.token_account_index)?))
+        .collect::<Result<Vec<_>>>()?;
+
+    for leg in legs.iter() {
+        let authority = get_leg_account(accounts, leg.authority_index)?;
+        if !authority.is_signer {
+            return Err(Error::from(ErrorCode::AccountNotSigner).with_account_name("authority"));
+        }
+        execute_transfer_leg(authority, accounts, &leg.transfer, &[])?;
+    }
+
+    for (position, balance_before) in net_positions.iter().zip(balances_before) {
+        let balance_after =
+            token_balance(get_leg_account(accounts, position.token_account_index)?)?;
+        let delta = balance_after as i128 - balance_before as i128;
+        require!(
+            delta == position.delta as i128,
+            TransferExtensionsError::NetPositionMismatch
+        );
+    }
+
+    Ok(())
+}
+
+fn token_balance(info: &AccountInfo) -> Result<u64> {
+    require!(
+        *info.owner == spl_token_2022::ID || *info.owner == anchor_spl::token::ID,
+        TransferExtensionsError::InvalidTokenProgram
+    );
+    let data = info.try_borrow_data()?;
+    Ok(StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data)?
+        .base
+        .amount)
+}
@@ -0,0 +1,106 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    errors::TransferExtensionsError,
+    instructions::TRANSFER_HOOK_PROGRAM_ID,
+    seeds::{ROYALTY_CONFIG_SEED, ROYALTY_DEBT_SEED},
+    sol_sdk::{invoke_transfer_checked_with_fee, FeeMode},
+    states::RoyaltyConfig,
+};
+
+
+/// RoyaltyDebt of the transfer hook program, which depends on this crate,
+/// so its discriminator and the leading fields of its layout are mirrored here
+pub const ROYALTY_DEBT_DISCRIMINATOR: [u8; 8] = [237, 51, 234, 80, 153, 138, 81, 103];
+
+#[derive(AnchorDeserialize)]
+struct RoyaltyDebtHeader {
+    wallet: Pubkey,
+    _mint: Pubkey,
+    owed: u64,
+}
+
+#[derive(Accounts)]
+pub struct SettleRoyalties<'info> {
+    #[account(
+        has_one = creator_vault,
+        seeds = [ROYALTY_CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub royalty_config: Box<Account<'info, RoyaltyConfig>>,
+
+    /// CHECK: RoyaltyDebt of the transfer hook program, checked by `read_royalty_debt`,
+    /// settled by the hook during the payment
+    #[account(mut,
+        seeds = [ROYALTY_DEBT_SEED, wallet.key().as_ref(), mint.key().as_ref()],
+        bump,
+        seeds::program = TRANSFER_HOOK_PROGRAM_ID,
+    )]
+    pub royalty_debt: UncheckedAccount<'info>,
+
+    #[account(mut,
+      token::mint = mint,
+      token::authority = wallet,
+      token::token_program = token_program,
+    )]
+    pub payer_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub creator_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+      mint::token_program = token_program,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub wallet: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Pays everything owed to the creator vault. The transfer hook settles the debt when it
+/// sees the payment, transfers to the creator vault accrue no royalty.
+/// The wallet pays the transfer fee on top, the vault receives exactly what is owed.
+/// `remaining_accounts` are the transfer hook extra accounts of the payment.
+pub fn settle_royalties<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SettleRoyalties<'info>>,
+) -> Result<()> {
+    let owed = read_royalty_debt(&ctx.accounts.royalty_debt, ctx.accounts.wallet.key)?;
+    require!(owed > 0, TransferExtensionsError::NoRoyaltyOwed);
+    msg!("Settle royalties: {}", owed);
+
+    invoke_transfer_checked_with_fee(
+        ctx.accounts.token_program.key,
+        ctx.accounts.payer_token_account.to_account_info(),
+        ctx.accounts.mint.to_account_info(),
+        ctx.accounts.creator_vault.to_account_info(),
+        ctx.accounts.wallet.to_account_info(),
+        ctx.remaining_accounts,
+        owed,
+        ctx.accounts.mint.decimals,
+        FeeMode::Net,
+        &[],
+    )?;
+
+    Ok(())
+}
+
+/// Royalties owed by `wallet`
+fn read_royalty_debt(royalty_debt: &AccountInfo, wallet: &Pubkey) -> Result<u64> {
+    require!(
+        !royalty_debt.data_is_empty(),
+        TransferExtensionsError::RoyaltyDebtNotInitialized
+    );
+    require_keys_eq!(*royalty_debt.owner, TRANSFER_HOOK_PROGRAM_ID, ErrorCode::ConstraintOwner);
+    let data = royalty_debt.try_borrow_data()?;
+    require!(
+        data.get(..8) == Some(&ROYALTY_DEBT_DISCRIMINATOR[..]),
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    let header = RoyaltyDebtHeader::deserialize(&mut &data[8..])
+        .map_err(|_| ErrorCode::AccountDidNotDeserialize)?;
+    require_keys_eq!(header.wallet, *wallet, ErrorCode::ConstraintHasOne);
+
+    Ok(header.owed)
+}
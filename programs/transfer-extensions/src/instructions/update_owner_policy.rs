@@ -0,0 +1,23 @@
+use anchor_lang::prelude::*;
+
+use crate::{seeds::OWNER_POLICY_SEED, states::OwnerPolicy};
+
+#[derive(Accounts)]
+pub struct UpdateOwnerPolicy<'info> {
+    #[account(mut,
+        seeds = [OWNER_POLICY_SEED, owner_policy.wallet.as_ref(), owner_policy.mint.as_ref()],
+        bump,
+        has_one = authority,
+    )]
+    pub owner_policy: Box<Account<'info, OwnerPolicy>>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn update_owner_policy(
+    ctx: Context<UpdateOwnerPolicy>,
+    max_transfer_amount: u64,
+) -> Result<()> {
+    ctx.accounts.owner_policy.max_transfer_amount = max_transfer_amount;
+    Ok(())
+}
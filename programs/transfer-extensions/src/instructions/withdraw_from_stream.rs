@@ -0,0 +1,74 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    errors::TransferExtensionsError,
+    seeds::PAYMENT_STREAM_SEED,
+    sol_sdk::invoke_transfer_checked,
+    states::PaymentStream,
+};
+
+#[derive(Accounts)]
+pub struct WithdrawFromStream<'info> {
+    #[account(mut,
+        has_one = recipient,
+        has_one = mint,
+    )]
+    pub stream: Box<Account<'info, PaymentStream>>,
+
+    #[account(mut,
+      associated_token::mint = mint,
+      associated_token::authority = stream,
+      associated_token::token_program = token_program,
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+      token::mint = mint,
+      token::authority = recipient,
+      token::token_program = token_program,
+    )]
+    pub recipient_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+      mint::token_program = token_program,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub recipient: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Transfers everything streamed and not yet withdrawn to the recipient.
+/// `remaining_accounts` are the transfer hook extra accounts of the withdrawal.
+pub fn withdraw_from_stream<'info>(
+    ctx: Context<'_, '_, 'info, 'info, WithdrawFromStream<'info>>,
+) -> Result<()> {
+    let stream = &ctx.accounts.stream;
+    let amount = stream
+        .withdrawable_amount(Clock::get()?.unix_timestamp)
+        .ok_or(TransferExtensionsError::StreamAmountOverflow)?;
+    require!(amount > 0, TransferExtensionsError::NothingToWithdraw);
+    msg!("Withdraw {} from stream {}", amount, stream.id);
+
+    let id = stream.id.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] =
+        &[&[PAYMENT_STREAM_SEED, stream.sender.as_ref(), &id, &[stream.bump]]];
+    invoke_transfer_checked(
+        ctx.accounts.token_program.key,
+        ctx.accounts.vault.to_account_info(),
+        ctx.accounts.mint.to_account_info(),
+        ctx.accounts.recipient_token_account.to_account_info(),
+        stream.to_account_info(),
+        ctx.remaining_accounts,
+        amount,
+        ctx.accounts.mint.decimals,
+        signer_seeds,
+    )?;
+
+    let stream = &mut ctx.accounts.stream;
+    stream.withdrawn = stream.withdrawn.checked_add(amount).unwrap();
+
+    Ok(())
+}
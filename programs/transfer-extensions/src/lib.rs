@@ -5,6 +5,8 @@ pub mod states;
 pub mod seeds;
 pub mod errors;
 pub mod sol_sdk;
+use sol_sdk::FeeMode;
+use states::{ExecutionTime, ReleaseCondition, SplitShare};
 
 declare_id!("4MNxsMM7niQkurWFyDvzhVbD3wHQFyAhnGjrvuYPi6Zu");
 
@@ -18,36 +20,159 @@ pub mod transfer_extensions {
         instructions::initialize_program_data(ctx)
     }
 
-    pub fn initialize_wallet_counter_in(
-        ctx: Context<InitializeWalletCounterIn>,
+    pub fn migrate_account(
+        ctx: Context<MigrateAccount>,
     ) -> Result<()> {
-        instructions::initialize_wallet_counter_in(ctx)
+        instructions::migrate_account(ctx)
     }
 
-    pub fn initialize_wallet_counter_out(
-        ctx: Context<InitializeWalletCounterOut>,
+    pub fn close_legacy_counters(
+        ctx: Context<CloseLegacyCounters>,
     ) -> Result<()> {
-        instructions::initialize_wallet_counter_out(ctx)
+        instructions::close_legacy_counters(ctx)
     }
 
-    pub fn initialize_mint_counter_in(
-        ctx: Context<InitializeMintCounterIn>,
+    pub fn multi_transfers<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MultiTransfers<'info>>,
+        amount1: u64,
+        amount2: u64,
+        fee_mode1: FeeMode,
+        fee_mode2: FeeMode,
     ) -> Result<()> {
-        instructions::initialize_mint_counter_in(ctx)
+        instructions::multi_transfers(ctx, amount1, amount2, fee_mode1, fee_mode2)
     }
 
-    pub fn initialize_mint_counter_out(
-        ctx: Context<InitializeMintCounterOut>,
+    pub fn batch_transfers<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchTransfers<'info>>,
+        legs: Vec<TransferLeg>,
     ) -> Result<()> {
-        instructions::initialize_mint_counter_out(ctx)
+        instructions::batch_transfers(ctx, legs)
     }
 
-    pub fn multi_transfers<'info>(
-        ctx: Context<'_, '_, '_, 'info, MultiTransfers<'info>>,
-        amount1: u64,
-        amount2: u64,
+    pub fn settle<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Settle>,
+        legs: Vec<SettlementLeg>,
+        net_positions: Vec<NetPosition>,
+    ) -> Result<()> {
+        instructions::settle(ctx, legs, net_positions)
+    }
+
+    pub fn create_escrow<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreateEscrow<'info>>,
+        id: u64,
+        amount: u64,
+        condition: ReleaseCondition,
+    ) -> Result<()> {
+        instructions::create_escrow(ctx, id, amount, condition)
+    }
+
+    pub fn release_escrow<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReleaseEscrow<'info>>,
+    ) -> Result<()> {
+        instructions::release_escrow(ctx)
+    }
+
+    pub fn cancel_escrow<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CancelEscrow<'info>>,
+    ) -> Result<()> {
+        instructions::cancel_escrow(ctx)
+    }
+
+    pub fn create_stream<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreateStream<'info>>,
+        id: u64,
+        rate_per_second: u64,
+        start: i64,
+        end: i64,
+    ) -> Result<()> {
+        instructions::create_stream(ctx, id, rate_per_second, start, end)
+    }
+
+    pub fn withdraw_from_stream<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WithdrawFromStream<'info>>,
+    ) -> Result<()> {
+        instructions::withdraw_from_stream(ctx)
+    }
+
+    pub fn cancel_stream<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CancelStream<'info>>,
+    ) -> Result<()> {
+        instructions::cancel_stream(ctx)
+    }
+
+    pub fn create_scheduled_transfer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreateScheduledTransfer<'info>>,
+        id: u64,
+        amount: u64,
+        tip: u64,
+        execution_time: ExecutionTime,
+    ) -> Result<()> {
+        instructions::create_scheduled_transfer(ctx, id, amount, tip, execution_time)
+    }
+
+    pub fn execute_scheduled_transfer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteScheduledTransfer<'info>>,
     ) -> Result<()> {
-        instructions::multi_transfers(ctx, amount1, amount2)
+        instructions::execute_scheduled_transfer(ctx)
+    }
+
+    pub fn cancel_scheduled_transfer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CancelScheduledTransfer<'info>>,
+    ) -> Result<()> {
+        instructions::cancel_scheduled_transfer(ctx)
+    }
+
+    pub fn initialize_splitter(
+        ctx: Context<InitializeSplitter>,
+        id: u64,
+        shares: Vec<SplitShare>,
+        remainder_index: u8,
+    ) -> Result<()> {
+        instructions::initialize_splitter(ctx, id, shares, remainder_index)
+    }
+
+    pub fn distribute<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Distribute<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::distribute(ctx, amount)
+    }
+
+    pub fn initialize_distributor(
+        ctx: Context<InitializeDistributor>,
+        id: u64,
+        merkle_root: [u8; 32],
+    ) -> Result<()> {
+        instructions::initialize_distributor(ctx, id, merkle_root)
+    }
+
+    pub fn initialize_claim_bitmap(
+        ctx: Context<InitializeClaimBitmap>,
+        chunk: u64,
+    ) -> Result<()> {
+        instructions::initialize_claim_bitmap(ctx, chunk)
+    }
+
+    pub fn claim<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Claim<'info>>,
+        index: u64,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::claim(ctx, index, amount, proof)
+    }
+
+    pub fn initialize_royalty_config(
+        ctx: Context<InitializeRoyaltyConfig>,
+        bps: u16,
+    ) -> Result<()> {
+        instructions::initialize_royalty_config(ctx, bps)
+    }
+
+    pub fn settle_royalties<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SettleRoyalties<'info>>,
+    ) -> Result<()> {
+        instructions::settle_royalties(ctx)
     }
 
     pub fn multi_transfers_heap<'info>(
@@ -57,6 +182,20 @@ pub mod transfer_extensions {
     ) -> Result<()> {
         instructions::multi_transfers_heap(ctx, amount1, amount2)
     }
+
+    pub fn initialize_owner_policy(
+        ctx: Context<InitializeOwnerPolicy>,
+        max_transfer_amount: u64,
+    ) -> Result<()> {
+        instructions::initialize_owner_policy(ctx, max_transfer_amount)
+    }
+
+    pub fn update_owner_policy(
+        ctx: Context<UpdateOwnerPolicy>,
+        max_transfer_amount: u64,
+    ) -> Result<()> {
+        instructions::update_owner_policy(ctx, max_transfer_amount)
+    }
 }
 
 
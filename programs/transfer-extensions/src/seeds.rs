@@ -1,3 +1,57 @@
+use spl_tlv_account_resolution::seeds::Seed;
+
 pub const GLOBAL_PROGRAM_DATA_SEED: &[u8] = b"global-program-data";
+/// Seeds of the legacy counters, replaced by the transfer hook program TransferStats
 pub const COUNTER_IN_SEED: &[u8] = b"counter-in";
 pub const COUNTER_OUT_SEED: &[u8] = b"counter-out";
+pub const GLOBAL_STATS_SHARD_SEED: &[u8] = b"global-stats-shard";
+pub const TRANSFER_STATS_SEED: &[u8] = b"transfer-stats";
+/// Seed of the transfer hook program PDA allowed to close the legacy counters it migrated
+pub const HOOK_AUTHORITY_SEED: &[u8] = b"hook-authority";
+/// Seed of the PDA signing the stats of legacy Token program transfers,
+/// recorded by the transfer hook program
+pub const TRANSFER_RECORDER_SEED: &[u8] = b"transfer-recorder";
+pub const ESCROW_SEED: &[u8] = b"escrow";
+pub const PAYMENT_STREAM_SEED: &[u8] = b"payment-stream";
+pub const SCHEDULED_TRANSFER_SEED: &[u8] = b"scheduled-transfer";
+pub const SPLITTER_SEED: &[u8] = b"splitter";
+pub const DISTRIBUTOR_SEED: &[u8] = b"distributor";
+pub const CLAIM_BITMAP_SEED: &[u8] = b"claim-bitmap";
+pub const ROYALTY_CONFIG_SEED: &[u8] = b"royalty-config";
+/// Seed of the RoyaltyDebt, owned by the transfer hook program
+pub const ROYALTY_DEBT_SEED: &[u8] = b"royalty-debt";
+pub const OWNER_POLICY_SEED: &[u8] = b"owner-policy";
+
+/// Token account data offset of the owner
+pub const TOKEN_ACCOUNT_OWNER_OFFSET: u8 = 32;
+/// Token account data offset of the delegate, after its `COption` tag
+pub const TOKEN_ACCOUNT_DELEGATE_OFFSET: u8 = 76;
+/// Transfer hook execute instruction data offset of the amount, after the discriminator
+pub const EXECUTE_AMOUNT_OFFSET: u8 = 8;
+
+/// Extra account meta seed of the owner of the token account at `account_index`
+pub fn token_account_owner_seed(account_index: u8) -> Seed {
+    Seed::AccountData {
+        account_index,
+        data_index: TOKEN_ACCOUNT_OWNER_OFFSET,
+        length: 32,
+    }
+}
+
+/// Extra account meta seed of the delegate of the token account at `account_index`,
+/// the default pubkey when it has none
+pub fn token_account_delegate_seed(account_index: u8) -> Seed {
+    Seed::AccountData {
+        account_index,
+        data_index: TOKEN_ACCOUNT_DELEGATE_OFFSET,
+        length: 32,
+    }
+}
+
+/// Extra account meta seed of the transferred amount, in little endian
+pub fn transfer_amount_seed() -> Seed {
+    Seed::InstructionData {
+        index: EXECUTE_AMOUNT_OFFSET,
+        length: 8,
+    }
+}
@@ -1,5 +1,8 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token_2022::spl_token_2022::extension::{transfer_hook, StateWithExtensions};
+use anchor_spl::token_2022::spl_token_2022::extension::transfer_fee::{self, TransferFeeConfig};
+use anchor_spl::token_2022::spl_token_2022::extension::{
+    transfer_hook, BaseStateWithExtensions, StateWithExtensions,
+};
 use anchor_spl::token_2022::spl_token_2022::instruction;
 use anchor_spl::token_2022::spl_token_2022::solana_program::entrypoint::ProgramResult;
 use anchor_spl::token_2022::spl_token_2022::solana_program::program::invoke_signed;
@@ -25,7 +28,7 @@ pub fn invoke_transfer_checked<'a>(
     decimals: u8,
     seeds: &[&[&[u8]]],
 ) -> ProgramResult {
-    let mut cpi_instruction = instruction::transfer_checked(
+    let cpi_instruction = instruction::transfer_checked(
         token_program_id,
         source_info.key,
         mint_info.key,
@@ -36,6 +39,136 @@ pub fn invoke_transfer_checked<'a>(
         decimals,
     )?;
 
+    invoke_with_extra_accounts(
+        cpi_instruction,
+        source_info,
+        mint_info,
+        destination_info,
+        authority_info,
+        additional_accounts,
+        amount,
+        seeds,
+    )
+}
+
+/// How the fee of a mint with the TransferFeeConfig extension is applied to a transfer
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FeeMode {
+    /// `amount` leaves the source, the destination receives `amount - fee`
+    #[default]
+    Gross,
+    /// The destination receives `amount`, the source pays `amount + fee`
+    Net,
+}
+
+/// Same as `invoke_transfer_checked`, but for mints with the TransferFeeConfig extension
+/// issues `transfer_checked_with_fee` with the fee of the current epoch
+#[allow(clippy::too_many_arguments)]
+pub fn invoke_transfer_checked_with_fee<'a>(
+    token_program_id: &Pubkey,
+    source_info: AccountInfo<'a>,
+    mint_info: AccountInfo<'a>,
+    destination_info: AccountInfo<'a>,
+    authority_info: AccountInfo<'a>,
+    additional_accounts: &[AccountInfo<'a>],
+    amount: u64,
+    decimals: u8,
+    fee_mode: FeeMode,
+    seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let transfer_amounts = {
+        let mint_data = mint_info.try_borrow_data()?;
+        let mint =
+            StateWithExtensions::<anchor_spl::token_2022::spl_token_2022::state::Mint>::unpack(
+                &mint_data,
+            )?;
+        match mint.get_extension::<TransferFeeConfig>() {
+            Ok(config) => Some(calculate_transfer_amounts(
+                config,
+                Clock::get()?.epoch,
+                amount,
+                fee_mode,
+            )?),
+            Err(_) => None,
+        }
+    };
+    let Some((transfer_amount, fee)) = transfer_amounts else {
+        return invoke_transfer_checked(
+            token_program_id,
+            source_info,
+            mint_info,
+            destination_info,
+            authority_info,
+            additional_accounts,
+            amount,
+            decimals,
+            seeds,
+        );
+    };
+
+    let cpi_instruction = transfer_fee::instruction::transfer_checked_with_fee(
+        token_program_id,
+        source_info.key,
+        mint_info.key,
+        destination_info.key,
+        authority_info.key,
+        &[], // add them later, to avoid unnecessary clones
+        transfer_amount,
+        decimals,
+        fee,
+    )?;
+
+    invoke_with_extra_accounts(
+        cpi_instruction,
+        source_info,
+        mint_info,
+        destination_info,
+        authority_info,
+        additional_accounts,
+        transfer_amount,
+        seeds,
+    )
+}
+
+/// Returns the amount leaving the source and the fee withheld from it
+pub fn calculate_transfer_amounts(
+    config: &TransferFeeConfig,
+    epoch: u64,
+    amount: u64,
+    fee_mode: FeeMode,
+) -> std::result::Result<(u64, u64), ProgramError> {
+    match fee_mode {
+        FeeMode::Gross => {
+            let fee = config
+                .calculate_epoch_fee(epoch, amount)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            Ok((amount, fee))
+        }
+        FeeMode::Net => {
+            let fee = config
+                .calculate_inverse_epoch_fee(epoch, amount)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            let gross_amount = amount
+                .checked_add(fee)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            Ok((gross_amount, fee))
+        }
+    }
+}
+
+/// Adds multisig signers and the transfer hook extra accounts to a token-2022
+/// transfer instruction and invokes it
+#[allow(clippy::too_many_arguments)]
+fn invoke_with_extra_accounts<'a>(
+    mut cpi_instruction: Instruction,
+    source_info: AccountInfo<'a>,
+    mint_info: AccountInfo<'a>,
+    destination_info: AccountInfo<'a>,
+    authority_info: AccountInfo<'a>,
+    additional_accounts: &[AccountInfo<'a>],
+    amount: u64,
+    seeds: &[&[&[u8]]],
+) -> ProgramResult {
     let mut cpi_account_infos = vec![
         source_info.clone(),
         mint_info.clone(),
@@ -54,8 +187,9 @@ pub fn invoke_transfer_checked<'a>(
                 .push(AccountMeta::new_readonly(*ai.key, ai.is_signer));
         });
     msg!("====!! Going to add extra accounts !!====");
-    // scope the borrowing to avoid a double-borrow during CPI
-    {
+    // scope the borrowing to avoid a double-borrow during CPI,
+    // legacy Token program mints have no extensions, so no transfer hook to resolve
+    if cpi_instruction.program_id != anchor_spl::token::ID {
         let mint_data = mint_info.try_borrow_data()?;
         let mint =
             StateWithExtensions::<anchor_spl::token_2022::spl_token_2022::state::Mint>::unpack(
@@ -288,7 +422,7 @@ pub fn add_to_cpi_instruction<'a>(
 // }
 
 /// De-escalate an account meta if necessary
-fn de_escalate_account_meta(account_meta: &mut AccountMeta, account_metas: &[AccountMeta]) {
+pub fn de_escalate_account_meta(account_meta: &mut AccountMeta, account_metas: &[AccountMeta]) {
     // This is a little tricky to read, but the idea is to see if
     // this account is marked as writable or signer anywhere in
     // the instruction at the start. If so, DON'T escalate it to
@@ -312,3 +446,24 @@ fn de_escalate_account_meta(account_meta: &mut AccountMeta, account_metas: &[Acc
         }
     }
 }
+
+/// Logs the heap bytes allocated so far by this invocation, read by the compute unit
+/// benchmark. The default bump allocator keeps its position at the start of the heap
+/// and allocates downwards from the end of it.
+#[cfg(all(feature = "heap-usage", target_os = "solana"))]
+pub fn log_heap_usage() {
+    use solana_program::entrypoint::{HEAP_LENGTH, HEAP_START_ADDRESS};
+
+    let heap_end = HEAP_START_ADDRESS as usize + HEAP_LENGTH;
+    // SAFETY: the first word of the heap is reserved for the allocator position
+    let position = unsafe { *(HEAP_START_ADDRESS as *const usize) };
+    let used = if position == 0 {
+        0
+    } else {
+        heap_end - position
+    };
+    msg!("Heap used: {}", used);
+}
+
+#[cfg(not(all(feature = "heap-usage", target_os = "solana")))]
+pub fn log_heap_usage() {}
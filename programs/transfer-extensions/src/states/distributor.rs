@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+
+use super::RESERVED_SPACE;
+
+/// Bytes of the bitmap of a claim bitmap chunk
+pub const CLAIM_BITMAP_BYTES: usize = 1024;
+/// Claims tracked by one claim bitmap chunk
+pub const CLAIMS_PER_BITMAP: u64 = CLAIM_BITMAP_BYTES as u64 * 8;
+
+/// Airdrop of `mint` tokens held in the distributor vault (its associated token account).
+/// Leaves of the Merkle tree are `keccak(index, claimant, amount)`.
+#[account()]
+#[derive(InitSpace)]
+pub struct Distributor {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub id: u64,
+    pub merkle_root: [u8; 32],
+    pub claimed_count: u64,
+    pub claimed_amount: u64,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; RESERVED_SPACE],
+}
+
+/// Claimed flags of `CLAIMS_PER_BITMAP` distributor leaves, from `chunk * CLAIMS_PER_BITMAP` on
+#[account()]
+#[derive(InitSpace)]
+pub struct ClaimBitmap {
+    pub distributor: Pubkey,
+    pub chunk: u64,
+    pub bits: [u8; CLAIM_BITMAP_BYTES],
+    pub version: u8,
+    pub reserved: [u8; RESERVED_SPACE],
+}
+
+impl ClaimBitmap {
+    pub fn is_claimed(&self, index: u64) -> bool {
+        let bit = index % CLAIMS_PER_BITMAP;
+        self.bits[(bit / 8) as usize] & (1 << (bit % 8)) != 0
+    }
+
+    pub fn set_claimed(&mut self, index: u64) {
+        let bit = index % CLAIMS_PER_BITMAP;
+        self.bits[(bit / 8) as usize] |= 1 << (bit % 8);
+    }
+}
@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+
+use super::RESERVED_SPACE;
+
+/// What, besides the maker signature, releases an escrow to the receiver
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum ReleaseCondition {
+    /// Anyone can release once `release_at` (unix timestamp) is reached
+    Timelock { release_at: i64 },
+    /// Only the maker releases, e.g. after the counter-party delivered off-chain
+    CounterpartySignature,
+    /// The arbiter releases or cancels
+    Arbiter { arbiter: Pubkey },
+}
+
+/// Tokens of `mint` held in the escrow vault (its associated token account) for `receiver`.
+/// Cancellation returns them to the maker and needs the receiver (or arbiter) signature.
+#[account()]
+#[derive(InitSpace)]
+pub struct Escrow {
+    pub maker: Pubkey,
+    pub receiver: Pubkey,
+    pub mint: Pubkey,
+    pub id: u64,
+    /// Amount received by the vault at creation, transfer fees excluded
+    pub amount: u64,
+    pub condition: ReleaseCondition,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; RESERVED_SPACE],
+}
@@ -1,7 +1,11 @@
 use anchor_lang::prelude::*;
 
+use super::RESERVED_SPACE;
+
 #[account()]
 #[derive(Default, InitSpace)]
 pub struct GlobalProgramData {
     pub transfers_count: u64,
+    pub version: u8,
+    pub reserved: [u8; RESERVED_SPACE],
 }
\ No newline at end of file
@@ -1,14 +1,38 @@
-pub mod global_program_data;
-pub use global_program_data::*;
+pub mod distributor;
+pub use distributor::*;
 
-pub mod wallet_counter_in;
-pub use wallet_counter_in::*;
+pub mod escrow;
+pub use escrow::*;
 
-pub mod wallet_counter_out;
-pub use wallet_counter_out::*;
+pub mod global_program_data;
+pub use global_program_data::*;
 
 pub mod mint_counter_in;
 pub use mint_counter_in::*;
 
 pub mod mint_counter_out;
 pub use mint_counter_out::*;
+
+pub mod owner_policy;
+pub use owner_policy::*;
+
+pub mod payment_stream;
+pub use payment_stream::*;
+
+pub mod royalty;
+pub use royalty::*;
+
+pub mod scheduled_transfer;
+pub use scheduled_transfer::*;
+
+pub mod splitter;
+pub use splitter::*;
+
+pub mod versioned;
+pub use versioned::*;
+
+pub mod wallet_counter_in;
+pub use wallet_counter_in::*;
+
+pub mod wallet_counter_out;
+pub use wallet_counter_out::*;
@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+use super::RESERVED_SPACE;
+use crate::errors::TransferExtensionsError;
+
+/// Transfer policy of a wallet (token account owner) for `mint`, set by the mint authority.
+/// Only enforced if the extra account meta list of the mint is initialized with owner policies.
+#[account()]
+#[derive(InitSpace)]
+pub struct OwnerPolicy {
+    pub wallet: Pubkey,
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    /// Largest amount leaving a token account of the wallet in one transfer
+    pub max_transfer_amount: u64,
+    pub version: u8,
+    pub reserved: [u8; RESERVED_SPACE],
+}
+
+impl OwnerPolicy {
+    pub fn check_transfer(&self, amount: u64) -> Result<()> {
+        require_gte!(
+            self.max_transfer_amount,
+            amount,
+            TransferExtensionsError::OwnerPolicyLimitExceeded
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+use super::RESERVED_SPACE;
+
+/// Tokens of `mint` streamed from `sender` to `recipient` at `rate_per_second`
+/// between `start` and `end` (unix timestamps), held in the stream vault
+/// (its associated token account) until withdrawn
+#[account()]
+#[derive(InitSpace)]
+pub struct PaymentStream {
+    pub sender: Pubkey,
+    pub recipient: Pubkey,
+    pub mint: Pubkey,
+    pub id: u64,
+    pub rate_per_second: u64,
+    pub start: i64,
+    pub end: i64,
+    pub withdrawn: u64,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; RESERVED_SPACE],
+}
+
+impl PaymentStream {
+    pub fn total_amount(&self) -> Option<u64> {
+        self.streamed_amount(self.end)
+    }
+
+    /// Amount streamed to the recipient by `now`, withdrawn or not.
+    /// `None` when the elapsed time or the amount overflows.
+    pub fn streamed_amount(&self, now: i64) -> Option<u64> {
+        // nothing is streamed before the start, nor by a schedule ending before it
+        let elapsed = now.min(self.end).checked_sub(self.start)?.max(0);
+        self.rate_per_second.checked_mul(u64::try_from(elapsed).ok()?)
+    }
+
+    pub fn withdrawable_amount(&self, now: i64) -> Option<u64> {
+        self.streamed_amount(now)?.checked_sub(self.withdrawn)
+    }
+}
@@ -0,0 +1,23 @@
+use anchor_lang::prelude::*;
+
+use super::RESERVED_SPACE;
+
+/// Royalty owed on every transfer of `mint`, in basis points of the transferred amount.
+/// Transfers to `creator_vault` (royalty settlements) are exempt.
+/// Accrued by the transfer hook program in the RoyaltyDebt of the source owner.
+#[account()]
+#[derive(InitSpace)]
+pub struct RoyaltyConfig {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub creator_vault: Pubkey,
+    pub bps: u16,
+    pub version: u8,
+    pub reserved: [u8; RESERVED_SPACE],
+}
+
+impl RoyaltyConfig {
+    pub fn royalty(&self, amount: u64) -> u64 {
+        (amount as u128 * self.bps as u128 / 10_000) as u64
+    }
+}
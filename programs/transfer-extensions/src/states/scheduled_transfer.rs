@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use super::RESERVED_SPACE;
+
+/// When a scheduled transfer becomes due
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum ExecutionTime {
+    Slot { slot: u64 },
+    Timestamp { unix_timestamp: i64 },
+}
+
+impl ExecutionTime {
+    pub fn is_due(&self, clock: &Clock) -> bool {
+        match *self {
+            ExecutionTime::Slot { slot } => clock.slot >= slot,
+            ExecutionTime::Timestamp { unix_timestamp } => clock.unix_timestamp >= unix_timestamp,
+        }
+    }
+}
+
+/// Transfer of `amount` from the vault (the associated token account of this PDA)
+/// to `destination`, executed by anyone once due for a `tip` from the same vault
+#[account()]
+#[derive(InitSpace)]
+pub struct ScheduledTransfer {
+    pub creator: Pubkey,
+    pub destination: Pubkey,
+    pub mint: Pubkey,
+    pub id: u64,
+    pub amount: u64,
+    pub tip: u64,
+    pub execution_time: ExecutionTime,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; RESERVED_SPACE],
+}
@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+
+use super::RESERVED_SPACE;
+
+/// Maximum number of recipients of a splitter
+pub const MAX_SPLIT_RECIPIENTS: usize = 10;
+/// Sum of the shares of all recipients
+pub const TOTAL_SHARE_BPS: u16 = 10_000;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct SplitShare {
+    pub token_account: Pubkey,
+    pub bps: u16,
+}
+
+/// Revenue split of `mint` payments between recipient token accounts
+#[account()]
+#[derive(InitSpace)]
+pub struct Splitter {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub id: u64,
+    #[max_len(MAX_SPLIT_RECIPIENTS)]
+    pub shares: Vec<SplitShare>,
+    /// Recipient getting the rounding remainder
+    pub remainder_index: u8,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; RESERVED_SPACE],
+}
+
+impl Splitter {
+    /// Amounts of every recipient: shares are rounded down
+    /// and the remainder goes to the recipient at `remainder_index`
+    pub fn split(&self, amount: u64) -> Vec<u64> {
+        let mut amounts: Vec<u64> = self
+            .shares
+            .iter()
+            .map(|share| (amount as u128 * share.bps as u128 / TOTAL_SHARE_BPS as u128) as u64)
+            .collect();
+        let remainder = amount - amounts.iter().sum::<u64>();
+        amounts[self.remainder_index as usize] += remainder;
+        amounts
+    }
+}
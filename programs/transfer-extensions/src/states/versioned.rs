@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use super::{
+    ClaimBitmap, Distributor, Escrow, GlobalProgramData, MintCounterIn, MintCounterOut,
+    OwnerPolicy, PaymentStream, RoyaltyConfig, ScheduledTransfer, Splitter,
+    WalletCounterIn, WalletCounterOut,
+};
+
+/// Layout version written by the current program
+pub const CURRENT_ACCOUNT_VERSION: u8 = 1;
+/// Bytes kept at the end of every state account for future fields
+pub const RESERVED_SPACE: usize = 32;
+
+/// State account which layout can evolve.
+/// New fields are only ever appended, so an older layout is a prefix of the current one.
+pub trait Versioned:
+    AccountSerialize + AccountDeserialize + Discriminator + Owner + Space
+{
+    /// Account size (with discriminator) before the `version` field was introduced
+    const LEGACY_SPACE: usize;
+
+    fn version(&self) -> u8;
+
+    /// Transforms data loaded from an older layout into the current one
+    fn migrate(&mut self);
+}
+
+/// Implements `Versioned` for a state with a `version: u8` field,
+/// also used by the transfer hook program for the accounts it owns
+#[macro_export]
+macro_rules! impl_versioned {
+    ($state:ty, $legacy_space:expr) => {
+        impl $crate::states::Versioned for $state {
+            const LEGACY_SPACE: usize = $legacy_space;
+
+            fn version(&self) -> u8 {
+                self.version
+            }
+
+            fn migrate(&mut self) {
+                self.version = $crate::states::CURRENT_ACCOUNT_VERSION;
+            }
+        }
+    };
+}
+
+impl_versioned!(GlobalProgramData, 8 + 8);
+// Replaced by the TransferStats of the transfer hook program, only read to migrate them
+impl_versioned!(MintCounterIn, 8 + 8 + 32);
+impl_versioned!(MintCounterOut, 8 + 8 + 32);
+impl_versioned!(WalletCounterIn, 8 + 8 + 32);
+impl_versioned!(WalletCounterOut, 8 + 8 + 32);
+// Created with a versioned layout from the start
+impl_versioned!(Escrow, 8 + Escrow::INIT_SPACE);
+impl_versioned!(PaymentStream, 8 + PaymentStream::INIT_SPACE);
+impl_versioned!(ScheduledTransfer, 8 + ScheduledTransfer::INIT_SPACE);
+impl_versioned!(Splitter, 8 + Splitter::INIT_SPACE);
+impl_versioned!(Distributor, 8 + Distributor::INIT_SPACE);
+impl_versioned!(ClaimBitmap, 8 + ClaimBitmap::INIT_SPACE);
+impl_versioned!(RoyaltyConfig, 8 + RoyaltyConfig::INIT_SPACE);
+impl_versioned!(OwnerPolicy, 8 + OwnerPolicy::INIT_SPACE);
+
+/// Loads a state account stored with either a legacy or the current layout.
+/// Fields missing in a legacy account are read as zeroes (so `version` is 0).
+pub fn load_versioned<T: Versioned>(info: &AccountInfo) -> Result<T> {
+    if *info.owner != T::owner() {
+        return Err(Error::from(ErrorCode::AccountOwnedByWrongProgram)
+            .with_pubkeys((*info.owner, T::owner())));
+    }
+    let data = info.try_borrow_data()?;
+    let space = 8 + T::INIT_SPACE;
+    if data.len() >= space {
+        return T::try_deserialize(&mut &data[..]);
+    }
+    require_gte!(data.len(), T::LEGACY_SPACE, ErrorCode::AccountDidNotDeserialize);
+    let mut padded = data.to_vec();
+    padded.resize(space, 0);
+    T::try_deserialize(&mut &padded[..])
+}
+
+/// Writes back a state account loaded with `load_versioned`, keeping its layout:
+/// an account that isn't migrated yet only gets its legacy fields stored.
+pub fn store_versioned<T: Versioned>(state: &T, info: &AccountInfo) -> Result<()> {
+    let mut buffer = Vec::with_capacity(8 + T::INIT_SPACE);
+    state.try_serialize(&mut buffer)?;
+    let mut data = info.try_borrow_mut_data()?;
+    let len = data.len().min(buffer.len());
+    data[..len].copy_from_slice(&buffer[..len]);
+    Ok(())
+}
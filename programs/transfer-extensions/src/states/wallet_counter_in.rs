@@ -1,8 +1,14 @@
 use anchor_lang::prelude::*;
 
+use super::RESERVED_SPACE;
+
+/// Legacy incoming transfers counter of a token account or owner, only kept readable
+/// until the transfer hook program migrates it into a TransferStats
 #[account()]
 #[derive(Default, InitSpace)]
 pub struct WalletCounterIn {
     pub transfers_count: u64,
     pub owner: Pubkey,
+    pub version: u8,
+    pub reserved: [u8; RESERVED_SPACE],
 }
\ No newline at end of file
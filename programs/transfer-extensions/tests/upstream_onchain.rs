@@ -0,0 +1,569 @@
+//! `sol_sdk::invoke_transfer_checked` against `spl_token_2022::onchain::invoke_transfer_checked`:
+//! both are run on the same account sets and must issue the same CPI, with the same
+//! account infos in the same order, or fail with the same error.
+//! The CPI is captured by syscall stubs instead of being executed.
+
+use std::cell::RefCell;
+use std::sync::Once;
+
+use anchor_lang::solana_program::account_info::AccountInfo;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::solana_program::program_stubs::{self, SyscallStubs};
+use anchor_lang::solana_program::pubkey::Pubkey;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        transfer_hook::TransferHook, BaseStateWithExtensionsMut, ExtensionType,
+        StateWithExtensionsMut,
+    },
+    onchain,
+    state::{Account, AccountState, Mint},
+};
+use spl_tlv_account_resolution::account::ExtraAccountMeta;
+use spl_tlv_account_resolution::error::AccountResolutionError;
+use spl_tlv_account_resolution::seeds::Seed;
+use spl_tlv_account_resolution::state::ExtraAccountMetaList;
+use spl_transfer_hook_interface::error::TransferHookError;
+use spl_transfer_hook_interface::get_extra_account_metas_address;
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+use transfer_extensions::seeds::{
+    token_account_delegate_seed, token_account_owner_seed, transfer_amount_seed,
+};
+use transfer_extensions::sol_sdk;
+
+const AMOUNT: u64 = 1_000;
+const DECIMALS: u8 = 9;
+
+/// Key and privileges of an account info handed to the CPI
+type AccountInfoKey = (Pubkey, bool, bool);
+
+thread_local! {
+    static INVOKED: RefCell<Vec<(Instruction, Vec<AccountInfoKey>)>> = const { RefCell::new(vec![]) };
+}
+
+/// Records the CPIs of the current thread
+struct CaptureStubs;
+
+impl SyscallStubs for CaptureStubs {
+    fn sol_log(&self, _message: &str) {}
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        _signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        INVOKED.with_borrow_mut(|invoked| {
+            invoked.push((
+                instruction.clone(),
+                account_infos
+                    .iter()
+                    .map(|info| (*info.key, info.is_signer, info.is_writable))
+                    .collect(),
+            ))
+        });
+        Ok(())
+    }
+}
+
+fn take_invoked() -> Vec<(Instruction, Vec<AccountInfoKey>)> {
+    INVOKED.with_borrow_mut(std::mem::take)
+}
+
+struct TestAccount {
+    key: Pubkey,
+    owner: Pubkey,
+    lamports: u64,
+    data: Vec<u8>,
+    is_signer: bool,
+    is_writable: bool,
+}
+
+impl TestAccount {
+    fn new(key: Pubkey, data: Vec<u8>, is_signer: bool, is_writable: bool) -> Self {
+        Self {
+            key,
+            owner: spl_token_2022::ID,
+            lamports: 1,
+            data,
+            is_signer,
+            is_writable,
+        }
+    }
+
+    fn info(&mut self) -> AccountInfo<'_> {
+        AccountInfo::new(
+            &self.key,
+            self.is_signer,
+            self.is_writable,
+            &mut self.lamports,
+            &mut self.data,
+            &self.owner,
+            false,
+            0,
+        )
+    }
+}
+
+fn mint_data(hook_program_id: Option<Pubkey>) -> Vec<u8> {
+    let extensions: &[ExtensionType] = match hook_program_id {
+        Some(_) => &[ExtensionType::TransferHook],
+        None => &[],
+    };
+    let mut data = vec![0; ExtensionType::try_calculate_account_len::<Mint>(extensions).unwrap()];
+    let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+    if let Some(program_id) = hook_program_id {
+        let extension = state.init_extension::<TransferHook>(true).unwrap();
+        extension.program_id = Some(program_id).try_into().unwrap();
+        state.init_account_type().unwrap();
+    }
+    state.base = Mint {
+        mint_authority: COption::None,
+        supply: AMOUNT,
+        decimals: DECIMALS,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    };
+    state.pack_base();
+    data
+}
+
+fn token_account_data(mint: &Pubkey, owner: &Pubkey) -> Vec<u8> {
+    let mut data = vec![0; Account::LEN];
+    Account {
+        mint: *mint,
+        owner: *owner,
+        amount: AMOUNT,
+        state: AccountState::Initialized,
+        ..Account::default()
+    }
+    .pack_into_slice(&mut data);
+    data
+}
+
+fn meta_list_data(metas: &[ExtraAccountMeta]) -> Vec<u8> {
+    let mut data = vec![0; ExtraAccountMetaList::size_of(metas.len()).unwrap()];
+    ExtraAccountMetaList::init::<ExecuteInstruction>(&mut data, metas).unwrap();
+    data
+}
+
+/// Keys of a hook-enabled transfer of `owner` and the extra accounts its meta list resolves to
+struct HookKeys {
+    source: Pubkey,
+    mint: Pubkey,
+    destination: Pubkey,
+    owner: Pubkey,
+    hook_program_id: Pubkey,
+    meta_list: Pubkey,
+    /// Fixed extra account
+    fixed: Pubkey,
+    /// `["counter", source]` PDA of the hook program
+    source_pda: Pubkey,
+    /// PDA of the hook program seeded with the owner read from the source account data
+    owner_pda: Pubkey,
+}
+
+impl HookKeys {
+    fn new() -> Self {
+        let source = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let hook_program_id = Pubkey::new_unique();
+        Self {
+            source,
+            mint,
+            destination: Pubkey::new_unique(),
+            owner,
+            hook_program_id,
+            meta_list: get_extra_account_metas_address(&mint, &hook_program_id),
+            fixed: Pubkey::new_unique(),
+            source_pda: Pubkey::find_program_address(
+                &[b"counter", source.as_ref()],
+                &hook_program_id,
+            )
+            .0,
+            owner_pda: Pubkey::find_program_address(&[owner.as_ref()], &hook_program_id).0,
+        }
+    }
+
+    /// The authority is asked writable, above its privileges in the transfer
+    fn extra_account_metas(&self) -> Vec<ExtraAccountMeta> {
+        vec![
+            ExtraAccountMeta::new_with_pubkey(&self.fixed, false, false).unwrap(),
+            ExtraAccountMeta::new_with_seeds(
+                &[
+                    Seed::Literal {
+                        bytes: b"counter".to_vec(),
+                    },
+                    Seed::AccountKey { index: 0 },
+                ],
+                false,
+                true,
+            )
+            .unwrap(),
+            ExtraAccountMeta::new_with_seeds(
+                &[Seed::AccountData {
+                    account_index: 0,
+                    data_index: 32,
+                    length: 32,
+                }],
+                false,
+                true,
+            )
+            .unwrap(),
+            ExtraAccountMeta::new_with_pubkey(&self.owner, true, true).unwrap(),
+        ]
+    }
+
+    /// Accounts of the transfer followed by the hook accounts
+    fn accounts(&self) -> Vec<TestAccount> {
+        vec![
+            TestAccount::new(
+                self.source,
+                token_account_data(&self.mint, &self.owner),
+                false,
+                true,
+            ),
+            TestAccount::new(
+                self.mint,
+                mint_data(Some(self.hook_program_id)),
+                false,
+                false,
+            ),
+            TestAccount::new(
+                self.destination,
+                token_account_data(&self.mint, &Pubkey::new_unique()),
+                false,
+                true,
+            ),
+            TestAccount::new(self.owner, vec![], true, false),
+            TestAccount::new(self.hook_program_id, vec![], false, false),
+            TestAccount::new(
+                self.meta_list,
+                meta_list_data(&self.extra_account_metas()),
+                false,
+                false,
+            ),
+            TestAccount::new(self.fixed, vec![], false, false),
+            TestAccount::new(self.source_pda, vec![], false, true),
+            TestAccount::new(self.owner_pda, vec![], false, true),
+        ]
+    }
+}
+
+/// Runs both helpers on the same accounts, asserts they agree and returns
+/// the result and the CPIs issued
+fn assert_matches_upstream<'a>(
+    token_program_id: &Pubkey,
+    source_info: &AccountInfo<'a>,
+    mint_info: &AccountInfo<'a>,
+    destination_info: &AccountInfo<'a>,
+    authority_info: &AccountInfo<'a>,
+    additional_accounts: &[AccountInfo<'a>],
+) -> (ProgramResult, Vec<(Instruction, Vec<AccountInfoKey>)>) {
+    static STUBS: Once = Once::new();
+    STUBS.call_once(|| {
+        program_stubs::set_syscall_stubs(Box::new(CaptureStubs));
+    });
+
+    take_invoked();
+    let result = sol_sdk::invoke_transfer_checked(
+        token_program_id,
+        source_info.clone(),
+        mint_info.clone(),
+        destination_info.clone(),
+        authority_info.clone(),
+        additional_accounts,
+        AMOUNT,
+        DECIMALS,
+        &[],
+    );
+    let invoked = take_invoked();
+
+    let expected = onchain::invoke_transfer_checked(
+        token_program_id,
+        source_info.clone(),
+        mint_info.clone(),
+        destination_info.clone(),
+        authority_info.clone(),
+        additional_accounts,
+        AMOUNT,
+        DECIMALS,
+        &[],
+    );
+    let expected_invoked = take_invoked();
+
+    assert_eq!(result, expected);
+    assert_eq!(invoked, expected_invoked);
+    (result, invoked)
+}
+
+fn account_keys(instruction: &Instruction) -> Vec<Pubkey> {
+    instruction
+        .accounts
+        .iter()
+        .map(|meta| meta.pubkey)
+        .collect()
+}
+
+#[test]
+fn transfer_without_hook_matches_upstream() {
+    let mint = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    for (token_program_id, mint_data) in [
+        (spl_token_2022::ID, mint_data(None)),
+        // legacy Token mints skip the extension lookup in `sol_sdk`
+        (anchor_spl::token::ID, mint_data(None)),
+    ] {
+        let mut accounts = [
+            TestAccount::new(
+                Pubkey::new_unique(),
+                token_account_data(&mint, &owner),
+                false,
+                true,
+            ),
+            TestAccount::new(mint, mint_data, false, false),
+            TestAccount::new(
+                Pubkey::new_unique(),
+                token_account_data(&mint, &Pubkey::new_unique()),
+                false,
+                true,
+            ),
+            TestAccount::new(owner, vec![], true, false),
+            TestAccount::new(Pubkey::new_unique(), vec![], false, true),
+        ];
+        let infos: Vec<_> = accounts.iter_mut().map(TestAccount::info).collect();
+
+        let (result, invoked) = assert_matches_upstream(
+            &token_program_id,
+            &infos[0],
+            &infos[1],
+            &infos[2],
+            &infos[3],
+            &infos[4..],
+        );
+        assert_eq!(result, Ok(()));
+        assert_eq!(invoked.len(), 1);
+        assert_eq!(invoked[0].0.program_id, token_program_id);
+        assert_eq!(invoked[0].0.accounts.len(), 4);
+    }
+}
+
+#[test]
+fn transfer_with_hook_matches_upstream() {
+    let keys = HookKeys::new();
+    let mut accounts = keys.accounts();
+    let infos: Vec<_> = accounts.iter_mut().map(TestAccount::info).collect();
+
+    // extra accounts are only looked up in the additional accounts, in any order.
+    // The signing authority among them is also passed on like a multisig signer
+    let additional_accounts: Vec<_> = [8, 6, 3, 7, 4, 5]
+        .into_iter()
+        .map(|index| infos[index].clone())
+        .collect();
+    let (result, invoked) = assert_matches_upstream(
+        &spl_token_2022::ID,
+        &infos[0],
+        &infos[1],
+        &infos[2],
+        &infos[3],
+        &additional_accounts,
+    );
+    assert_eq!(result, Ok(()));
+    let (instruction, account_infos) = &invoked[0];
+    assert_eq!(
+        account_keys(instruction),
+        [
+            keys.source,
+            keys.mint,
+            keys.destination,
+            keys.owner,
+            keys.owner,
+            keys.fixed,
+            keys.source_pda,
+            keys.owner_pda,
+            keys.owner,
+            keys.hook_program_id,
+            keys.meta_list,
+        ]
+    );
+    // de-escalated to the privileges of the authority in the hook execute instruction
+    assert!(!instruction.accounts[8].is_signer);
+    assert!(!instruction.accounts[8].is_writable);
+    assert_eq!(
+        account_infos.iter().map(|info| info.0).collect::<Vec<_>>(),
+        account_keys(instruction)
+    );
+}
+
+#[test]
+fn multisig_signers_match_upstream() {
+    let keys = HookKeys::new();
+    let mut accounts = keys.accounts();
+    // the owner is a multisig account, signed for by two of its signers
+    accounts[3].is_signer = false;
+    accounts.push(TestAccount::new(Pubkey::new_unique(), vec![], true, false));
+    accounts.push(TestAccount::new(Pubkey::new_unique(), vec![], true, false));
+    let signers = [accounts[9].key, accounts[10].key];
+    let infos: Vec<_> = accounts.iter_mut().map(TestAccount::info).collect();
+
+    let additional_accounts: Vec<_> = [9, 4, 10, 5, 6, 3, 7, 8]
+        .into_iter()
+        .map(|index| infos[index].clone())
+        .collect();
+    let (result, invoked) = assert_matches_upstream(
+        &spl_token_2022::ID,
+        &infos[0],
+        &infos[1],
+        &infos[2],
+        &infos[3],
+        &additional_accounts,
+    );
+    assert_eq!(result, Ok(()));
+    let instruction = &invoked[0].0;
+    assert_eq!(account_keys(instruction)[4..6], signers);
+    assert!(instruction.accounts[4..6]
+        .iter()
+        .all(|meta| meta.is_signer && !meta.is_writable));
+    // the multisig account doesn't sign
+    assert_eq!(instruction.accounts[9].pubkey, keys.owner);
+    assert!(!instruction.accounts[9].is_signer);
+}
+
+#[test]
+fn duplicate_accounts_match_upstream() {
+    let keys = HookKeys::new();
+    let mut accounts = keys.accounts();
+    let infos: Vec<_> = accounts.iter_mut().map(TestAccount::info).collect();
+
+    // transfer to itself, with hook and extra accounts passed twice
+    let additional_accounts: Vec<_> = [4, 5, 6, 7, 8, 6, 5, 0, 3]
+        .into_iter()
+        .map(|index| infos[index].clone())
+        .collect();
+    let (result, invoked) = assert_matches_upstream(
+        &spl_token_2022::ID,
+        &infos[0],
+        &infos[1],
+        &infos[0],
+        &infos[3],
+        &additional_accounts,
+    );
+    assert_eq!(result, Ok(()));
+    assert_eq!(
+        account_keys(&invoked[0].0)[..3],
+        [keys.source, keys.mint, keys.source]
+    );
+}
+
+#[test]
+fn missing_hook_accounts_fail_like_upstream() {
+    let keys = HookKeys::new();
+    let mut accounts = keys.accounts();
+    let infos: Vec<_> = accounts.iter_mut().map(TestAccount::info).collect();
+
+    for (missing, error) in [
+        (keys.meta_list, TransferHookError::IncorrectAccount.into()),
+        (
+            keys.hook_program_id,
+            TransferHookError::IncorrectAccount.into(),
+        ),
+        (keys.fixed, AccountResolutionError::IncorrectAccount.into()),
+        (
+            keys.owner_pda,
+            AccountResolutionError::IncorrectAccount.into(),
+        ),
+    ] {
+        let additional_accounts: Vec<_> = infos[3..]
+            .iter()
+            .filter(|info| *info.key != missing)
+            .cloned()
+            .collect();
+        let (result, invoked) = assert_matches_upstream(
+            &spl_token_2022::ID,
+            &infos[0],
+            &infos[1],
+            &infos[2],
+            &infos[3],
+            &additional_accounts,
+        );
+        assert_eq!(result, Err(error), "{} is missing", missing);
+        assert!(invoked.is_empty());
+    }
+}
+
+#[test]
+fn account_and_instruction_data_seeds_match_upstream() {
+    let keys = HookKeys::new();
+    let destination_owner = Pubkey::new_unique();
+    let metas = [
+        vec![
+            Seed::Literal {
+                bytes: b"policy".to_vec(),
+            },
+            token_account_owner_seed(0),
+            Seed::AccountKey { index: 1 },
+        ],
+        vec![token_account_delegate_seed(0)],
+        vec![transfer_amount_seed()],
+        vec![token_account_owner_seed(2)],
+    ]
+    .map(|seeds| ExtraAccountMeta::new_with_seeds(&seeds, false, false).unwrap());
+
+    for delegate in [Some(Pubkey::new_unique()), None] {
+        let find = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &keys.hook_program_id).0;
+        let delegate_seed = delegate.unwrap_or_default();
+        let pdas = [
+            find(&[b"policy", keys.owner.as_ref(), keys.mint.as_ref()]),
+            find(&[delegate_seed.as_ref()]),
+            find(&[&AMOUNT.to_le_bytes()]),
+            find(&[destination_owner.as_ref()]),
+        ];
+
+        let mut source_data = token_account_data(&keys.mint, &keys.owner);
+        let mut source = Account::unpack(&source_data).unwrap();
+        source.delegate = delegate.into();
+        source.delegated_amount = delegate.map_or(0, |_| AMOUNT);
+        source.pack_into_slice(&mut source_data);
+
+        let mut accounts: Vec<_> = [
+            TestAccount::new(keys.source, source_data, false, true),
+            TestAccount::new(
+                keys.mint,
+                mint_data(Some(keys.hook_program_id)),
+                false,
+                false,
+            ),
+            TestAccount::new(
+                keys.destination,
+                token_account_data(&keys.mint, &destination_owner),
+                false,
+                true,
+            ),
+            TestAccount::new(keys.owner, vec![], true, false),
+            TestAccount::new(keys.hook_program_id, vec![], false, false),
+            TestAccount::new(keys.meta_list, meta_list_data(&metas), false, false),
+        ]
+        .into_iter()
+        .chain(
+            pdas.iter()
+                .map(|pda| TestAccount::new(*pda, vec![], false, false)),
+        )
+        .collect();
+        let infos: Vec<_> = accounts.iter_mut().map(TestAccount::info).collect();
+
+        let (result, invoked) = assert_matches_upstream(
+            &spl_token_2022::ID,
+            &infos[0],
+            &infos[1],
+            &infos[2],
+            &infos[3],
+            &infos[4..],
+        );
+        assert_eq!(result, Ok(()));
+        assert_eq!(account_keys(&invoked[0].0)[4..8], pdas);
+    }
+}
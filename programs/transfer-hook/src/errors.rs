@@ -0,0 +1,21 @@
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum HookError {
+    #[msg("Signer is not the mint authority")]
+    NotMintAuthority,
+    #[msg("Transfers of the mint are paused")]
+    MintPaused,
+    #[msg("Stats account doesn't belong to the transferred token account or mint")]
+    InvalidStatsAccount,
+    #[msg("Global stats shard is passed more than once")]
+    DuplicateGlobalStatsShard,
+    #[msg("Royalty account doesn't belong to the mint or the source owner")]
+    InvalidRoyaltyAccount,
+    #[msg("Destination owner isn't on the allowlist of the mint")]
+    DestinationNotAllowlisted,
+    #[msg("Transfer would leave less than the locked amount before the release")]
+    TokensLocked,
+    #[msg("Lockup doesn't belong to the mint or the source owner")]
+    InvalidLockupAccount,
+}
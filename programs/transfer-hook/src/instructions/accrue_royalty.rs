@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    extension::{transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions},
+    state::Mint as Token2022Mint,
+};
+use anchor_spl::token_interface::TokenAccount;
+use transfer_extensions::sol_sdk::{calculate_transfer_amounts, FeeMode};
+use transfer_extensions::states::{load_versioned, store_versioned, RoyaltyConfig};
+
+use crate::errors::HookError;
+use crate::state::RoyaltyDebt;
+
+/// Accrues the royalty of the transfer in the RoyaltyDebt of the source owner, in place.
+/// A transfer to the creator vault is a settlement: it pays off the debt by the amount the
+/// vault receives, net of the transfer fee, and accrues nothing.
+pub fn accrue_royalty(
+    source: &InterfaceAccount<TokenAccount>,
+    mint_info: &AccountInfo,
+    destination: &InterfaceAccount<TokenAccount>,
+    royalty_config_info: &AccountInfo,
+    royalty_debt_info: &AccountInfo,
+    amount: u64,
+) -> Result<()> {
+    // owned by transfer_extensions, only read
+    let royalty_config = load_versioned::<RoyaltyConfig>(royalty_config_info)?;
+    let mut royalty_debt = load_versioned::<RoyaltyDebt>(royalty_debt_info)?;
+    require!(
+        royalty_config.mint == source.mint
+            && royalty_debt.mint == source.mint
+            && royalty_debt.wallet == source.owner,
+        HookError::InvalidRoyaltyAccount
+    );
+
+    if destination.key() == royalty_config.creator_vault {
+        let settled = royalty_debt.settle(received_amount(mint_info, amount)?);
+        msg!("Royalty settled: {}, owed: {}", settled, royalty_debt.owed);
+    } else {
+        let royalty = royalty_config.royalty(amount);
+        royalty_debt.accrue(royalty);
+        msg!("Royalty accrued: {}, owed: {}", royalty, royalty_debt.owed);
+    }
+    store_versioned(&royalty_debt, royalty_debt_info)
+}
+
+/// `amount` less the transfer fee the mint withholds from it
+fn received_amount(mint_info: &AccountInfo, amount: u64) -> Result<u64> {
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint = StateWithExtensions::<Token2022Mint>::unpack(&mint_data)?;
+    let Ok(config) = mint.get_extension::<TransferFeeConfig>() else {
+        return Ok(amount);
+    };
+    let (_, fee) = calculate_transfer_amounts(config, Clock::get()?.epoch, amount, FeeMode::Gross)?;
+    Ok(amount.saturating_sub(fee))
+}
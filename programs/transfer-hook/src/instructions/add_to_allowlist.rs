@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use transfer_extensions::states::CURRENT_ACCOUNT_VERSION;
+
+use crate::state::{AllowlistEntry, HookConfig, ALLOWLIST_ENTRY_SEED, HOOK_CONFIG_SEED};
+
+#[derive(Accounts)]
+pub struct AddToAllowlist<'info> {
+    #[account(init, payer = authority, space = 8 + AllowlistEntry::INIT_SPACE,
+        seeds = [ALLOWLIST_ENTRY_SEED, wallet.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+
+    /// CHECK: HookConfig, loaded by `HookConfig::load_for_authority`
+    #[account(
+      seeds = [HOOK_CONFIG_SEED, mint.key().as_ref()],
+      bump,
+    )]
+    pub hook_config: UncheckedAccount<'info>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: token account owner allowed to receive the mint, doesn't have to sign
+    pub wallet: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Entries are only checked if the extra account meta list of the mint is initialized
+/// with the allowlist module
+pub fn add_to_allowlist(ctx: Context<AddToAllowlist>) -> Result<()> {
+    HookConfig::load_for_authority(&ctx.accounts.hook_config, &ctx.accounts.authority)?;
+
+    let allowlist_entry = &mut ctx.accounts.allowlist_entry;
+    allowlist_entry.wallet = ctx.accounts.wallet.key();
+    allowlist_entry.mint = ctx.accounts.mint.key();
+    allowlist_entry.version = CURRENT_ACCOUNT_VERSION;
+
+    Ok(())
+}
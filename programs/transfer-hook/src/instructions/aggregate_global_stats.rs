@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+use transfer_extensions::{
+    seeds::GLOBAL_PROGRAM_DATA_SEED,
+    states::{load_versioned, GlobalProgramData},
+};
+
+use crate::errors::HookError;
+use crate::state::{GlobalStatsShard, GLOBAL_STATS_SHARDS};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+pub struct GlobalStats {
+    pub transfers_count: u64,
+    pub volume: u128,
+    /// Number of shards included in the sums
+    pub shards_count: u16,
+}
+
+/// Read-only view, shards to sum are passed in `remaining_accounts`
+#[derive(Accounts)]
+pub struct AggregateGlobalStats<'info> {
+    /// CHECK: GlobalProgramData of transfer_extensions, keeps the count recorded before sharding
+    #[account(
+        seeds = [GLOBAL_PROGRAM_DATA_SEED],
+        bump,
+        seeds::program = transfer_extensions::ID,
+    )]
+    pub global_program_data: UncheckedAccount<'info>,
+}
+
+pub fn aggregate_global_stats<'info>(
+    ctx: Context<'_, '_, 'info, 'info, AggregateGlobalStats<'info>>,
+) -> Result<GlobalStats> {
+    let global_program_data =
+        load_versioned::<GlobalProgramData>(&ctx.accounts.global_program_data.to_account_info())?;
+    let mut stats = GlobalStats {
+        transfers_count: global_program_data.transfers_count,
+        ..Default::default()
+    };
+
+    let mut seen = [false; GLOBAL_STATS_SHARDS];
+    for shard_info in ctx.remaining_accounts.iter() {
+        let shard = Account::<GlobalStatsShard>::try_from(shard_info)?;
+        require!(
+            !seen[shard.shard as usize],
+            HookError::DuplicateGlobalStatsShard
+        );
+        seen[shard.shard as usize] = true;
+
+        stats.transfers_count = stats
+            .transfers_count
+            .checked_add(shard.transfers_count)
+            .unwrap();
+        stats.volume = stats.volume.checked_add(shard.volume).unwrap();
+        stats.shards_count += 1;
+    }
+
+    Ok(stats)
+}
@@ -1,12 +1,15 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token_interface::{Mint, TokenAccount};
-use transfer_extensions::{
-    program::TransferExtensions,
-    states::{
-        GlobalProgramData, MintCounterIn, MintCounterOut,
-        WalletCounterIn, WalletCounterOut,
-    },
+use anchor_spl::token_2022::spl_token_2022::{
+    extension::{transfer_hook::TransferHookAccount, BaseStateWithExtensions, StateWithExtensions},
+    state::Account as Token2022Account,
 };
+use anchor_spl::token_interface::{Mint, TokenAccount};
+use spl_transfer_hook_interface::error::TransferHookError;
+use transfer_extensions::states::{load_versioned, OwnerPolicy};
+
+use crate::errors::HookError;
+use crate::instructions::{accrue_royalty, record_transfer_stats};
+use crate::state::{AllowlistEntry, HookConfig, Lockup, HOOK_CONFIG_SEED};
 
 #[derive(Accounts)]
 #[instruction(amount: u64)]
@@ -38,38 +41,138 @@ pub struct ExecuteTransferHook<'info> {
     )]
     pub extra_metas_account: UncheckedAccount<'info>,
 
-    pub additional_account_1: Program<'info, TransferExtensions>,
-
-    pub wallet_counter_in_from: Account<'info, WalletCounterIn>,
-
-    pub wallet_counter_in_to: Account<'info, WalletCounterIn>,
-
-    pub wallet_counter_out_from: Account<'info, WalletCounterOut>,
-
-    pub wallet_counter_out_to: Account<'info, WalletCounterOut>,
-
-    pub mint_counter_in: Account<'info, MintCounterIn>,
-
-    pub mint_counter_out: Account<'info, MintCounterOut>,
-
-    pub global_program_data: Account<'info, GlobalProgramData>,
+    /// CHECK: HookConfig, loaded with `load_versioned`
+    #[account(
+      seeds = [HOOK_CONFIG_SEED, mint.key().as_ref()],
+      bump,
+    )]
+    pub hook_config: UncheckedAccount<'info>,
+    // `remaining_accounts` are the accounts of the enabled modules, see `get_extra_account_metas`
 }
 
-pub fn handler(ctx: Context<ExecuteTransferHook>, amount: u64) -> Result<()> {
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, ExecuteTransferHook<'info>>,
+    amount: u64,
+) -> Result<()> {
     msg!("Executing transfer hook with amount: {:?}", amount);
+    assert_is_transferring(&ctx.accounts.source_account.to_account_info())?;
+
+    let hook_config = load_versioned::<HookConfig>(&ctx.accounts.hook_config)?;
+    require!(!hook_config.paused, HookError::MintPaused);
+    let modules = hook_config.modules;
+
+    let mut remaining_accounts = ctx.remaining_accounts.iter();
+    let mut next_account = || {
+        remaining_accounts
+            .next()
+            .ok_or(error!(ErrorCode::AccountNotEnoughKeys))
+    };
+
+    if modules.uses_transfer_extensions() {
+        require_keys_eq!(
+            next_account()?.key(),
+            transfer_extensions::ID,
+            ErrorCode::InvalidProgramId
+        );
+    }
+    // stats are checked by `record_transfer_stats`, royalty accounts by `accrue_royalty`
+    let stats_accounts = if modules.counters.is_some() {
+        Some([
+            next_account()?,
+            next_account()?,
+            next_account()?,
+            next_account()?,
+        ])
+    } else {
+        None
+    };
+    let royalty_accounts = if modules.royalties {
+        Some((next_account()?, next_account()?))
+    } else {
+        None
+    };
+    let owner_policy = if modules.owner_policy {
+        Some(next_account()?)
+    } else {
+        None
+    };
+    let allowlist_entry = if modules.allowlist {
+        Some(next_account()?)
+    } else {
+        None
+    };
+    let lockup = if modules.lockups {
+        Some(next_account()?)
+    } else {
+        None
+    };
+
+    // wallets without an owner policy aren't limited
+    if let Some(owner_policy) = owner_policy.filter(|info| !info.data_is_empty()) {
+        load_versioned::<OwnerPolicy>(owner_policy)?.check_transfer(amount)?;
+    }
+
+    let mint = ctx.accounts.mint.key();
+    if let Some(allowlist_entry) = allowlist_entry {
+        let destination_owner = ctx.accounts.destination_account.owner;
+        require!(
+            !allowlist_entry.data_is_empty()
+                && load_versioned::<AllowlistEntry>(allowlist_entry)
+                    .is_ok_and(|entry| entry.wallet == destination_owner && entry.mint == mint),
+            HookError::DestinationNotAllowlisted
+        );
+    }
+
+    // wallets without a lockup aren't locked
+    if let Some(lockup) = lockup.filter(|info| !info.data_is_empty()) {
+        let lockup = load_versioned::<Lockup>(lockup)?;
+        require!(
+            lockup.wallet == ctx.accounts.source_account.owner && lockup.mint == mint,
+            HookError::InvalidLockupAccount
+        );
+        lockup.check_transfer(
+            ctx.accounts.source_account.amount,
+            Clock::get()?.unix_timestamp,
+        )?;
+    }
+
+    // The stats are owned by this program, so they're updated without calling back
+    // into transfer_extensions, which may have started the transfer
+    if let Some([source_stats, destination_stats, mint_stats, global_stats_shard]) = stats_accounts
+    {
+        record_transfer_stats(
+            &ctx.accounts.source_account,
+            &ctx.accounts.destination_account,
+            source_stats,
+            destination_stats,
+            mint_stats,
+            global_stats_shard,
+            amount,
+        )?;
+    }
+
+    // The debts are owned by this program as well
+    if let Some((royalty_config, royalty_debt)) = royalty_accounts {
+        accrue_royalty(
+            &ctx.accounts.source_account,
+            &ctx.accounts.mint.to_account_info(),
+            &ctx.accounts.destination_account,
+            royalty_config,
+            royalty_debt,
+            amount,
+        )?;
+    }
 
-    let wallet_counter_in_to = &mut ctx.accounts.wallet_counter_in_from;
-    wallet_counter_in_to.transfers_count = wallet_counter_in_to.transfers_count.checked_add(1).unwrap();
-    let wallet_counter_out_from = &mut ctx.accounts.wallet_counter_out_to;
-    wallet_counter_out_from.transfers_count = wallet_counter_out_from.transfers_count.checked_add(1).unwrap();
-
-    let mint_counter_in = &mut ctx.accounts.mint_counter_in;
-    mint_counter_in.transfers_count = mint_counter_in.transfers_count.checked_add(1).unwrap();
-    let mint_counter_out = &mut ctx.accounts.mint_counter_out;
-    mint_counter_out.transfers_count = mint_counter_out.transfers_count.checked_add(1).unwrap();
-
-    let global_program_data = &mut ctx.accounts.global_program_data;
-    global_program_data.transfers_count = global_program_data.transfers_count.checked_add(1).unwrap();
+    Ok(())
+}
 
+/// Stats must only be recorded for real transfers, not for direct calls of the hook
+fn assert_is_transferring(source_info: &AccountInfo) -> Result<()> {
+    let source_data = source_info.try_borrow_data()?;
+    let source = StateWithExtensions::<Token2022Account>::unpack(&source_data)?;
+    let extension = source.get_extension::<TransferHookAccount>()?;
+    if !bool::from(extension.transferring) {
+        return Err(ProgramError::from(TransferHookError::ProgramCalledOutsideOfTransfer).into());
+    }
     Ok(())
 }
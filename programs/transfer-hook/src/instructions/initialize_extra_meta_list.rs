@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
 use anchor_lang::{
     prelude::Result,
     solana_program::{program::invoke, pubkey::Pubkey, system_instruction::transfer},
@@ -9,15 +10,35 @@ use spl_tlv_account_resolution::state::ExtraAccountMetaList;
 use spl_tlv_account_resolution::{account::ExtraAccountMeta, seeds::Seed};
 use spl_transfer_hook_interface::instruction::ExecuteInstruction;
 use transfer_extensions::program::TransferExtensions;
-use transfer_extensions::seeds::{COUNTER_IN_SEED, COUNTER_OUT_SEED, GLOBAL_PROGRAM_DATA_SEED};
+use transfer_extensions::seeds::{
+    token_account_owner_seed, GLOBAL_STATS_SHARD_SEED, OWNER_POLICY_SEED, ROYALTY_CONFIG_SEED,
+    ROYALTY_DEBT_SEED, TOKEN_ACCOUNT_OWNER_OFFSET, TRANSFER_STATS_SEED,
+};
+use transfer_extensions::states::CURRENT_ACCOUNT_VERSION;
+
+use crate::errors::HookError;
+use crate::state::{HookConfig, HookModules, ALLOWLIST_ENTRY_SEED, HOOK_CONFIG_SEED, LOCKUP_SEED};
 
 pub const META_LIST_ACCOUNT_SEED: &[u8] = b"extra-account-metas";
 
+/// How wallet stats are derived from the transferred token accounts
+#[derive(
+    AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub enum CounterLayout {
+    /// One stats account per token account
+    #[default]
+    TokenAccount,
+    /// One aggregate stats account per (token account owner, mint)
+    Owner,
+}
+
 #[derive(Accounts)]
+#[instruction(modules: HookModules)]
 pub struct InitializeExtraAccountMetaList<'info> {
     #[account(
       init,
-      space = get_meta_list_size(account_manager_program.key)?,
+      space = get_meta_list_size(account_manager_program.key, &modules)?,
       seeds = [
         META_LIST_ACCOUNT_SEED,
         mint.key().as_ref(),
@@ -28,8 +49,18 @@ pub struct InitializeExtraAccountMetaList<'info> {
     /// CHECK: extra metas account
     pub extra_metas_account: UncheckedAccount<'info>,
 
+    #[account(
+      init,
+      space = 8 + HookConfig::INIT_SPACE,
+      seeds = [HOOK_CONFIG_SEED, mint.key().as_ref()],
+      bump,
+      payer = payer,
+    )]
+    pub hook_config: Box<Account<'info, HookConfig>>,
+
     #[account(
         mint::token_program = TOKEN_2022_PROGRAM_ID,
+        constraint = mint.mint_authority == COption::Some(payer.key()) @ HookError::NotMintAuthority,
     )]
     pub mint: Box<InterfaceAccount<'info, Mint>>,
 
@@ -42,111 +73,179 @@ pub struct InitializeExtraAccountMetaList<'info> {
     pub system_program: Program<'info, System>,
 }
 
+/// The payer must be the mint authority, it becomes the authority of the HookConfig
 pub fn initialize_extra_account_meta_list(
     ctx: Context<InitializeExtraAccountMetaList>,
+    modules: HookModules,
 ) -> Result<()> {
+    let hook_config = &mut ctx.accounts.hook_config;
+    hook_config.mint = ctx.accounts.mint.key();
+    hook_config.authority = ctx.accounts.payer.key();
+    hook_config.modules = modules;
+    hook_config.paused = false;
+    hook_config.version = CURRENT_ACCOUNT_VERSION;
+
     let extra_metas_account = &ctx.accounts.extra_metas_account;
-    let metas = get_extra_account_metas(ctx.accounts.account_manager_program.key)?;
+    let metas = get_extra_account_metas(ctx.accounts.account_manager_program.key, &modules)?;
     let mut data = extra_metas_account.try_borrow_mut_data()?;
     ExtraAccountMetaList::init::<ExecuteInstruction>(&mut data, &metas)?;
 
     Ok(())
 }
 
-pub fn get_meta_list_size(program_id: &Pubkey) -> Result<usize> {
-    Ok(ExtraAccountMetaList::size_of(get_extra_account_metas(program_id)?.len()).unwrap())
+pub fn get_meta_list_size(program_id: &Pubkey, modules: &HookModules) -> Result<usize> {
+    Ok(ExtraAccountMetaList::size_of(get_extra_account_metas(program_id, modules)?.len()).unwrap())
+}
+
+/// Account index of the transfer_extensions program, deriving the PDAs of its modules
+const PROGRAM_META_INDEX: u8 = 6;
+
+/// Seeds of the wallet stats of the token account at `account_index`
+fn wallet_stats_seeds(account_index: u8, layout: CounterLayout) -> Vec<Seed> {
+    let subject = match layout {
+        CounterLayout::TokenAccount => Seed::AccountKey {
+            index: account_index,
+        },
+        CounterLayout::Owner => token_account_owner_seed(account_index),
+    };
+    vec![
+        Seed::Literal {
+            bytes: TRANSFER_STATS_SEED.to_vec(),
+        },
+        subject,
+        Seed::AccountKey { index: 1 },
+    ]
 }
 
-pub fn get_extra_account_metas(program_id: &Pubkey) -> Result<Vec<ExtraAccountMeta>> {
-    Ok(vec![
-        // [index 5, 0] account manager program id
-        ExtraAccountMeta::new_with_pubkey(
-            program_id,
-            false, // is_signer
-            false, // is_writable
-        )?,
-        // [index 6, 1] counter in from
-        ExtraAccountMeta::new_external_pda_with_seeds(
-            5,
+/// Extra accounts of the enabled `modules`, in the order the hook reads them:
+/// the HookConfig, the transfer_extensions program if needed, then the accounts of the counters,
+/// royalties, owner policy, allowlist and lockups modules
+pub fn get_extra_account_metas(
+    program_id: &Pubkey,
+    modules: &HookModules,
+) -> Result<Vec<ExtraAccountMeta>> {
+    // [index 5] hook config of the mint
+    let mut metas = vec![ExtraAccountMeta::new_with_seeds(
+        &[
+            Seed::Literal {
+                bytes: HOOK_CONFIG_SEED.to_vec(),
+            },
+            Seed::AccountKey { index: 1 },
+        ],
+        false, // is_signer
+        false, // is_writable
+    )?];
+    if modules.uses_transfer_extensions() {
+        // [index 6] account manager program id, owner of the royalty config and owner policies
+        metas.push(ExtraAccountMeta::new_with_pubkey(program_id, false, false)?);
+    }
+    if let Some(layout) = modules.counters {
+        // source and destination stats, owned by this program
+        for account_index in [0, 2] {
+            metas.push(ExtraAccountMeta::new_with_seeds(
+                &wallet_stats_seeds(account_index, layout),
+                false,
+                true,
+            )?);
+        }
+        // mint stats
+        metas.push(ExtraAccountMeta::new_with_seeds(
             &[
                 Seed::Literal {
-                    bytes: COUNTER_IN_SEED.to_vec(),
+                    bytes: TRANSFER_STATS_SEED.to_vec(),
                 },
-                Seed::AccountKey { index: 0 },
+                Seed::AccountKey { index: 1 },
+                Seed::AccountKey { index: 1 },
             ],
             false,
-            false,
-        )?,
-        // [index 7, 2]  counter in to
-        ExtraAccountMeta::new_external_pda_with_seeds(
-            5,
+            true,
+        )?);
+        // global stats shard, picked by the first byte of the source owner
+        metas.push(ExtraAccountMeta::new_with_seeds(
             &[
                 Seed::Literal {
-                    bytes: COUNTER_IN_SEED.to_vec(),
+                    bytes: GLOBAL_STATS_SHARD_SEED.to_vec(),
+                },
+                Seed::AccountData {
+                    account_index: 0,
+                    data_index: TOKEN_ACCOUNT_OWNER_OFFSET,
+                    length: 1,
                 },
-                Seed::AccountKey { index: 2 },
             ],
             false,
-            false,
-        )?,
-        // [index 8, 3] counter out from
-        ExtraAccountMeta::new_external_pda_with_seeds(
-            5,
+            true,
+        )?);
+    }
+    if modules.royalties {
+        // royalty config of the mint
+        metas.push(ExtraAccountMeta::new_external_pda_with_seeds(
+            PROGRAM_META_INDEX,
             &[
                 Seed::Literal {
-                    bytes: COUNTER_OUT_SEED.to_vec(),
+                    bytes: ROYALTY_CONFIG_SEED.to_vec(),
                 },
-                Seed::AccountKey { index: 0 },
+                Seed::AccountKey { index: 1 },
             ],
             false,
             false,
-        )?,
-        // [index 9, 4] counter out to
-        ExtraAccountMeta::new_external_pda_with_seeds(
-            5,
+        )?);
+        // royalty debt of the source owner, owned by this program
+        metas.push(ExtraAccountMeta::new_with_seeds(
             &[
                 Seed::Literal {
-                    bytes: COUNTER_OUT_SEED.to_vec(),
+                    bytes: ROYALTY_DEBT_SEED.to_vec(),
                 },
-                Seed::AccountKey { index: 2 },
+                token_account_owner_seed(0),
+                Seed::AccountKey { index: 1 },
             ],
             false,
-            false,
-        )?,
-        // [index 10, 5] counter in mint
-        ExtraAccountMeta::new_external_pda_with_seeds(
-            5,
+            true,
+        )?);
+    }
+    if modules.owner_policy {
+        // owner policy of the source owner
+        metas.push(ExtraAccountMeta::new_external_pda_with_seeds(
+            PROGRAM_META_INDEX,
             &[
                 Seed::Literal {
-                    bytes: COUNTER_IN_SEED.to_vec(),
+                    bytes: OWNER_POLICY_SEED.to_vec(),
                 },
+                token_account_owner_seed(0),
                 Seed::AccountKey { index: 1 },
             ],
             false,
             false,
-        )?,
-        // [index 11, 6] counter out mint
-        ExtraAccountMeta::new_external_pda_with_seeds(
-            5,
+        )?);
+    }
+    if modules.allowlist {
+        // allowlist entry of the destination owner, owned by this program
+        metas.push(ExtraAccountMeta::new_with_seeds(
             &[
                 Seed::Literal {
-                    bytes: COUNTER_OUT_SEED.to_vec(),
+                    bytes: ALLOWLIST_ENTRY_SEED.to_vec(),
                 },
+                token_account_owner_seed(2),
                 Seed::AccountKey { index: 1 },
             ],
             false,
             false,
-        )?,
-        // [index 12, 7] global program data
-        ExtraAccountMeta::new_external_pda_with_seeds(
-            5,
-            &[Seed::Literal {
-                bytes: GLOBAL_PROGRAM_DATA_SEED.to_vec(),
-            }],
+        )?);
+    }
+    if modules.lockups {
+        // lockup of the source owner, owned by this program
+        metas.push(ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: LOCKUP_SEED.to_vec(),
+                },
+                token_account_owner_seed(0),
+                Seed::AccountKey { index: 1 },
+            ],
             false,
             false,
-        )?,
-    ])
+        )?);
+    }
+    Ok(metas)
 }
 
 pub fn update_account_lamports_to_minimum_balance<'info>(
@@ -154,7 +253,9 @@ pub fn update_account_lamports_to_minimum_balance<'info>(
     payer: AccountInfo<'info>,
     system_program: AccountInfo<'info>,
 ) -> Result<()> {
-    let extra_lamports = Rent::get()?.minimum_balance(account.data_len()) - account.get_lamports();
+    let extra_lamports = Rent::get()?
+        .minimum_balance(account.data_len())
+        .saturating_sub(account.get_lamports());
     if extra_lamports > 0 {
         invoke(
             &transfer(payer.key, account.key, extra_lamports),
@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+use transfer_extensions::{seeds::GLOBAL_STATS_SHARD_SEED, states::CURRENT_ACCOUNT_VERSION};
+
+use crate::state::GlobalStatsShard;
+
+#[derive(Accounts)]
+#[instruction(shard: u8)]
+pub struct InitializeGlobalStatsShard<'info> {
+    #[account(init, payer = payer, space = 8 + GlobalStatsShard::INIT_SPACE,
+        seeds = [GLOBAL_STATS_SHARD_SEED, &[shard]],
+        bump
+    )]
+    pub global_stats_shard: Account<'info, GlobalStatsShard>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_global_stats_shard(
+    ctx: Context<InitializeGlobalStatsShard>,
+    shard: u8,
+) -> Result<()> {
+    let global_stats_shard = &mut ctx.accounts.global_stats_shard;
+    global_stats_shard.shard = shard;
+    global_stats_shard.version = CURRENT_ACCOUNT_VERSION;
+
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use transfer_extensions::{seeds::ROYALTY_DEBT_SEED, states::CURRENT_ACCOUNT_VERSION};
+
+use crate::state::RoyaltyDebt;
+
+/// Every wallet sending a mint with royalties needs its debt account
+#[derive(Accounts)]
+pub struct InitializeRoyaltyDebt<'info> {
+    #[account(init, payer = payer, space = 8 + RoyaltyDebt::INIT_SPACE,
+        seeds = [ROYALTY_DEBT_SEED, wallet.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub royalty_debt: Box<Account<'info, RoyaltyDebt>>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: token account owner, doesn't have to sign (permissionless init)
+    pub wallet: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_royalty_debt(ctx: Context<InitializeRoyaltyDebt>) -> Result<()> {
+    let royalty_debt = &mut ctx.accounts.royalty_debt;
+    royalty_debt.wallet = ctx.accounts.wallet.key();
+    royalty_debt.mint = ctx.accounts.mint.key();
+    royalty_debt.version = CURRENT_ACCOUNT_VERSION;
+
+    Ok(())
+}
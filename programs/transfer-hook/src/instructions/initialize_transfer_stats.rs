@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use transfer_extensions::{seeds::TRANSFER_STATS_SEED, states::CURRENT_ACCOUNT_VERSION};
+
+use crate::state::TransferStats;
+
+/// Single init for every stats subject: stats of a token account, of a token account owner
+/// (aggregated over all its token accounts of the mint) or of the mint itself (`subject == mint`)
+#[derive(Accounts)]
+pub struct InitializeTransferStats<'info> {
+    #[account(init, payer = payer, space = 8 + TransferStats::INIT_SPACE,
+        seeds = [
+          TRANSFER_STATS_SEED,
+          &subject.key().to_bytes(),
+          &mint.key().to_bytes(),
+        ],
+        bump
+    )]
+    pub transfer_stats: Account<'info, TransferStats>,
+
+    /// Token-2022 or legacy Token program mint
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Token account, owner wallet or the mint, doesn't have to sign (permissionless init)
+    pub subject: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_transfer_stats(ctx: Context<InitializeTransferStats>) -> Result<()> {
+    let transfer_stats = &mut ctx.accounts.transfer_stats;
+    transfer_stats.subject = ctx.accounts.subject.key();
+    transfer_stats.mint = ctx.accounts.mint.key();
+    transfer_stats.version = CURRENT_ACCOUNT_VERSION;
+
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::Instruction, program::invoke_signed, program_option::COption,
+};
+use anchor_lang::InstructionData;
+use anchor_spl::{token_2022::ID as TOKEN_2022_PROGRAM_ID, token_interface::Mint};
+use transfer_extensions::{
+    program::TransferExtensions,
+    seeds::{HOOK_AUTHORITY_SEED, TRANSFER_STATS_SEED},
+    states::{
+        load_versioned, MintCounterIn, MintCounterOut, WalletCounterIn, WalletCounterOut,
+        CURRENT_ACCOUNT_VERSION,
+    },
+};
+
+use crate::errors::HookError;
+use crate::state::TransferStats;
+
+/// Moves the in and out counters a mint recorded before TransferStats into the stats of
+/// the same subject: a token account, a token account owner or the mint itself
+#[derive(Accounts)]
+pub struct MigrateLegacyCounters<'info> {
+    #[account(init, payer = payer, space = 8 + TransferStats::INIT_SPACE,
+        seeds = [
+          TRANSFER_STATS_SEED,
+          &subject.key().to_bytes(),
+          &mint.key().to_bytes(),
+        ],
+        bump
+    )]
+    pub transfer_stats: Box<Account<'info, TransferStats>>,
+
+    /// CHECK: WalletCounterIn or MintCounterIn, checked by transfer_extensions when closed
+    #[account(mut)]
+    pub counter_in: UncheckedAccount<'info>,
+
+    /// CHECK: WalletCounterOut or MintCounterOut, checked by transfer_extensions when closed
+    #[account(mut)]
+    pub counter_out: UncheckedAccount<'info>,
+
+    /// CHECK: token account, owner wallet or the mint the counters were initialized for
+    pub subject: UncheckedAccount<'info>,
+
+    #[account(
+        mint::token_program = TOKEN_2022_PROGRAM_ID,
+        constraint = mint.mint_authority == COption::Some(payer.key()) @ HookError::NotMintAuthority,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: PDA of this program signing the close of the counters
+    #[account(seeds = [HOOK_AUTHORITY_SEED], bump)]
+    pub hook_authority: UncheckedAccount<'info>,
+
+    #[account()]
+    pub account_manager_program: Program<'info, TransferExtensions>,
+
+    /// Pays the stats and gets the rent of the closed counters back
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Transfer counts are kept, volumes weren't counted by the legacy counters and start at zero.
+/// The payer must be the mint authority, the counters were paid by its users.
+pub fn migrate_legacy_counters(ctx: Context<MigrateLegacyCounters>) -> Result<()> {
+    let accounts = ctx.accounts;
+    let (transfers_in, transfers_out) = if accounts.subject.key() == accounts.mint.key() {
+        (
+            load_versioned::<MintCounterIn>(&accounts.counter_in)?.transfers_count,
+            load_versioned::<MintCounterOut>(&accounts.counter_out)?.transfers_count,
+        )
+    } else {
+        (
+            load_versioned::<WalletCounterIn>(&accounts.counter_in)?.transfers_count,
+            load_versioned::<WalletCounterOut>(&accounts.counter_out)?.transfers_count,
+        )
+    };
+
+    let transfer_stats = &mut accounts.transfer_stats;
+    transfer_stats.subject = accounts.subject.key();
+    transfer_stats.mint = accounts.mint.key();
+    transfer_stats.transfers_in = transfers_in;
+    transfer_stats.transfers_out = transfers_out;
+    transfer_stats.version = CURRENT_ACCOUNT_VERSION;
+
+    // built by hand, transfer_extensions has no CPI client: Anchor can't generate one for
+    // its `settle` instruction, which has no accounts of its own
+    let close_accounts = transfer_extensions::accounts::CloseLegacyCounters {
+        counter_in: accounts.counter_in.key(),
+        counter_out: accounts.counter_out.key(),
+        subject: accounts.subject.key(),
+        mint: accounts.mint.key(),
+        hook_authority: accounts.hook_authority.key(),
+        receiver: accounts.payer.key(),
+    };
+    invoke_signed(
+        &Instruction {
+            program_id: transfer_extensions::ID,
+            accounts: close_accounts.to_account_metas(None),
+            data: transfer_extensions::instruction::CloseLegacyCounters {}.data(),
+        },
+        &[
+            accounts.counter_in.to_account_info(),
+            accounts.counter_out.to_account_info(),
+            accounts.subject.to_account_info(),
+            accounts.mint.to_account_info(),
+            accounts.hook_authority.to_account_info(),
+            accounts.payer.to_account_info(),
+        ],
+        &[&[HOOK_AUTHORITY_SEED, &[ctx.bumps.hook_authority]]],
+    )
+    .map_err(Into::into)
+}
@@ -3,3 +3,39 @@ pub use handler::*;
 
 pub mod initialize_extra_meta_list;
 pub use initialize_extra_meta_list::*;
+
+pub mod update_extra_meta_list;
+pub use update_extra_meta_list::*;
+
+pub mod set_paused;
+pub use set_paused::*;
+
+pub mod add_to_allowlist;
+pub use add_to_allowlist::*;
+
+pub mod remove_from_allowlist;
+pub use remove_from_allowlist::*;
+
+pub mod set_lockup;
+pub use set_lockup::*;
+
+pub mod initialize_transfer_stats;
+pub use initialize_transfer_stats::*;
+
+pub mod initialize_global_stats_shard;
+pub use initialize_global_stats_shard::*;
+
+pub mod aggregate_global_stats;
+pub use aggregate_global_stats::*;
+
+pub mod record_transfer;
+pub use record_transfer::*;
+
+pub mod migrate_legacy_counters;
+pub use migrate_legacy_counters::*;
+
+pub mod initialize_royalty_debt;
+pub use initialize_royalty_debt::*;
+
+pub mod accrue_royalty;
+pub use accrue_royalty::*;
@@ -0,0 +1,137 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+use transfer_extensions::{
+    seeds::TRANSFER_RECORDER_SEED,
+    states::{load_versioned, store_versioned},
+};
+
+use crate::errors::HookError;
+use crate::state::{GlobalStatsShard, TransferStats};
+
+/// Records a transfer of a legacy Token program mint, which has no transfer hook.
+/// Only called by transfer_extensions, signed with its transfer recorder PDA.
+#[derive(Accounts)]
+pub struct RecordTransfer<'info> {
+    /// CHECK: TransferStats, checked by `record_transfer_stats`
+    #[account(mut)]
+    pub source_stats: UncheckedAccount<'info>,
+
+    /// CHECK: TransferStats, checked by `record_transfer_stats`
+    #[account(mut)]
+    pub destination_stats: UncheckedAccount<'info>,
+
+    /// CHECK: TransferStats, checked by `record_transfer_stats`
+    #[account(mut)]
+    pub mint_stats: UncheckedAccount<'info>,
+
+    /// CHECK: GlobalStatsShard, checked by `record_transfer_stats`
+    #[account(mut)]
+    pub global_stats_shard: UncheckedAccount<'info>,
+
+    #[account(
+      token::mint = mint,
+      token::token_program = anchor_spl::token::ID,
+    )]
+    pub source_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+      token::mint = mint,
+      token::token_program = anchor_spl::token::ID,
+    )]
+    pub destination_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token-2022 mints are recorded by the hook itself
+    #[account(
+      mint::token_program = anchor_spl::token::ID,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+      seeds = [TRANSFER_RECORDER_SEED],
+      bump,
+      seeds::program = transfer_extensions::ID,
+    )]
+    pub transfer_recorder: Signer<'info>,
+}
+
+pub fn record_transfer(ctx: Context<RecordTransfer>, amount: u64) -> Result<()> {
+    let accounts = ctx.accounts;
+    record_transfer_stats(
+        &accounts.source_account,
+        &accounts.destination_account,
+        &accounts.source_stats,
+        &accounts.destination_stats,
+        &accounts.mint_stats,
+        &accounts.global_stats_shard,
+        amount,
+    )
+}
+
+/// Checks the stats against the transferred token accounts and updates them in place
+pub fn record_transfer_stats<'info>(
+    source: &InterfaceAccount<TokenAccount>,
+    destination: &InterfaceAccount<TokenAccount>,
+    source_stats_info: &AccountInfo<'info>,
+    destination_stats_info: &AccountInfo<'info>,
+    mint_stats_info: &AccountInfo<'info>,
+    global_stats_shard_info: &AccountInfo,
+    amount: u64,
+) -> Result<()> {
+    // The source and destination stats are the same account for a transfer to self, and the
+    // stats of an owner are the mint stats when the mint owns the token account. Each account
+    // is loaded and stored once with all its updates, a second store would undo the first.
+    let mut stats: Vec<(&AccountInfo<'info>, TransferStats)> = Vec::with_capacity(3);
+    for info in [source_stats_info, destination_stats_info, mint_stats_info] {
+        if !stats.iter().any(|(loaded, _)| loaded.key == info.key) {
+            stats.push((info, load_versioned::<TransferStats>(info)?));
+        }
+    }
+    let [source_index, destination_index, mint_index] =
+        [source_stats_info, destination_stats_info, mint_stats_info].map(|info| {
+            stats
+                .iter()
+                .position(|(loaded, _)| loaded.key == info.key)
+                .unwrap()
+        });
+    let mut global_stats_shard = load_versioned::<GlobalStatsShard>(global_stats_shard_info)?;
+
+    let mint_stats = &stats[mint_index].1;
+    require!(
+        is_wallet_stats_of(&stats[source_index].1, source)
+            && is_wallet_stats_of(&stats[destination_index].1, destination)
+            && mint_stats.subject == source.mint
+            && mint_stats.mint == source.mint,
+        HookError::InvalidStatsAccount
+    );
+    require_eq!(
+        global_stats_shard.shard,
+        source.owner.to_bytes()[0],
+        HookError::InvalidStatsAccount
+    );
+
+    stats[source_index].1.record_out(amount);
+    stats[destination_index].1.record_in(amount);
+    stats[mint_index].1.record_out(amount);
+    stats[mint_index].1.record_in(amount);
+    for (info, stats) in &stats {
+        store_versioned(stats, info)?;
+    }
+
+    global_stats_shard.transfers_count = global_stats_shard.transfers_count.checked_add(1).unwrap();
+    global_stats_shard.volume = global_stats_shard
+        .volume
+        .checked_add(amount as u128)
+        .unwrap();
+    store_versioned(&global_stats_shard, global_stats_shard_info)?;
+
+    Ok(())
+}
+
+/// Stats of the token account itself or of its owner
+fn is_wallet_stats_of(
+    stats: &TransferStats,
+    token_account: &InterfaceAccount<TokenAccount>,
+) -> bool {
+    stats.mint == token_account.mint
+        && (stats.subject == token_account.key() || stats.subject == token_account.owner)
+}
@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{AllowlistEntry, HookConfig, ALLOWLIST_ENTRY_SEED, HOOK_CONFIG_SEED};
+
+#[derive(Accounts)]
+pub struct RemoveFromAllowlist<'info> {
+    #[account(mut, close = authority,
+        seeds = [
+          ALLOWLIST_ENTRY_SEED,
+          allowlist_entry.wallet.as_ref(),
+          allowlist_entry.mint.as_ref(),
+        ],
+        bump
+    )]
+    pub allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+
+    /// CHECK: HookConfig, loaded by `HookConfig::load_for_authority`
+    #[account(
+      seeds = [HOOK_CONFIG_SEED, allowlist_entry.mint.as_ref()],
+      bump,
+    )]
+    pub hook_config: UncheckedAccount<'info>,
+
+    /// Gets the rent of the entry back
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+pub fn remove_from_allowlist(ctx: Context<RemoveFromAllowlist>) -> Result<()> {
+    HookConfig::load_for_authority(&ctx.accounts.hook_config, &ctx.accounts.authority)?;
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use transfer_extensions::states::CURRENT_ACCOUNT_VERSION;
+
+use crate::state::{HookConfig, Lockup, HOOK_CONFIG_SEED, LOCKUP_SEED};
+
+#[derive(Accounts)]
+pub struct SetLockup<'info> {
+    #[account(init_if_needed, payer = authority, space = 8 + Lockup::INIT_SPACE,
+        seeds = [LOCKUP_SEED, wallet.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub lockup: Box<Account<'info, Lockup>>,
+
+    /// CHECK: HookConfig, loaded by `HookConfig::load_for_authority`
+    #[account(
+      seeds = [HOOK_CONFIG_SEED, mint.key().as_ref()],
+      bump,
+    )]
+    pub hook_config: UncheckedAccount<'info>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: token account owner the lockup applies to, doesn't have to sign
+    pub wallet: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Creates or replaces the lockup of a wallet, a zero `locked_amount` releases it.
+/// Lockups are only enforced if the extra account meta list of the mint is initialized
+/// with the lockups module.
+pub fn set_lockup(ctx: Context<SetLockup>, locked_amount: u64, release_time: i64) -> Result<()> {
+    HookConfig::load_for_authority(&ctx.accounts.hook_config, &ctx.accounts.authority)?;
+
+    let lockup = &mut ctx.accounts.lockup;
+    lockup.wallet = ctx.accounts.wallet.key();
+    lockup.mint = ctx.accounts.mint.key();
+    lockup.locked_amount = locked_amount;
+    lockup.release_time = release_time;
+    lockup.version = CURRENT_ACCOUNT_VERSION;
+
+    Ok(())
+}
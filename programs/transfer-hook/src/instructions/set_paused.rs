@@ -0,0 +1,21 @@
+use anchor_lang::prelude::*;
+use transfer_extensions::states::store_versioned;
+
+use crate::state::HookConfig;
+
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    /// CHECK: HookConfig, loaded by `HookConfig::load_for_authority`
+    #[account(mut)]
+    pub hook_config: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+/// Paused mints can't be transferred, whatever modules are enabled
+pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+    let mut hook_config =
+        HookConfig::load_for_authority(&ctx.accounts.hook_config, &ctx.accounts.authority)?;
+    hook_config.paused = paused;
+    store_versioned(&hook_config, &ctx.accounts.hook_config)
+}
@@ -0,0 +1,131 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::system_program::{create_account, CreateAccount};
+use anchor_spl::{token_2022::ID as TOKEN_2022_PROGRAM_ID, token_interface::Mint};
+use spl_tlv_account_resolution::state::ExtraAccountMetaList;
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+use transfer_extensions::program::TransferExtensions;
+use transfer_extensions::states::{
+    load_versioned, store_versioned, CURRENT_ACCOUNT_VERSION, RESERVED_SPACE,
+};
+
+use crate::errors::HookError;
+use crate::instructions::{
+    get_extra_account_metas, get_meta_list_size, update_account_lamports_to_minimum_balance,
+    META_LIST_ACCOUNT_SEED,
+};
+use crate::state::{HookConfig, HookModules, HOOK_CONFIG_SEED};
+
+/// Reconfigures the modules of a mint. Also upgrades meta lists initialized before the
+/// HookConfig existed: the config is created and the list rebuilt with it at index 5.
+/// Configs stored before the last modules grow to the current layout.
+#[derive(Accounts)]
+pub struct UpdateExtraAccountMetaList<'info> {
+    /// CHECK: extra metas account, rebuilt from `modules`
+    #[account(
+      mut,
+      seeds = [
+        META_LIST_ACCOUNT_SEED,
+        mint.key().as_ref(),
+      ],
+      bump,
+    )]
+    pub extra_metas_account: UncheckedAccount<'info>,
+
+    /// CHECK: HookConfig, created or grown to the current layout, see `store_hook_config`
+    #[account(
+      mut,
+      seeds = [HOOK_CONFIG_SEED, mint.key().as_ref()],
+      bump,
+    )]
+    pub hook_config: UncheckedAccount<'info>,
+
+    #[account(
+        mint::token_program = TOKEN_2022_PROGRAM_ID,
+        constraint = mint.mint_authority == COption::Some(payer.key()) @ HookError::NotMintAuthority,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account()]
+    pub account_manager_program: Program<'info, TransferExtensions>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// The payer must be the mint authority, it becomes the authority of the HookConfig.
+/// A paused mint stays paused.
+pub fn update_extra_account_meta_list(
+    ctx: Context<UpdateExtraAccountMetaList>,
+    modules: HookModules,
+) -> Result<()> {
+    store_hook_config(&ctx, modules)?;
+
+    let program_id = ctx.accounts.account_manager_program.key;
+    let extra_metas_account = ctx.accounts.extra_metas_account.to_account_info();
+    extra_metas_account.realloc(get_meta_list_size(program_id, &modules)?, false)?;
+    update_account_lamports_to_minimum_balance(
+        extra_metas_account.clone(),
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+
+    // the list is written from scratch, whatever its previous size
+    let metas = get_extra_account_metas(program_id, &modules)?;
+    let mut data = extra_metas_account.try_borrow_mut_data()?;
+    data.fill(0);
+    ExtraAccountMetaList::init::<ExecuteInstruction>(&mut data, &metas)?;
+
+    Ok(())
+}
+
+/// Writes the config with the current layout. Configs stored before the last modules
+/// are reallocated and zeroed first, so no stale byte is read as a module appended later.
+fn store_hook_config(
+    ctx: &Context<UpdateExtraAccountMetaList>,
+    modules: HookModules,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let hook_config_info = accounts.hook_config.to_account_info();
+    let mint = accounts.mint.key();
+    let space = 8 + HookConfig::INIT_SPACE;
+
+    let paused = if hook_config_info.data_is_empty() {
+        create_account(
+            CpiContext::new_with_signer(
+                accounts.system_program.to_account_info(),
+                CreateAccount {
+                    from: accounts.payer.to_account_info(),
+                    to: hook_config_info.clone(),
+                },
+                &[&[HOOK_CONFIG_SEED, mint.as_ref(), &[ctx.bumps.hook_config]]],
+            ),
+            Rent::get()?.minimum_balance(space),
+            space as u64,
+            &crate::ID,
+        )?;
+        false
+    } else {
+        let paused = load_versioned::<HookConfig>(&hook_config_info)?.paused;
+        hook_config_info.realloc(space, false)?;
+        update_account_lamports_to_minimum_balance(
+            hook_config_info.clone(),
+            accounts.payer.to_account_info(),
+            accounts.system_program.to_account_info(),
+        )?;
+        paused
+    };
+
+    hook_config_info.try_borrow_mut_data()?.fill(0);
+    let hook_config = HookConfig {
+        mint,
+        authority: accounts.payer.key(),
+        paused,
+        version: CURRENT_ACCOUNT_VERSION,
+        reserved: [0; RESERVED_SPACE],
+        modules,
+    };
+    store_versioned(&hook_config, &hook_config_info)
+}
@@ -2,8 +2,11 @@ use anchor_lang::prelude::*;
 
 declare_id!("14KA3wb3jtHft5MLy59VCJAAVDbCAduDydUAKDCEnipV");
 
+pub mod errors;
 pub mod instructions;
+pub mod state;
 use instructions::*;
+use state::*;
 
 #[program]
 pub mod transfer_hook {
@@ -11,14 +14,74 @@ pub mod transfer_hook {
 
     /// execute transfer hook
     #[interface(spl_transfer_hook_interface::execute)]
-    pub fn execute_transaction(ctx: Context<ExecuteTransferHook>, amount: u64) -> Result<()> {
+    pub fn execute_transaction<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteTransferHook<'info>>,
+        amount: u64,
+    ) -> Result<()> {
         instructions::handler(ctx, amount)
     }
 
     pub fn initialize_extra_account_meta_list(
         ctx: Context<InitializeExtraAccountMetaList>,
+        modules: HookModules,
+    ) -> Result<()> {
+        instructions::initialize_extra_account_meta_list(ctx, modules)
+    }
+
+    pub fn update_extra_account_meta_list(
+        ctx: Context<UpdateExtraAccountMetaList>,
+        modules: HookModules,
+    ) -> Result<()> {
+        instructions::update_extra_account_meta_list(ctx, modules)
+    }
+
+    pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+        instructions::set_paused(ctx, paused)
+    }
+
+    pub fn add_to_allowlist(ctx: Context<AddToAllowlist>) -> Result<()> {
+        instructions::add_to_allowlist(ctx)
+    }
+
+    pub fn remove_from_allowlist(ctx: Context<RemoveFromAllowlist>) -> Result<()> {
+        instructions::remove_from_allowlist(ctx)
+    }
+
+    pub fn set_lockup(
+        ctx: Context<SetLockup>,
+        locked_amount: u64,
+        release_time: i64,
     ) -> Result<()> {
-        instructions::initialize_extra_account_meta_list(ctx)
+        instructions::set_lockup(ctx, locked_amount, release_time)
+    }
+
+    pub fn initialize_transfer_stats(ctx: Context<InitializeTransferStats>) -> Result<()> {
+        instructions::initialize_transfer_stats(ctx)
+    }
+
+    pub fn initialize_global_stats_shard(
+        ctx: Context<InitializeGlobalStatsShard>,
+        shard: u8,
+    ) -> Result<()> {
+        instructions::initialize_global_stats_shard(ctx, shard)
+    }
+
+    pub fn aggregate_global_stats<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AggregateGlobalStats<'info>>,
+    ) -> Result<GlobalStats> {
+        instructions::aggregate_global_stats(ctx)
+    }
+
+    pub fn record_transfer(ctx: Context<RecordTransfer>, amount: u64) -> Result<()> {
+        instructions::record_transfer(ctx, amount)
+    }
+
+    pub fn migrate_legacy_counters(ctx: Context<MigrateLegacyCounters>) -> Result<()> {
+        instructions::migrate_legacy_counters(ctx)
+    }
+
+    pub fn initialize_royalty_debt(ctx: Context<InitializeRoyaltyDebt>) -> Result<()> {
+        instructions::initialize_royalty_debt(ctx)
     }
 }
 
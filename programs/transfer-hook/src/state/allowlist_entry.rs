@@ -0,0 +1,16 @@
+use anchor_lang::prelude::*;
+
+use transfer_extensions::states::RESERVED_SPACE;
+
+pub const ALLOWLIST_ENTRY_SEED: &[u8] = b"allowlist-entry";
+
+/// Allows a wallet (token account owner) to receive `mint` when the allowlist module is
+/// enabled, added and removed by the authority of the HookConfig
+#[account()]
+#[derive(InitSpace)]
+pub struct AllowlistEntry {
+    pub wallet: Pubkey,
+    pub mint: Pubkey,
+    pub version: u8,
+    pub reserved: [u8; RESERVED_SPACE],
+}
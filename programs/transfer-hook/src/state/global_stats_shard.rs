@@ -0,0 +1,18 @@
+use anchor_lang::prelude::*;
+
+use transfer_extensions::states::RESERVED_SPACE;
+
+/// Number of global stats shards, a shard is picked by one byte of the source owner
+pub const GLOBAL_STATS_SHARDS: usize = 256;
+
+/// Part of the global transfer stats, so transfers of different owners
+/// don't write-lock the same account
+#[account()]
+#[derive(Default, InitSpace)]
+pub struct GlobalStatsShard {
+    pub shard: u8,
+    pub transfers_count: u64,
+    pub volume: u128,
+    pub version: u8,
+    pub reserved: [u8; RESERVED_SPACE],
+}
@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+use transfer_extensions::states::{load_versioned, RESERVED_SPACE};
+
+use crate::instructions::CounterLayout;
+
+pub const HOOK_CONFIG_SEED: &[u8] = b"hook-config";
+
+/// Modules the hook runs on every transfer of a mint, chosen by the issuer.
+/// The extra account meta list only holds the accounts of the enabled modules,
+/// `update_extra_account_meta_list` rebuilds it when they change.
+///
+/// Stored last in the HookConfig, as its size depends on `counters`. New modules are
+/// appended: a config stored before them is shorter and loads them as disabled through
+/// `load_versioned`, until `update_extra_account_meta_list` reallocates it.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct HookModules {
+    /// Records the transfer in the stats of both wallets, the mint and the global shard
+    pub counters: Option<CounterLayout>,
+    /// Accrues the royalty of the mint RoyaltyConfig in the RoyaltyDebt of the source owner
+    /// (owned by this program), both must exist
+    pub royalties: bool,
+    /// Enforces the OwnerPolicy of the source owner, if it has one
+    pub owner_policy: bool,
+    /// Only delivers to token accounts of wallets with an AllowlistEntry
+    pub allowlist: bool,
+    /// Enforces the Lockup of the source owner, if it has one
+    pub lockups: bool,
+}
+
+impl HookModules {
+    /// Modules reading accounts of the transfer_extensions program. The hook never updates
+    /// them, transfer_extensions may have started the transfer and can't be called back.
+    pub fn uses_transfer_extensions(&self) -> bool {
+        self.royalties || self.owner_policy
+    }
+}
+
+/// Hook settings of a mint, passed to every transfer as the first extra account.
+/// Always loaded with `load_versioned`, configs stored before the last modules are shorter.
+#[account()]
+#[derive(InitSpace)]
+pub struct HookConfig {
+    pub mint: Pubkey,
+    /// Mint authority when the meta list was last initialized or updated, can pause transfers
+    /// and manage the allowlist and lockups
+    pub authority: Pubkey,
+    pub paused: bool,
+    pub version: u8,
+    pub reserved: [u8; RESERVED_SPACE],
+    pub modules: HookModules,
+}
+
+/// Space of the HookConfigs stored with the first layout, later modules are appended to it
+pub const HOOK_CONFIG_FIRST_SPACE: usize = 8 + 32 + 32 + 1 + 1 + RESERVED_SPACE + 6;
+
+impl HookConfig {
+    /// Loads the config for an instruction of its authority, which must sign it
+    pub fn load_for_authority(info: &AccountInfo, authority: &Signer) -> Result<Self> {
+        let hook_config = load_versioned::<Self>(info)?;
+        require_keys_eq!(
+            hook_config.authority,
+            authority.key(),
+            ErrorCode::ConstraintHasOne
+        );
+        Ok(hook_config)
+    }
+}
@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use transfer_extensions::states::RESERVED_SPACE;
+
+use crate::errors::HookError;
+
+pub const LOCKUP_SEED: &[u8] = b"lockup";
+
+/// Tokens of `mint` a wallet (token account owner) can't transfer before `release_time`,
+/// set by the authority of the HookConfig. Only enforced if the lockups module is enabled.
+#[account()]
+#[derive(InitSpace)]
+pub struct Lockup {
+    pub wallet: Pubkey,
+    pub mint: Pubkey,
+    /// Balance each token account of the wallet keeps until the release
+    pub locked_amount: u64,
+    /// Unix timestamp
+    pub release_time: i64,
+    pub version: u8,
+    pub reserved: [u8; RESERVED_SPACE],
+}
+
+impl Lockup {
+    /// `balance` is what the source token account holds after the transfer
+    pub fn check_transfer(&self, balance: u64, now: i64) -> Result<()> {
+        if now < self.release_time {
+            require_gte!(balance, self.locked_amount, HookError::TokensLocked);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+use anchor_lang::Space;
+
+pub mod allowlist_entry;
+pub use allowlist_entry::*;
+
+pub mod global_stats_shard;
+pub use global_stats_shard::*;
+
+pub mod hook_config;
+pub use hook_config::*;
+
+pub mod lockup;
+pub use lockup::*;
+
+pub mod royalty_debt;
+pub use royalty_debt::*;
+
+pub mod transfer_stats;
+pub use transfer_stats::*;
+
+// Created with a versioned layout from the start
+transfer_extensions::impl_versioned!(AllowlistEntry, 8 + AllowlistEntry::INIT_SPACE);
+transfer_extensions::impl_versioned!(GlobalStatsShard, 8 + GlobalStatsShard::INIT_SPACE);
+transfer_extensions::impl_versioned!(HookConfig, HOOK_CONFIG_FIRST_SPACE);
+transfer_extensions::impl_versioned!(Lockup, 8 + Lockup::INIT_SPACE);
+transfer_extensions::impl_versioned!(RoyaltyDebt, 8 + RoyaltyDebt::INIT_SPACE);
+transfer_extensions::impl_versioned!(TransferStats, 8 + TransferStats::INIT_SPACE);
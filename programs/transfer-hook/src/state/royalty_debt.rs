@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use transfer_extensions::states::RESERVED_SPACE;
+
+/// Royalties accrued by the transfers of a wallet (token account owner) of `mint`,
+/// transfers of the wallet to the creator vault settle them
+#[account()]
+#[derive(InitSpace)]
+pub struct RoyaltyDebt {
+    pub wallet: Pubkey,
+    pub mint: Pubkey,
+    /// Accrued and not settled yet
+    pub owed: u64,
+    /// Settled over the lifetime of the account
+    pub settled: u64,
+    pub version: u8,
+    pub reserved: [u8; RESERVED_SPACE],
+}
+
+impl RoyaltyDebt {
+    pub fn accrue(&mut self, royalty: u64) {
+        self.owed = self.owed.checked_add(royalty).unwrap();
+    }
+
+    /// Settles up to `amount` of the owed royalties, returns the settled part
+    pub fn settle(&mut self, amount: u64) -> u64 {
+        let settled = amount.min(self.owed);
+        self.owed -= settled;
+        self.settled = self.settled.checked_add(settled).unwrap();
+        settled
+    }
+}
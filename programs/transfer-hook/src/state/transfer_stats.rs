@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use transfer_extensions::states::RESERVED_SPACE;
+
+/// Bidirectional transfer statistics of a subject: a token account,
+/// a token account owner or the mint itself (then `subject == mint`)
+#[account()]
+#[derive(Default, InitSpace)]
+pub struct TransferStats {
+    pub subject: Pubkey,
+    pub mint: Pubkey,
+    pub transfers_in: u64,
+    pub transfers_out: u64,
+    pub volume_in: u128,
+    pub volume_out: u128,
+    pub version: u8,
+    pub reserved: [u8; RESERVED_SPACE],
+}
+
+impl TransferStats {
+    pub fn record_in(&mut self, amount: u64) {
+        self.transfers_in = self.transfers_in.checked_add(1).unwrap();
+        self.volume_in = self.volume_in.checked_add(amount as u128).unwrap();
+    }
+
+    pub fn record_out(&mut self, amount: u64) {
+        self.transfers_out = self.transfers_out.checked_add(1).unwrap();
+        self.volume_out = self.volume_out.checked_add(amount as u128).unwrap();
+    }
+}
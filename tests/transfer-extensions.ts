@@ -12,21 +12,28 @@ import {
 import {
   ExtensionType,
   TOKEN_2022_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  createMint,
+  mintTo,
   getMintLen,
   createInitializeMintInstruction,
   createInitializeTransferHookInstruction,
+  createInitializeTransferFeeConfigInstruction,
   createAssociatedTokenAccount,
+  createAccount,
   createMintToInstruction,
   createTransferCheckedWithTransferHookInstruction,
   getAccount,
   getMint,
   getTransferHook,
   addExtraAccountMetasForExecute,
+  getAssociatedTokenAddressSync,
 } from "@solana/spl-token";
 
 import { TransferExtensions } from "../target/types/transfer_extensions";
 import { TransferHook } from "../target/types/transfer_hook";
 import { assert } from "chai";
+import { keccak_256 } from "@noble/hashes/sha3";
 
 describe("transfer-extensions", () => {
   // Configure the client to use the local cluster.
@@ -44,6 +51,21 @@ describe("transfer-extensions", () => {
     console.log("Your transaction signature", tx);
   });
 
+  it("Rejects migration of current layout", async () => {
+    const [globalProgramData] = PublicKey.findProgramAddressSync(
+      [Buffer.from("global-program-data")],
+      program.programId
+    );
+    try {
+      await program.methods.migrateAccount()
+        .accounts({ account: globalProgramData })
+        .rpc();
+      assert.fail("migration should fail");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "AccountAlreadyMigrated");
+    }
+  });
+
   // Generate keypair to use as address for the transfer-hook enabled mint
   const mint = new Keypair();
   const decimals = 9;
@@ -85,22 +107,26 @@ describe("transfer-extensions", () => {
     console.log(`Transaction Signature: ${txSig}`);
   });
 
-  it("Initializes Mint Counters", async () => {
-    const tx = await program.methods.initializeMintCounterIn()
-      .accounts({
-        mint: mint.publicKey,
-      })
-      .signers([wallet.payer])
-      .rpc();
-    console.log("Mint CounterIn transaction signature", tx);
+  const TRANSFER_STATS_SEED = "transfer-stats";
+  const transferStatsPda = (subject: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from(TRANSFER_STATS_SEED),
+        subject.toBuffer(),
+        mint.publicKey.toBuffer(),
+      ],
+      transferHookProgram.programId
+    )[0];
 
-    const mintCounterOutTxSignature = await program.methods.initializeMintCounterOut()
+  it("Initializes Mint Stats", async () => {
+    const tx = await transferHookProgram.methods.initializeTransferStats()
       .accounts({
         mint: mint.publicKey,
+        subject: mint.publicKey,
       })
       .signers([wallet.payer])
       .rpc();
-    console.log("Mint CounterOut transaction signature", mintCounterOutTxSignature);
+    console.log("Mint TransferStats transaction signature", tx);
   });
 
   const sender = new Keypair();
@@ -120,23 +146,40 @@ describe("transfer-extensions", () => {
       TOKEN_2022_PROGRAM_ID,
     );
 
-    const tx = await program.methods.initializeWalletCounterIn()
+    const tx = await transferHookProgram.methods.initializeTransferStats()
       .accounts({
         mint: mint.publicKey,
-        userWallet: sender.publicKey,
+        subject: senderTokenAccountPubkey,
       })
       .signers([wallet.payer])
       .rpc();
-    console.log("Sender CounterIn transaction signature", tx);
+    console.log("Sender TransferStats transaction signature", tx);
+  });
+
+  const GLOBAL_STATS_SHARD_SEED = "global-stats-shard";
+  // shard is picked by the first byte of the source token account owner
+  const globalStatsShardPda = (owner: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from(GLOBAL_STATS_SHARD_SEED), owner.toBuffer().subarray(0, 1)],
+      transferHookProgram.programId
+    )[0];
 
-    const senderCounterOutTxSignature = await program.methods.initializeWalletCounterOut()
+  it("Initializes Sender Global Stats Shard", async () => {
+    const tx = await transferHookProgram.methods.initializeGlobalStatsShard(sender.publicKey.toBuffer()[0])
+      .signers([wallet.payer])
+      .rpc();
+    console.log("Sender GlobalStatsShard transaction signature", tx);
+  });
+
+  it("Initializes Sender Owner Stats", async () => {
+    const tx = await transferHookProgram.methods.initializeTransferStats()
       .accounts({
         mint: mint.publicKey,
-        userWallet: sender.publicKey,
+        subject: sender.publicKey,
       })
       .signers([wallet.payer])
       .rpc();
-    console.log("Sender CounterOut transaction signature", senderCounterOutTxSignature);
+    console.log("Sender owner TransferStats transaction signature", tx);
   });
 
   const recipient = new Keypair();
@@ -151,23 +194,14 @@ describe("transfer-extensions", () => {
       TOKEN_2022_PROGRAM_ID,
     );
 
-    const tx = await program.methods.initializeWalletCounterIn()
-      .accounts({
-        mint: mint.publicKey,
-        userWallet: recipient.publicKey,
-      })
-      .signers([wallet.payer])
-      .rpc();
-    console.log("Recipient CounterIn transaction signature", tx);
-
-    const recipientCounterOutTxSignature = await program.methods.initializeWalletCounterOut()
+    const tx = await transferHookProgram.methods.initializeTransferStats()
       .accounts({
         mint: mint.publicKey,
-        userWallet: recipient.publicKey,
+        subject: recipientTokenAccountPubkey,
       })
       .signers([wallet.payer])
       .rpc();
-    console.log("Recipient CounterOut transaction signature", recipientCounterOutTxSignature);
+    console.log("Recipient TransferStats transaction signature", tx);
   });
 
   it("Mint Tokens", async () => {
@@ -201,7 +235,7 @@ describe("transfer-extensions", () => {
   // Account to store extra accounts required by the transfer hook instruction
   it("Create ExtraAccountMetaList Account", async () => {
     const initializeExtraAccountMetaListInstruction = await transferHookProgram.methods
-      .initializeExtraAccountMetaList()
+      .initializeExtraAccountMetaList({ counters: { tokenAccount: {} }, royalties: false, ownerPolicy: false, allowlist: false, lockups: false })
       .accounts({
         payer: wallet.publicKey,
         mint: mint.publicKey,
@@ -251,6 +285,12 @@ describe("transfer-extensions", () => {
 
     const tokenAccount = await getAccount(provider.connection, recipientTokenAccountPubkey, undefined, TOKEN_2022_PROGRAM_ID);
     assert.equal(Number(tokenAccount.amount), amount);
+
+    const recipientStats = await transferHookProgram.account.transferStats.fetch(transferStatsPda(recipientTokenAccountPubkey));
+    assert.equal(recipientStats.transfersIn.toNumber(), 1);
+    assert.equal(recipientStats.volumeIn.toNumber(), amount);
+    const senderStats = await transferHookProgram.account.transferStats.fetch(transferStatsPda(senderTokenAccountPubkey));
+    assert.equal(senderStats.transfersOut.toNumber(), 1);
   });
 
   const recipient2 = new Keypair();
@@ -265,100 +305,54 @@ describe("transfer-extensions", () => {
       TOKEN_2022_PROGRAM_ID,
     );
 
-    const tx = await program.methods.initializeWalletCounterIn()
+    const tx = await transferHookProgram.methods.initializeTransferStats()
       .accounts({
         mint: mint.publicKey,
-        userWallet: recipient2.publicKey,
+        subject: recipient2TokenAccountPubkey,
       })
       .signers([wallet.payer])
       .rpc();
-    console.log("Recipient2 CounterIn transaction signature", tx);
+    console.log("Recipient2 TransferStats transaction signature", tx);
+  });
 
-    const recipient2CounterOutTxSignature = await program.methods.initializeWalletCounterOut()
+  it("Initializes Stats for non-associated Token Account", async () => {
+    const auxiliaryTokenAccountPubkey = await createAccount(
+      provider.connection,
+      wallet.payer,
+      mint.publicKey,
+      recipient2.publicKey,
+      new Keypair(),
+      undefined,
+      TOKEN_2022_PROGRAM_ID,
+    );
+
+    const tx = await transferHookProgram.methods.initializeTransferStats()
       .accounts({
         mint: mint.publicKey,
-        userWallet: recipient2.publicKey,
+        subject: auxiliaryTokenAccountPubkey,
       })
       .signers([wallet.payer])
       .rpc();
-    console.log("Recipient2 CounterOut transaction signature", recipient2CounterOutTxSignature);
+    console.log("Auxiliary TransferStats transaction signature", tx);
   });
 
-  const COUNTER_IN_SEED = "counter-in";
-  const COUNTER_OUT_SEED = "counter-out";
   const GLOBAL_PROGRAM_DATA_SEED = "global-program-data";
-  let recipient1walletCounterInPda: PublicKey;
-  let recipient1walletCounterOutPda: PublicKey;
-  let recipient2walletCounterInPda: PublicKey;
-  let recipient2walletCounterOutPda: PublicKey;
-  let mintCounterInPda: PublicKey;
-  let mintCounterOutPda: PublicKey;
+  let recipient1StatsPda: PublicKey;
+  let recipient2StatsPda: PublicKey;
+  let mintStatsPda: PublicKey;
   let globalProgramDataPda: PublicKey;
-  let senderWalletCounterInPda: PublicKey;
-  let senderWalletCounterOutPda: PublicKey;
+  let senderStatsPda: PublicKey;
   it("multiple transfers", async () => {
-    [recipient1walletCounterInPda] = PublicKey.findProgramAddressSync(
-      [
-        Buffer.from(COUNTER_IN_SEED),
-        recipientTokenAccountPubkey.toBuffer(),
-      ],
-      program.programId
-    );
-    [recipient1walletCounterOutPda] = PublicKey.findProgramAddressSync(
-      [
-        Buffer.from(COUNTER_OUT_SEED),
-        recipientTokenAccountPubkey.toBuffer(),
-      ],
-      program.programId
-    );
-    [recipient2walletCounterInPda] = PublicKey.findProgramAddressSync(
-      [
-        Buffer.from(COUNTER_IN_SEED),
-        recipient2TokenAccountPubkey.toBuffer(),
-      ],
-      program.programId
-    );
-    [recipient2walletCounterOutPda] = PublicKey.findProgramAddressSync(
-      [
-        Buffer.from(COUNTER_OUT_SEED),
-        recipient2TokenAccountPubkey.toBuffer(),
-      ],
-      program.programId
-    );
-    [mintCounterInPda] = PublicKey.findProgramAddressSync(
-      [
-        Buffer.from(COUNTER_IN_SEED),
-        mint.publicKey.toBuffer(),
-      ],
-      program.programId
-    );
-    [mintCounterOutPda] = PublicKey.findProgramAddressSync(
-      [
-        Buffer.from(COUNTER_OUT_SEED),
-        mint.publicKey.toBuffer(),
-      ],
-      program.programId
-    );
+    recipient1StatsPda = transferStatsPda(recipientTokenAccountPubkey);
+    recipient2StatsPda = transferStatsPda(recipient2TokenAccountPubkey);
+    mintStatsPda = transferStatsPda(mint.publicKey);
     [globalProgramDataPda] = PublicKey.findProgramAddressSync(
       [
         Buffer.from(GLOBAL_PROGRAM_DATA_SEED),
       ],
       program.programId
     );
-    [senderWalletCounterInPda] = PublicKey.findProgramAddressSync(
-      [
-        Buffer.from(COUNTER_IN_SEED),
-        senderTokenAccountPubkey.toBuffer(),
-      ],
-      program.programId
-    );
-    [senderWalletCounterOutPda] = PublicKey.findProgramAddressSync(
-      [
-        Buffer.from(COUNTER_OUT_SEED),
-        senderTokenAccountPubkey.toBuffer(),
-      ],
-      program.programId
-    );
+    senderStatsPda = transferStatsPda(senderTokenAccountPubkey);
     // 1 tokens
     const amount1 = 1 * 10 ** decimals;
     const amount2 = 2 * 10 ** decimals;
@@ -366,6 +360,8 @@ describe("transfer-extensions", () => {
     const multiTransfersInstruction = program.instruction.multiTransfers(
       new anchor.BN(amount1),
       new anchor.BN(amount2),
+      { gross: {} },
+      { gross: {} },
       {
         accounts: {
           sourceAccount: senderTokenAccountPubkey,
@@ -421,14 +417,10 @@ describe("transfer-extensions", () => {
     console.log("Recipient2AssocAccount:", recipient2TokenAccountPubkey.toString());
     console.log("mint:", mint.publicKey.toString());
     console.log("SenderAssocAccount:", senderTokenAccountPubkey.toString());
-    console.log("Sender Wallet Counter In PDA:", senderWalletCounterInPda.toString());
-    console.log("Sender Wallet Counter Out PDA:", senderWalletCounterOutPda.toString());
-    console.log("Recipient1 Wallet Counter In PDA:", recipient1walletCounterInPda.toString());
-    console.log("Recipient2 Wallet Counter In PDA:", recipient2walletCounterInPda.toString());
-    console.log("Recipient1 Wallet Counter Out PDA:", recipient1walletCounterOutPda.toString());
-    console.log("Recipient2 Wallet Counter Out PDA:", recipient2walletCounterOutPda.toString());
-    console.log("Mint Counter In PDA:", mintCounterInPda.toString());
-    console.log("Mint Counter Out PDA:", mintCounterOutPda.toString());
+    console.log("Sender Stats PDA:", senderStatsPda.toString());
+    console.log("Recipient1 Stats PDA:", recipient1StatsPda.toString());
+    console.log("Recipient2 Stats PDA:", recipient2StatsPda.toString());
+    console.log("Mint Stats PDA:", mintStatsPda.toString());
     console.log("Global Program Data PDA:", globalProgramDataPda.toString());
     console.log("*".repeat(50));
     console.log("*".repeat(50));
@@ -504,14 +496,10 @@ describe("transfer-extensions", () => {
     console.log("Recipient2AssocAccount:", recipient2TokenAccountPubkey.toString());
     console.log("mint:", mint.publicKey.toString());
     console.log("SenderAssocAccount:", senderTokenAccountPubkey.toString());
-    console.log("Sender Wallet Counter In PDA:", senderWalletCounterInPda.toString());
-    console.log("Sender Wallet Counter Out PDA:", senderWalletCounterOutPda.toString());
-    console.log("Recipient1 Wallet Counter In PDA:", recipient1walletCounterInPda.toString());
-    console.log("Recipient2 Wallet Counter In PDA:", recipient2walletCounterInPda.toString());
-    console.log("Recipient1 Wallet Counter Out PDA:", recipient1walletCounterOutPda.toString());
-    console.log("Recipient2 Wallet Counter Out PDA:", recipient2walletCounterOutPda.toString());
-    console.log("Mint Counter In PDA:", mintCounterInPda.toString());
-    console.log("Mint Counter Out PDA:", mintCounterOutPda.toString());
+    console.log("Sender Stats PDA:", senderStatsPda.toString());
+    console.log("Recipient1 Stats PDA:", recipient1StatsPda.toString());
+    console.log("Recipient2 Stats PDA:", recipient2StatsPda.toString());
+    console.log("Mint Stats PDA:", mintStatsPda.toString());
     console.log("Global Program Data PDA:", globalProgramDataPda.toString());
     console.log("*".repeat(50));
     console.log("*".repeat(50));
@@ -537,4 +525,1014 @@ describe("transfer-extensions", () => {
     tokenAccount = await getAccount(provider.connection, recipientTokenAccountPubkey, undefined, TOKEN_2022_PROGRAM_ID);
     assert.equal(Number(tokenAccount.amount), recipientBalanceBefore + amount1);
   });
+
+  it("Aggregates Global Stats", async () => {
+    const globalStats = await transferHookProgram.methods.aggregateGlobalStats()
+      .remainingAccounts([
+        { pubkey: globalStatsShardPda(sender.publicKey), isSigner: false, isWritable: false },
+      ])
+      .view();
+    assert.equal(globalStats.shardsCount, 1);
+    // single transfer, two multi transfers legs and one heap experiment leg
+    assert.equal(globalStats.transfersCount.toNumber(), 4);
+  });
+
+  // Mint with both TransferHook and TransferFeeConfig extensions, 1% fee
+  const feeMint = new Keypair();
+  const feeBasisPoints = 100;
+  let senderFeeTokenAccountPubkey: PublicKey;
+  let recipientFeeTokenAccountPubkey: PublicKey;
+  let recipient2FeeTokenAccountPubkey: PublicKey;
+  it("Create Mint Account with Transfer Hook and Transfer Fee Extensions", async () => {
+    const extensions = [ExtensionType.TransferHook, ExtensionType.TransferFeeConfig];
+    const mintLen = getMintLen(extensions);
+    const lamports =
+      await provider.connection.getMinimumBalanceForRentExemption(mintLen);
+
+    const transaction = new Transaction().add(
+      SystemProgram.createAccount({
+        fromPubkey: wallet.publicKey,
+        newAccountPubkey: feeMint.publicKey,
+        space: mintLen,
+        lamports: lamports,
+        programId: TOKEN_2022_PROGRAM_ID,
+      }),
+      createInitializeTransferHookInstruction(
+        feeMint.publicKey,
+        wallet.publicKey,
+        transferHookProgram.programId,
+        TOKEN_2022_PROGRAM_ID,
+      ),
+      createInitializeTransferFeeConfigInstruction(
+        feeMint.publicKey,
+        wallet.publicKey,
+        wallet.publicKey,
+        feeBasisPoints,
+        BigInt(10 ** decimals),
+        TOKEN_2022_PROGRAM_ID,
+      ),
+      createInitializeMintInstruction(
+        feeMint.publicKey,
+        decimals,
+        wallet.payer.publicKey,
+        null,
+        TOKEN_2022_PROGRAM_ID,
+      ),
+    );
+    await sendAndConfirmTransaction(provider.connection, transaction, [wallet.payer, feeMint]);
+
+    [senderFeeTokenAccountPubkey, recipientFeeTokenAccountPubkey, recipient2FeeTokenAccountPubkey] =
+      await Promise.all([sender, recipient, recipient2].map((owner) =>
+        createAssociatedTokenAccount(
+          provider.connection,
+          wallet.payer,
+          feeMint.publicKey,
+          owner.publicKey,
+          undefined,
+          TOKEN_2022_PROGRAM_ID,
+        )
+      ));
+
+    for (const subject of [
+      feeMint.publicKey,
+      senderFeeTokenAccountPubkey,
+      recipientFeeTokenAccountPubkey,
+      recipient2FeeTokenAccountPubkey,
+    ]) {
+      await transferHookProgram.methods.initializeTransferStats()
+        .accounts({ mint: feeMint.publicKey, subject })
+        .signers([wallet.payer])
+        .rpc();
+    }
+
+    await transferHookProgram.methods
+      .initializeExtraAccountMetaList({ counters: { tokenAccount: {} }, royalties: false, ownerPolicy: false, allowlist: false, lockups: false })
+      .accounts({ payer: wallet.publicKey, mint: feeMint.publicKey })
+      .rpc();
+
+    await sendAndConfirmTransaction(
+      provider.connection,
+      new Transaction().add(
+        createMintToInstruction(
+          feeMint.publicKey,
+          senderFeeTokenAccountPubkey,
+          wallet.publicKey,
+          100 * 10 ** decimals,
+          [],
+          TOKEN_2022_PROGRAM_ID
+        )
+      ),
+      [wallet.payer],
+    );
+  });
+
+  it("multiple transfers with net and gross fee modes", async () => {
+    const amount1 = 1 * 10 ** decimals;
+    const amount2 = 2 * 10 ** decimals;
+
+    const multiTransfersInstruction = program.instruction.multiTransfers(
+      new anchor.BN(amount1),
+      new anchor.BN(amount2),
+      { net: {} },
+      { gross: {} },
+      {
+        accounts: {
+          sourceAccount: senderFeeTokenAccountPubkey,
+          destinationAccount1: recipientFeeTokenAccountPubkey,
+          destinationAccount2: recipient2FeeTokenAccountPubkey,
+          mint: feeMint.publicKey,
+          signer: sender.publicKey,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        },
+        signers: [sender],
+      })
+
+    // the hook is executed with the gross amount of the net leg
+    const fee1 = Math.ceil((amount1 * feeBasisPoints) / (10_000 - feeBasisPoints));
+    for (const [destination, amount] of [
+      [recipientFeeTokenAccountPubkey, amount1 + fee1],
+      [recipient2FeeTokenAccountPubkey, amount2],
+    ] as [PublicKey, number][]) {
+      await addExtraAccountMetasForExecute(
+        provider.connection,
+        multiTransfersInstruction,
+        transferHookProgram.programId,
+        senderFeeTokenAccountPubkey,
+        feeMint.publicKey,
+        destination,
+        sender.publicKey,
+        amount,
+        undefined
+      );
+    }
+
+    const transaction = new Transaction().add(
+      ComputeBudgetProgram.setComputeUnitLimit({ units: 1000000 }),
+      multiTransfersInstruction,
+    );
+    const txSig = await sendAndConfirmTransaction(provider.connection, transaction, [sender]);
+    console.log(`Multi Transfer with fee Transaction Signature: ${txSig}`);
+
+    const recipientAccount = await getAccount(provider.connection, recipientFeeTokenAccountPubkey, undefined, TOKEN_2022_PROGRAM_ID);
+    assert.equal(Number(recipientAccount.amount), amount1);
+    const recipient2Account = await getAccount(provider.connection, recipient2FeeTokenAccountPubkey, undefined, TOKEN_2022_PROGRAM_ID);
+    assert.equal(Number(recipient2Account.amount), amount2 - (amount2 * feeBasisPoints) / 10_000);
+  });
+
+  it("multiple transfers of legacy Token program mint", async () => {
+    const legacyMint = await createMint(
+      provider.connection,
+      wallet.payer,
+      wallet.publicKey,
+      null,
+      decimals,
+      undefined,
+      undefined,
+      TOKEN_PROGRAM_ID,
+    );
+    const [senderLegacyTokenAccount, recipientLegacyTokenAccount, recipient2LegacyTokenAccount] =
+      await Promise.all([sender, recipient, recipient2].map((owner) =>
+        createAssociatedTokenAccount(
+          provider.connection,
+          wallet.payer,
+          legacyMint,
+          owner.publicKey,
+          undefined,
+          TOKEN_PROGRAM_ID,
+        )
+      ));
+    await mintTo(
+      provider.connection,
+      wallet.payer,
+      legacyMint,
+      senderLegacyTokenAccount,
+      wallet.payer,
+      100 * 10 ** decimals,
+      [],
+      undefined,
+      TOKEN_PROGRAM_ID,
+    );
+
+    const legacyStatsPda = (subject: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from(TRANSFER_STATS_SEED), subject.toBuffer(), legacyMint.toBuffer()],
+        transferHookProgram.programId
+      )[0];
+    const [transferRecorder] = PublicKey.findProgramAddressSync(
+      [Buffer.from("transfer-recorder")],
+      program.programId
+    );
+    const subjects = [
+      senderLegacyTokenAccount,
+      recipientLegacyTokenAccount,
+      recipient2LegacyTokenAccount,
+      legacyMint,
+    ];
+    for (const subject of subjects) {
+      await transferHookProgram.methods.initializeTransferStats()
+        .accounts({ mint: legacyMint, subject })
+        .signers([wallet.payer])
+        .rpc();
+    }
+
+    const amount1 = 1 * 10 ** decimals;
+    const amount2 = 2 * 10 ** decimals;
+    // no hook fires for legacy mints, so stats are passed in remaining accounts
+    await program.methods
+      .multiTransfers(new anchor.BN(amount1), new anchor.BN(amount2), { gross: {} }, { gross: {} })
+      .accounts({
+        sourceAccount: senderLegacyTokenAccount,
+        destinationAccount1: recipientLegacyTokenAccount,
+        destinationAccount2: recipient2LegacyTokenAccount,
+        mint: legacyMint,
+        signer: sender.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(
+        [...subjects.map(legacyStatsPda), globalStatsShardPda(sender.publicKey)].map((pubkey) => ({
+          pubkey,
+          isSigner: false,
+          isWritable: true,
+        })).concat(
+          // the stats are recorded by the hook program, signed with the transfer recorder
+          [transferHookProgram.programId, transferRecorder].map((pubkey) => ({
+            pubkey,
+            isSigner: false,
+            isWritable: false,
+          }))
+        )
+      )
+      .signers([sender])
+      .rpc();
+
+    const recipient2Account = await getAccount(provider.connection, recipient2LegacyTokenAccount, undefined, TOKEN_PROGRAM_ID);
+    assert.equal(Number(recipient2Account.amount), amount2);
+    const senderStats = await transferHookProgram.account.transferStats.fetch(legacyStatsPda(senderLegacyTokenAccount));
+    assert.equal(senderStats.transfersOut.toNumber(), 2);
+    assert.equal(senderStats.volumeOut.toNumber(), amount1 + amount2);
+  });
+
+  it("batch transfers of several mints", async () => {
+    const amount1 = 1 * 10 ** decimals;
+    const amount2 = 3 * 10 ** decimals;
+    const accountMeta = (pubkey: PublicKey, isWritable: boolean) => ({ pubkey, isSigner: false, isWritable });
+
+    const recipientBalanceBefore = Number(
+      (await getAccount(provider.connection, recipientTokenAccountPubkey, undefined, TOKEN_2022_PROGRAM_ID)).amount
+    );
+
+    const batchTransfersInstruction = await program.methods
+      .batchTransfers([
+        { sourceIndex: 0, mintIndex: 1, destinationIndex: 2, amount: new anchor.BN(amount1), feeMode: { gross: {} }, statsIndexes: null },
+        { sourceIndex: 3, mintIndex: 4, destinationIndex: 5, amount: new anchor.BN(amount2), feeMode: { net: {} }, statsIndexes: null },
+      ])
+      .accounts({ signer: sender.publicKey })
+      .remainingAccounts([
+        accountMeta(senderTokenAccountPubkey, true),
+        accountMeta(mint.publicKey, false),
+        accountMeta(recipientTokenAccountPubkey, true),
+        accountMeta(senderFeeTokenAccountPubkey, true),
+        accountMeta(feeMint.publicKey, false),
+        accountMeta(recipientFeeTokenAccountPubkey, true),
+        accountMeta(TOKEN_2022_PROGRAM_ID, false),
+      ])
+      .instruction();
+
+    // every mint resolves its own transfer hook extra accounts
+    const fee2 = Math.ceil((amount2 * feeBasisPoints) / (10_000 - feeBasisPoints));
+    for (const [source, legMint, destination, amount] of [
+      [senderTokenAccountPubkey, mint.publicKey, recipientTokenAccountPubkey, amount1],
+      [senderFeeTokenAccountPubkey, feeMint.publicKey, recipientFeeTokenAccountPubkey, amount2 + fee2],
+    ] as [PublicKey, PublicKey, PublicKey, number][]) {
+      await addExtraAccountMetasForExecute(
+        provider.connection,
+        batchTransfersInstruction,
+        transferHookProgram.programId,
+        source,
+        legMint,
+        destination,
+        sender.publicKey,
+        amount,
+        undefined
+      );
+    }
+
+    const recipientFeeBalanceBefore = Number(
+      (await getAccount(provider.connection, recipientFeeTokenAccountPubkey, undefined, TOKEN_2022_PROGRAM_ID)).amount
+    );
+    const transaction = new Transaction().add(
+      ComputeBudgetProgram.setComputeUnitLimit({ units: 1000000 }),
+      batchTransfersInstruction,
+    );
+    const txSig = await sendAndConfirmTransaction(provider.connection, transaction, [sender]);
+    console.log(`Batch Transfers Transaction Signature: ${txSig}`);
+
+    const recipientAccount = await getAccount(provider.connection, recipientTokenAccountPubkey, undefined, TOKEN_2022_PROGRAM_ID);
+    assert.equal(Number(recipientAccount.amount), recipientBalanceBefore + amount1);
+    const recipientFeeAccount = await getAccount(provider.connection, recipientFeeTokenAccountPubkey, undefined, TOKEN_2022_PROGRAM_ID);
+    assert.equal(Number(recipientFeeAccount.amount), recipientFeeBalanceBefore + amount2);
+  });
+
+  it("settles legs of several authorities against net positions", async () => {
+    const amount1 = 2 * 10 ** decimals;
+    const amount2 = 1 * 10 ** decimals;
+    const accountMeta = (pubkey: PublicKey, isWritable: boolean, isSigner = false) => ({ pubkey, isSigner, isWritable });
+
+    // the hook records the transfer of the recipient in its own global stats shard
+    if (recipient.publicKey.toBuffer()[0] !== sender.publicKey.toBuffer()[0]) {
+      await transferHookProgram.methods.initializeGlobalStatsShard(recipient.publicKey.toBuffer()[0])
+        .signers([wallet.payer])
+        .rpc();
+    }
+
+    const balances = async () => Promise.all(
+      [senderTokenAccountPubkey, recipientTokenAccountPubkey].map(async (tokenAccount) =>
+        Number((await getAccount(provider.connection, tokenAccount, undefined, TOKEN_2022_PROGRAM_ID)).amount)
+      )
+    );
+    const [senderBalanceBefore, recipientBalanceBefore] = await balances();
+
+    const settleInstruction = await program.methods
+      .settle(
+        [
+          { authorityIndex: 3, transfer: { sourceIndex: 0, mintIndex: 1, destinationIndex: 2, amount: new anchor.BN(amount1), feeMode: { gross: {} }, statsIndexes: null } },
+          { authorityIndex: 4, transfer: { sourceIndex: 2, mintIndex: 1, destinationIndex: 0, amount: new anchor.BN(amount2), feeMode: { gross: {} }, statsIndexes: null } },
+        ],
+        [
+          { tokenAccountIndex: 0, delta: new anchor.BN(amount2 - amount1) },
+          { tokenAccountIndex: 2, delta: new anchor.BN(amount1 - amount2) },
+        ],
+      )
+      .remainingAccounts([
+        accountMeta(senderTokenAccountPubkey, true),
+        accountMeta(mint.publicKey, false),
+        accountMeta(recipientTokenAccountPubkey, true),
+        accountMeta(sender.publicKey, false, true),
+        accountMeta(recipient.publicKey, false, true),
+        accountMeta(TOKEN_2022_PROGRAM_ID, false),
+      ])
+      .instruction();
+
+    for (const [source, destination, owner, amount] of [
+      [senderTokenAccountPubkey, recipientTokenAccountPubkey, sender.publicKey, amount1],
+      [recipientTokenAccountPubkey, senderTokenAccountPubkey, recipient.publicKey, amount2],
+    ] as [PublicKey, PublicKey, PublicKey, number][]) {
+      await addExtraAccountMetasForExecute(
+        provider.connection,
+        settleInstruction,
+        transferHookProgram.programId,
+        source,
+        mint.publicKey,
+        destination,
+        owner,
+        amount,
+        undefined
+      );
+    }
+
+    const transaction = new Transaction().add(
+      ComputeBudgetProgram.setComputeUnitLimit({ units: 1000000 }),
+      settleInstruction,
+    );
+    const txSig = await sendAndConfirmTransaction(provider.connection, transaction, [wallet.payer, sender, recipient]);
+    console.log(`Settle Transaction Signature: ${txSig}`);
+
+    const [senderBalanceAfter, recipientBalanceAfter] = await balances();
+    assert.equal(senderBalanceAfter, senderBalanceBefore - amount1 + amount2);
+    assert.equal(recipientBalanceAfter, recipientBalanceBefore + amount1 - amount2);
+  });
+
+  it("escrows hook-enabled tokens until a timelock passes", async () => {
+    const amount = 5 * 10 ** decimals;
+    const id = new anchor.BN(1);
+    const [escrow] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), sender.publicKey.toBuffer(), id.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const vault = getAssociatedTokenAddressSync(mint.publicKey, escrow, true, TOKEN_2022_PROGRAM_ID);
+
+    // the hook records both the deposit into and the release from the vault
+    await transferHookProgram.methods.initializeTransferStats()
+      .accounts({ mint: mint.publicKey, subject: vault })
+      .signers([wallet.payer])
+      .rpc();
+    const escrowShard = globalStatsShardPda(escrow);
+    if ((await provider.connection.getAccountInfo(escrowShard)) === null) {
+      await transferHookProgram.methods.initializeGlobalStatsShard(escrow.toBuffer()[0])
+        .signers([wallet.payer])
+        .rpc();
+    }
+
+    const createEscrowInstruction = await program.methods
+      .createEscrow(id, new anchor.BN(amount), { timelock: { releaseAt: new anchor.BN(0) } })
+      .accounts({
+        makerTokenAccount: senderTokenAccountPubkey,
+        mint: mint.publicKey,
+        receiver: recipient.publicKey,
+        maker: sender.publicKey,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .instruction();
+    await addExtraAccountMetasForExecute(
+      provider.connection,
+      createEscrowInstruction,
+      transferHookProgram.programId,
+      senderTokenAccountPubkey,
+      mint.publicKey,
+      vault,
+      sender.publicKey,
+      amount,
+      undefined
+    );
+    await sendAndConfirmTransaction(
+      provider.connection,
+      new Transaction().add(
+        ComputeBudgetProgram.setComputeUnitLimit({ units: 1000000 }),
+        createEscrowInstruction,
+      ),
+      [wallet.payer, sender],
+    );
+    assert.equal((await program.account.escrow.fetch(escrow)).amount.toNumber(), amount);
+
+    const recipientBalanceBefore = Number(
+      (await getAccount(provider.connection, recipientTokenAccountPubkey, undefined, TOKEN_2022_PROGRAM_ID)).amount
+    );
+    // past the timelock anyone can release
+    const releaseEscrowInstruction = await program.methods
+      .releaseEscrow()
+      .accounts({
+        escrow,
+        receiverTokenAccount: recipientTokenAccountPubkey,
+        mint: mint.publicKey,
+        maker: sender.publicKey,
+        authority: wallet.publicKey,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .instruction();
+    await addExtraAccountMetasForExecute(
+      provider.connection,
+      releaseEscrowInstruction,
+      transferHookProgram.programId,
+      vault,
+      mint.publicKey,
+      recipientTokenAccountPubkey,
+      escrow,
+      amount,
+      undefined
+    );
+    const txSig = await sendAndConfirmTransaction(
+      provider.connection,
+      new Transaction().add(
+        ComputeBudgetProgram.setComputeUnitLimit({ units: 1000000 }),
+        releaseEscrowInstruction,
+      ),
+      [wallet.payer],
+    );
+    console.log(`Release Escrow Transaction Signature: ${txSig}`);
+
+    const recipientAccount = await getAccount(provider.connection, recipientTokenAccountPubkey, undefined, TOKEN_2022_PROGRAM_ID);
+    assert.equal(Number(recipientAccount.amount), recipientBalanceBefore + amount);
+    assert.isNull(await provider.connection.getAccountInfo(escrow));
+    assert.isNull(await provider.connection.getAccountInfo(vault));
+    const vaultStats = await transferHookProgram.account.transferStats.fetch(transferStatsPda(vault));
+    assert.equal(vaultStats.transfersIn.toNumber(), 1);
+    assert.equal(vaultStats.transfersOut.toNumber(), 1);
+  });
+
+  it("streams hook-enabled tokens to a recipient", async () => {
+    const ratePerSecond = 1000;
+    const id = new anchor.BN(1);
+    const [stream] = PublicKey.findProgramAddressSync(
+      [Buffer.from("payment-stream"), sender.publicKey.toBuffer(), id.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const vault = getAssociatedTokenAddressSync(mint.publicKey, stream, true, TOKEN_2022_PROGRAM_ID);
+
+    await transferHookProgram.methods.initializeTransferStats()
+      .accounts({ mint: mint.publicKey, subject: vault })
+      .signers([wallet.payer])
+      .rpc();
+    const streamShard = globalStatsShardPda(stream);
+    if ((await provider.connection.getAccountInfo(streamShard)) === null) {
+      await transferHookProgram.methods.initializeGlobalStatsShard(stream.toBuffer()[0])
+        .signers([wallet.payer])
+        .rpc();
+    }
+
+    // every transfer of the stream resolves the same extra accounts
+    const sendWithHook = async (
+      instruction: anchor.web3.TransactionInstruction,
+      source: PublicKey,
+      destination: PublicKey,
+      owner: PublicKey,
+      signers: Keypair[],
+    ) => {
+      await addExtraAccountMetasForExecute(
+        provider.connection,
+        instruction,
+        transferHookProgram.programId,
+        source,
+        mint.publicKey,
+        destination,
+        owner,
+        0,
+        undefined
+      );
+      return sendAndConfirmTransaction(
+        provider.connection,
+        new Transaction().add(
+          ComputeBudgetProgram.setComputeUnitLimit({ units: 1000000 }),
+          instruction,
+        ),
+        signers,
+      );
+    };
+
+    const now = Math.floor(Date.now() / 1000);
+    const start = now - 100;
+    const end = now + 100;
+    await sendWithHook(
+      await program.methods
+        .createStream(id, new anchor.BN(ratePerSecond), new anchor.BN(start), new anchor.BN(end))
+        .accounts({
+          senderTokenAccount: senderTokenAccountPubkey,
+          mint: mint.publicKey,
+          recipient: recipient.publicKey,
+          sender: sender.publicKey,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .instruction(),
+      senderTokenAccountPubkey,
+      vault,
+      sender.publicKey,
+      [wallet.payer, sender],
+    );
+    const vaultAccount = await getAccount(provider.connection, vault, undefined, TOKEN_2022_PROGRAM_ID);
+    assert.equal(Number(vaultAccount.amount), ratePerSecond * (end - start));
+
+    const recipientBalanceBefore = Number(
+      (await getAccount(provider.connection, recipientTokenAccountPubkey, undefined, TOKEN_2022_PROGRAM_ID)).amount
+    );
+    const txSig = await sendWithHook(
+      await program.methods
+        .withdrawFromStream()
+        .accounts({
+          stream,
+          recipientTokenAccount: recipientTokenAccountPubkey,
+          mint: mint.publicKey,
+          recipient: recipient.publicKey,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .instruction(),
+      vault,
+      recipientTokenAccountPubkey,
+      stream,
+      [wallet.payer, recipient],
+    );
+    console.log(`Withdraw From Stream Transaction Signature: ${txSig}`);
+
+    const withdrawn = (await program.account.paymentStream.fetch(stream)).withdrawn.toNumber();
+    assert.isAtLeast(withdrawn, ratePerSecond * 100);
+    const recipientAccount = await getAccount(provider.connection, recipientTokenAccountPubkey, undefined, TOKEN_2022_PROGRAM_ID);
+    assert.equal(Number(recipientAccount.amount), recipientBalanceBefore + withdrawn);
+
+    const cancelStreamInstruction = await program.methods
+      .cancelStream()
+      .accounts({
+        stream,
+        senderTokenAccount: senderTokenAccountPubkey,
+        recipientTokenAccount: recipientTokenAccountPubkey,
+        mint: mint.publicKey,
+        sender: sender.publicKey,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .instruction();
+    // the refund to the sender resolves its own destination stats
+    await addExtraAccountMetasForExecute(
+      provider.connection,
+      cancelStreamInstruction,
+      transferHookProgram.programId,
+      vault,
+      mint.publicKey,
+      senderTokenAccountPubkey,
+      stream,
+      0,
+      undefined
+    );
+    await sendWithHook(cancelStreamInstruction, vault, recipientTokenAccountPubkey, stream, [wallet.payer, sender]);
+
+    assert.isNull(await provider.connection.getAccountInfo(stream));
+    assert.isNull(await provider.connection.getAccountInfo(vault));
+  });
+
+  it("executes a due scheduled transfer from any cranker", async () => {
+    const amount = 3 * 10 ** decimals;
+    const tip = 10 ** (decimals - 2);
+    const id = new anchor.BN(1);
+    const [scheduledTransfer] = PublicKey.findProgramAddressSync(
+      [Buffer.from("scheduled-transfer"), sender.publicKey.toBuffer(), id.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const vault = getAssociatedTokenAddressSync(mint.publicKey, scheduledTransfer, true, TOKEN_2022_PROGRAM_ID);
+
+    await transferHookProgram.methods.initializeTransferStats()
+      .accounts({ mint: mint.publicKey, subject: vault })
+      .signers([wallet.payer])
+      .rpc();
+    const scheduledTransferShard = globalStatsShardPda(scheduledTransfer);
+    if ((await provider.connection.getAccountInfo(scheduledTransferShard)) === null) {
+      await transferHookProgram.methods.initializeGlobalStatsShard(scheduledTransfer.toBuffer()[0])
+        .signers([wallet.payer])
+        .rpc();
+    }
+
+    const createInstruction = await program.methods
+      .createScheduledTransfer(id, new anchor.BN(amount), new anchor.BN(tip), { slot: { slot: new anchor.BN(0) } })
+      .accounts({
+        creatorTokenAccount: senderTokenAccountPubkey,
+        destinationAccount: recipientTokenAccountPubkey,
+        mint: mint.publicKey,
+        creator: sender.publicKey,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .instruction();
+    await addExtraAccountMetasForExecute(
+      provider.connection,
+      createInstruction,
+      transferHookProgram.programId,
+      senderTokenAccountPubkey,
+      mint.publicKey,
+      vault,
+      sender.publicKey,
+      amount + tip,
+      undefined
+    );
+    await sendAndConfirmTransaction(
+      provider.connection,
+      new Transaction().add(
+        ComputeBudgetProgram.setComputeUnitLimit({ units: 1000000 }),
+        createInstruction,
+      ),
+      [wallet.payer, sender],
+    );
+
+    const balance = async (tokenAccount: PublicKey) =>
+      Number((await getAccount(provider.connection, tokenAccount, undefined, TOKEN_2022_PROGRAM_ID)).amount);
+    const recipientBalanceBefore = await balance(recipientTokenAccountPubkey);
+    const crankerBalanceBefore = await balance(recipient2TokenAccountPubkey);
+
+    // the cranker neither owns the vault nor the destination, the tip goes to a token account of its choice
+    const executeInstruction = await program.methods
+      .executeScheduledTransfer()
+      .accounts({
+        scheduledTransfer,
+        destinationAccount: recipientTokenAccountPubkey,
+        crankerTokenAccount: recipient2TokenAccountPubkey,
+        creatorTokenAccount: senderTokenAccountPubkey,
+        mint: mint.publicKey,
+        creator: sender.publicKey,
+        cranker: wallet.publicKey,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .instruction();
+    for (const [destination, transferAmount] of [
+      [recipientTokenAccountPubkey, amount],
+      [recipient2TokenAccountPubkey, tip],
+    ] as [PublicKey, number][]) {
+      await addExtraAccountMetasForExecute(
+        provider.connection,
+        executeInstruction,
+        transferHookProgram.programId,
+        vault,
+        mint.publicKey,
+        destination,
+        scheduledTransfer,
+        transferAmount,
+        undefined
+      );
+    }
+    const txSig = await sendAndConfirmTransaction(
+      provider.connection,
+      new Transaction().add(
+        ComputeBudgetProgram.setComputeUnitLimit({ units: 1000000 }),
+        executeInstruction,
+      ),
+      [wallet.payer],
+    );
+    console.log(`Execute Scheduled Transfer Transaction Signature: ${txSig}`);
+
+    assert.equal(await balance(recipientTokenAccountPubkey), recipientBalanceBefore + amount);
+    assert.equal(await balance(recipient2TokenAccountPubkey), crankerBalanceBefore + tip);
+    assert.isNull(await provider.connection.getAccountInfo(scheduledTransfer));
+  });
+
+  it("distributes a payment between splitter recipients", async () => {
+    const amount = 1_000_000_001;
+    const id = new anchor.BN(1);
+    const [splitter] = PublicKey.findProgramAddressSync(
+      [Buffer.from("splitter"), sender.publicKey.toBuffer(), id.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    await program.methods
+      .initializeSplitter(
+        id,
+        [
+          { tokenAccount: recipientTokenAccountPubkey, bps: 3333 },
+          { tokenAccount: recipient2TokenAccountPubkey, bps: 6667 },
+        ],
+        0,
+      )
+      .accounts({ mint: mint.publicKey, authority: sender.publicKey })
+      .signers([sender])
+      .rpc();
+
+    const balance = async (tokenAccount: PublicKey) =>
+      Number((await getAccount(provider.connection, tokenAccount, undefined, TOKEN_2022_PROGRAM_ID)).amount);
+    const recipientBalanceBefore = await balance(recipientTokenAccountPubkey);
+    const recipient2BalanceBefore = await balance(recipient2TokenAccountPubkey);
+
+    const accountMeta = (pubkey: PublicKey, isWritable: boolean) => ({ pubkey, isSigner: false, isWritable });
+    const distributeInstruction = await program.methods
+      .distribute(new anchor.BN(amount))
+      .accounts({ splitter, signer: sender.publicKey })
+      .remainingAccounts([
+        accountMeta(senderTokenAccountPubkey, true),
+        accountMeta(mint.publicKey, false),
+        accountMeta(recipientTokenAccountPubkey, true),
+        accountMeta(recipient2TokenAccountPubkey, true),
+        accountMeta(TOKEN_2022_PROGRAM_ID, false),
+      ])
+      .instruction();
+    for (const destination of [recipientTokenAccountPubkey, recipient2TokenAccountPubkey]) {
+      await addExtraAccountMetasForExecute(
+        provider.connection,
+        distributeInstruction,
+        transferHookProgram.programId,
+        senderTokenAccountPubkey,
+        mint.publicKey,
+        destination,
+        sender.publicKey,
+        0,
+        undefined
+      );
+    }
+    const txSig = await sendAndConfirmTransaction(
+      provider.connection,
+      new Transaction().add(
+        ComputeBudgetProgram.setComputeUnitLimit({ units: 1000000 }),
+        distributeInstruction,
+      ),
+      [wallet.payer, sender],
+    );
+    console.log(`Distribute Transaction Signature: ${txSig}`);
+
+    // shares are rounded down, the remainder goes to the first recipient
+    const recipient2Share = Math.floor((amount * 6667) / 10_000);
+    assert.equal(await balance(recipient2TokenAccountPubkey), recipient2BalanceBefore + recipient2Share);
+    assert.equal(await balance(recipientTokenAccountPubkey), recipientBalanceBefore + amount - recipient2Share);
+  });
+
+  it("claims an airdrop with a Merkle proof", async () => {
+    const id = new anchor.BN(1);
+    const [distributor] = PublicKey.findProgramAddressSync(
+      [Buffer.from("distributor"), wallet.publicKey.toBuffer(), id.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const vault = getAssociatedTokenAddressSync(mint.publicKey, distributor, true, TOKEN_2022_PROGRAM_ID);
+
+    const leaf = (index: number, claimant: PublicKey, amount: number) =>
+      Buffer.from(keccak_256(Buffer.concat([
+        new anchor.BN(index).toArrayLike(Buffer, "le", 8),
+        claimant.toBuffer(),
+        new anchor.BN(amount).toArrayLike(Buffer, "le", 8),
+      ])));
+    const claims: [PublicKey, number][] = [
+      [recipient.publicKey, 2 * 10 ** decimals],
+      [recipient2.publicKey, 1 * 10 ** decimals],
+    ];
+    const [leaf0, leaf1] = claims.map(([claimant, amount], index) => leaf(index, claimant, amount));
+    const merkleRoot = Buffer.from(keccak_256(Buffer.concat(Buffer.compare(leaf0, leaf1) <= 0 ? [leaf0, leaf1] : [leaf1, leaf0])));
+
+    await program.methods
+      .initializeDistributor(id, [...merkleRoot])
+      .accounts({ mint: mint.publicKey, authority: wallet.publicKey, tokenProgram: TOKEN_2022_PROGRAM_ID })
+      .rpc();
+    await program.methods
+      .initializeClaimBitmap(new anchor.BN(0))
+      .accounts({ distributor })
+      .rpc();
+    await transferHookProgram.methods.initializeTransferStats()
+      .accounts({ mint: mint.publicKey, subject: vault })
+      .signers([wallet.payer])
+      .rpc();
+    const distributorShard = globalStatsShardPda(distributor);
+    if ((await provider.connection.getAccountInfo(distributorShard)) === null) {
+      await transferHookProgram.methods.initializeGlobalStatsShard(distributor.toBuffer()[0])
+        .signers([wallet.payer])
+        .rpc();
+    }
+
+    // the vault is funded with a regular transfer
+    const totalAmount = claims.reduce((sum, [, amount]) => sum + amount, 0);
+    await sendAndConfirmTransaction(
+      provider.connection,
+      new Transaction().add(
+        await createTransferCheckedWithTransferHookInstruction(
+          provider.connection,
+          senderTokenAccountPubkey,
+          mint.publicKey,
+          vault,
+          sender.publicKey,
+          BigInt(totalAmount),
+          decimals,
+          [],
+          "confirmed",
+          TOKEN_2022_PROGRAM_ID
+        )
+      ),
+      [wallet.payer, sender],
+    );
+
+    const [, claimAmount] = claims[0];
+    const recipientBalanceBefore = Number(
+      (await getAccount(provider.connection, recipientTokenAccountPubkey, undefined, TOKEN_2022_PROGRAM_ID)).amount
+    );
+    const claimInstruction = await program.methods
+      .claim(new anchor.BN(0), new anchor.BN(claimAmount), [[...leaf1]])
+      .accounts({
+        distributor,
+        claimantTokenAccount: recipientTokenAccountPubkey,
+        mint: mint.publicKey,
+        claimant: recipient.publicKey,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .instruction();
+    await addExtraAccountMetasForExecute(
+      provider.connection,
+      claimInstruction,
+      transferHookProgram.programId,
+      vault,
+      mint.publicKey,
+      recipientTokenAccountPubkey,
+      distributor,
+      claimAmount,
+      undefined
+    );
+    // a different compute unit limit keeps the retried transaction from being a duplicate
+    const sendClaim = (units: number) => sendAndConfirmTransaction(
+      provider.connection,
+      new Transaction().add(
+        ComputeBudgetProgram.setComputeUnitLimit({ units }),
+        claimInstruction,
+      ),
+      [wallet.payer, recipient],
+    );
+    const txSig = await sendClaim(1000000);
+    console.log(`Claim Transaction Signature: ${txSig}`);
+
+    const recipientAccount = await getAccount(provider.connection, recipientTokenAccountPubkey, undefined, TOKEN_2022_PROGRAM_ID);
+    assert.equal(Number(recipientAccount.amount), recipientBalanceBefore + claimAmount);
+    const distributorAccount = await program.account.distributor.fetch(distributor);
+    assert.equal(distributorAccount.claimedCount.toNumber(), 1);
+
+    try {
+      await sendClaim(1000001);
+      assert.fail("claimed twice");
+    } catch (error) {
+      assert.include(String(error), "AlreadyClaimed");
+    }
+  });
+
+  it("accrues royalties on transfers and settles them to the creator vault", async () => {
+    const royaltyMint = new Keypair();
+    const royaltyBps = 250;
+    const mintLen = getMintLen([ExtensionType.TransferHook]);
+    await sendAndConfirmTransaction(
+      provider.connection,
+      new Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: wallet.publicKey,
+          newAccountPubkey: royaltyMint.publicKey,
+          space: mintLen,
+          lamports: await provider.connection.getMinimumBalanceForRentExemption(mintLen),
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializeTransferHookInstruction(
+          royaltyMint.publicKey,
+          wallet.publicKey,
+          transferHookProgram.programId,
+          TOKEN_2022_PROGRAM_ID,
+        ),
+        createInitializeMintInstruction(royaltyMint.publicKey, decimals, wallet.publicKey, null, TOKEN_2022_PROGRAM_ID),
+      ),
+      [wallet.payer, royaltyMint],
+    );
+
+    // recipient2 holds the creator vault
+    const [senderRoyaltyAccount, recipientRoyaltyAccount, creatorVault] = await Promise.all(
+      [sender, recipient, recipient2].map((owner) =>
+        createAssociatedTokenAccount(
+          provider.connection,
+          wallet.payer,
+          royaltyMint.publicKey,
+          owner.publicKey,
+          undefined,
+          TOKEN_2022_PROGRAM_ID,
+        )
+      )
+    );
+    for (const subject of [royaltyMint.publicKey, senderRoyaltyAccount, recipientRoyaltyAccount, creatorVault]) {
+      await transferHookProgram.methods.initializeTransferStats()
+        .accounts({ mint: royaltyMint.publicKey, subject })
+        .signers([wallet.payer])
+        .rpc();
+    }
+    await program.methods
+      .initializeRoyaltyConfig(royaltyBps)
+      .accounts({ mint: royaltyMint.publicKey, creatorVault, authority: wallet.publicKey })
+      .rpc();
+    await transferHookProgram.methods
+      .initializeRoyaltyDebt()
+      .accounts({ mint: royaltyMint.publicKey, wallet: sender.publicKey })
+      .rpc();
+    await transferHookProgram.methods
+      .initializeExtraAccountMetaList({ counters: { tokenAccount: {} }, royalties: true, ownerPolicy: false, allowlist: false, lockups: false })
+      .accounts({ payer: wallet.publicKey, mint: royaltyMint.publicKey })
+      .rpc();
+    await mintTo(
+      provider.connection,
+      wallet.payer,
+      royaltyMint.publicKey,
+      senderRoyaltyAccount,
+      wallet.payer,
+      100 * 10 ** decimals,
+      [],
+      undefined,
+      TOKEN_2022_PROGRAM_ID,
+    );
+
+    const amount = 10 * 10 ** decimals;
+    await sendAndConfirmTransaction(
+      provider.connection,
+      new Transaction().add(
+        await createTransferCheckedWithTransferHookInstruction(
+          provider.connection,
+          senderRoyaltyAccount,
+          royaltyMint.publicKey,
+          recipientRoyaltyAccount,
+          sender.publicKey,
+          BigInt(amount),
+          decimals,
+          [],
+          "confirmed",
+          TOKEN_2022_PROGRAM_ID
+        )
+      ),
+      [wallet.payer, sender],
+    );
+    const [royaltyDebt] = PublicKey.findProgramAddressSync(
+      [Buffer.from("royalty-debt"), sender.publicKey.toBuffer(), royaltyMint.publicKey.toBuffer()],
+      transferHookProgram.programId
+    );
+    const royalty = (amount * royaltyBps) / 10_000;
+    assert.equal((await transferHookProgram.account.royaltyDebt.fetch(royaltyDebt)).owed.toNumber(), royalty);
+
+    const settleRoyaltiesInstruction = await program.methods
+      .settleRoyalties()
+      .accounts({
+        payerTokenAccount: senderRoyaltyAccount,
+        creatorVault,
+        mint: royaltyMint.publicKey,
+        wallet: sender.publicKey,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .instruction();
+    await addExtraAccountMetasForExecute(
+      provider.connection,
+      settleRoyaltiesInstruction,
+      transferHookProgram.programId,
+      senderRoyaltyAccount,
+      royaltyMint.publicKey,
+      creatorVault,
+      sender.publicKey,
+      royalty,
+      undefined
+    );
+    const txSig = await sendAndConfirmTransaction(
+      provider.connection,
+      new Transaction().add(
+        ComputeBudgetProgram.setComputeUnitLimit({ units: 1000000 }),
+        settleRoyaltiesInstruction,
+      ),
+      [wallet.payer, sender],
+    );
+    console.log(`Settle Royalties Transaction Signature: ${txSig}`);
+
+    // the hook settles the debt on the payment, which accrues no royalty
+    const debt = await transferHookProgram.account.royaltyDebt.fetch(royaltyDebt);
+    assert.equal(debt.owed.toNumber(), 0);
+    assert.equal(debt.settled.toNumber(), royalty);
+    const creatorVaultAccount = await getAccount(provider.connection, creatorVault, undefined, TOKEN_2022_PROGRAM_ID);
+    assert.equal(Number(creatorVaultAccount.amount), royalty);
+  });
 });
@@ -0,0 +1,428 @@
+//! Mint, token accounts and counters a variant transfers between, set up once per variant
+
+use std::collections::{BTreeSet, HashMap};
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_spl::associated_token::{
+    get_associated_token_address_with_program_id,
+    spl_associated_token_account::instruction::create_associated_token_account,
+};
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{transfer_hook as transfer_hook_extension, ExtensionType},
+    state::Mint,
+};
+use anyhow::{anyhow, Context, Result};
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::packet::PACKET_DATA_SIZE;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::Transaction;
+use transfer_extensions_client::instructions;
+use transfer_extensions_client::pda::*;
+use transfer_extensions_client::resolve::{
+    add_extra_account_metas_for_transfer, batch_transfers_with_extra_accounts,
+    multi_transfers_with_extra_accounts, BatchTransfer,
+};
+use transfer_extensions_client::transfer_extensions::{self, sol_sdk::FeeMode};
+use transfer_extensions_client::transfer_hook::{
+    self, instructions::CounterLayout, state::HookModules,
+};
+
+use crate::Measurement;
+
+const DECIMALS: u8 = 9;
+
+/// Transferred by every leg, the source holds enough for all of them
+const AMOUNT: u64 = 1_000;
+
+const COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// Setup instructions sent per transaction, to stay below the packet size
+const SETUP_INSTRUCTIONS_PER_TRANSACTION: usize = 4;
+
+/// Token program of the mint and extra account meta list of its hook
+#[derive(Clone, Copy)]
+pub struct Variant {
+    pub name: &'static str,
+    /// Modules of the hook, `None` for a legacy Token mint, which has no transfer hook
+    pub meta_list: Option<HookModules>,
+}
+
+impl Variant {
+    pub const ALL: [Variant; 6] = [
+        Variant {
+            name: "legacy-token",
+            meta_list: None,
+        },
+        Variant {
+            name: "pause-only",
+            meta_list: Some(HookModules {
+                counters: None,
+                royalties: false,
+                owner_policy: false,
+                allowlist: false,
+                lockups: false,
+            }),
+        },
+        Variant {
+            name: "token-account",
+            meta_list: Some(HookModules {
+                counters: Some(CounterLayout::TokenAccount),
+                royalties: false,
+                owner_policy: false,
+                allowlist: false,
+                lockups: false,
+            }),
+        },
+        Variant {
+            name: "owner",
+            meta_list: Some(HookModules {
+                counters: Some(CounterLayout::Owner),
+                royalties: false,
+                owner_policy: false,
+                allowlist: false,
+                lockups: false,
+            }),
+        },
+        Variant {
+            name: "token-account-royalties",
+            meta_list: Some(HookModules {
+                counters: Some(CounterLayout::TokenAccount),
+                royalties: true,
+                owner_policy: false,
+                allowlist: false,
+                lockups: false,
+            }),
+        },
+        Variant {
+            name: "owner-royalties",
+            meta_list: Some(HookModules {
+                counters: Some(CounterLayout::Owner),
+                royalties: true,
+                owner_policy: false,
+                allowlist: false,
+                lockups: false,
+            }),
+        },
+    ];
+
+    fn token_program(&self) -> Pubkey {
+        match self.meta_list {
+            Some(_) => spl_token_2022::ID,
+            None => anchor_spl::token::ID,
+        }
+    }
+}
+
+/// The payer owns the source token account and signs every transfer
+pub struct Fixture {
+    context: ProgramTestContext,
+    variant: Variant,
+    mint: Pubkey,
+    source: Pubkey,
+    destinations: Vec<Pubkey>,
+    extra_metas: usize,
+}
+
+impl Fixture {
+    pub async fn new(variant: Variant, destinations: usize) -> Result<Self> {
+        let mut program_test = ProgramTest::default();
+        program_test.prefer_bpf(true);
+        program_test.add_program("transfer_extensions", transfer_extensions::ID, None);
+        program_test.add_program("transfer_hook", transfer_hook::ID, None);
+
+        let mut fixture = Self {
+            context: program_test.start_with_context().await,
+            variant,
+            mint: Pubkey::default(),
+            source: Pubkey::default(),
+            destinations: vec![],
+            extra_metas: 0,
+        };
+        let payer = fixture.context.payer.pubkey();
+        let token_program = variant.token_program();
+        fixture.mint = fixture.create_mint().await?;
+        let mint = fixture.mint;
+
+        // at least two destinations for `multi_transfers`
+        let owners: Vec<_> = std::iter::once(payer)
+            .chain((0..destinations.max(2)).map(|_| Pubkey::new_unique()))
+            .collect();
+        let token_accounts: Vec<_> = owners
+            .iter()
+            .map(|owner| get_associated_token_address_with_program_id(owner, &mint, &token_program))
+            .collect();
+        fixture.source = token_accounts[0];
+        fixture.destinations = token_accounts[1..].to_vec();
+
+        let mut setup: Vec<_> = owners
+            .iter()
+            .map(|owner| create_associated_token_account(&payer, owner, &mint, &token_program))
+            .collect();
+        setup.extend(
+            std::iter::once(mint)
+                .chain(token_accounts.iter().copied())
+                .chain(owners.iter().copied())
+                .map(|subject| instructions::initialize_transfer_stats(&payer, &subject, &mint)),
+        );
+        let shards: BTreeSet<_> = owners.iter().map(|owner| owner.to_bytes()[0]).collect();
+        setup.extend(
+            shards
+                .into_iter()
+                .map(|shard| instructions::initialize_global_stats_shard(&payer, shard)),
+        );
+        if let Some(modules) = variant.meta_list {
+            if modules.royalties {
+                let creator = Pubkey::new_unique();
+                setup.push(create_associated_token_account(
+                    &payer,
+                    &creator,
+                    &mint,
+                    &token_program,
+                ));
+                let creator_vault =
+                    get_associated_token_address_with_program_id(&creator, &mint, &token_program);
+                setup.push(instructions::initialize_royalty_config(
+                    &payer,
+                    &mint,
+                    &creator_vault,
+                    100,
+                ));
+                setup.push(instructions::initialize_royalty_debt(&payer, &payer, &mint));
+            }
+            setup.push(instructions::initialize_extra_account_meta_list(
+                &payer, &mint, modules,
+            ));
+        }
+        setup.push(spl_token_2022::instruction::mint_to(
+            &token_program,
+            &mint,
+            &fixture.source,
+            &payer,
+            &[],
+            AMOUNT * destinations.max(2) as u64,
+        )?);
+        for chunk in setup.chunks(SETUP_INSTRUCTIONS_PER_TRANSACTION) {
+            fixture.process(chunk, &[]).await?;
+        }
+
+        let mut leg = spl_token_2022::instruction::transfer_checked(
+            &token_program,
+            &fixture.source,
+            &mint,
+            &fixture.destinations[0],
+            &payer,
+            &[],
+            AMOUNT,
+            DECIMALS,
+        )?;
+        let accounts = fixture.snapshot().await?;
+        add_extra_account_metas_for_transfer(
+            &accounts,
+            &mut leg,
+            &fixture.source,
+            &mint,
+            &fixture.destinations[0],
+            &payer,
+            AMOUNT,
+        )
+        .map_err(|error| anyhow!(error))?;
+        // hook program and meta list follow the resolved metas
+        fixture.extra_metas = leg.accounts.len().saturating_sub(4 + 2);
+        Ok(fixture)
+    }
+
+    pub async fn multi_transfers(&mut self) -> Result<Measurement> {
+        let accounts = self.snapshot().await?;
+        let instruction = multi_transfers_with_extra_accounts(
+            &accounts,
+            &self.context.payer.pubkey(),
+            &self.source,
+            &self.destinations[0],
+            &self.destinations[1],
+            &self.mint,
+            &self.variant.token_program(),
+            AMOUNT,
+            AMOUNT,
+            FeeMode::Gross,
+            FeeMode::Gross,
+        )
+        .map_err(|error| anyhow!(error))?;
+        self.measure("multi_transfers", 2, instruction).await
+    }
+
+    /// Only the first leg transfers, the second one allocates on the heap
+    pub async fn multi_transfers_heap(&mut self) -> Result<Measurement> {
+        let payer = self.context.payer.pubkey();
+        let mut instruction = instructions::multi_transfers_heap(
+            &payer,
+            &self.source,
+            &self.destinations[0],
+            &self.destinations[1],
+            &self.mint,
+            &self.variant.token_program(),
+            AMOUNT,
+            AMOUNT,
+        );
+        let accounts = self.snapshot().await?;
+        add_extra_account_metas_for_transfer(
+            &accounts,
+            &mut instruction,
+            &self.source,
+            &self.mint,
+            &self.destinations[0],
+            &payer,
+            AMOUNT,
+        )
+        .map_err(|error| anyhow!(error))?;
+        self.measure("multi_transfers_heap", 1, instruction).await
+    }
+
+    pub async fn batch_transfers(&mut self, legs: usize) -> Result<Measurement> {
+        let transfers: Vec<_> = self.destinations[..legs]
+            .iter()
+            .map(|destination| BatchTransfer {
+                source_account: self.source,
+                mint: self.mint,
+                destination_account: *destination,
+                token_program: self.variant.token_program(),
+                amount: AMOUNT,
+                fee_mode: FeeMode::Gross,
+            })
+            .collect();
+        let accounts = self.snapshot().await?;
+        let instruction = batch_transfers_with_extra_accounts(
+            &accounts,
+            &self.context.payer.pubkey(),
+            &transfers,
+        )
+        .map_err(|error| anyhow!(error))?;
+        self.measure("batch_transfers", legs, instruction).await
+    }
+
+    /// Simulates `instruction`, failures are part of the measurement
+    async fn measure(
+        &mut self,
+        name: &'static str,
+        legs: usize,
+        instruction: Instruction,
+    ) -> Result<Measurement> {
+        let transaction = self.transaction(&[instruction], &[]);
+        let transaction_size = bincode::serialize(&transaction)?.len();
+        let accounts = transaction.message.account_keys.len();
+
+        let simulation = self
+            .context
+            .banks_client
+            .simulate_transaction(transaction)
+            .await
+            .with_context(|| format!("simulating {} with {} legs", name, legs))?;
+        let details = simulation
+            .simulation_details
+            .with_context(|| format!("no simulation details of {}", name))?;
+        // the last log of the outer instruction, inner instructions don't log their heap
+        let heap_used = details
+            .logs
+            .iter()
+            .rev()
+            .find_map(|log| log.strip_prefix("Program log: Heap used: "))
+            .and_then(|used| used.parse().ok());
+
+        Ok(Measurement {
+            instruction: name,
+            variant: self.variant.name,
+            extra_metas: self.extra_metas,
+            legs,
+            accounts,
+            transaction_size,
+            fits_packet: transaction_size <= PACKET_DATA_SIZE,
+            compute_units: details.units_consumed,
+            heap_used,
+            error: simulation
+                .result
+                .and_then(|result| result.err())
+                .map(|error| error.to_string()),
+        })
+    }
+
+    async fn create_mint(&mut self) -> Result<Pubkey> {
+        let mint = Keypair::new();
+        let payer = self.context.payer.pubkey();
+        let token_program = self.variant.token_program();
+        let extensions: &[ExtensionType] = match self.variant.meta_list {
+            Some(_) => &[ExtensionType::TransferHook],
+            None => &[],
+        };
+        let space = ExtensionType::try_calculate_account_len::<Mint>(extensions)?;
+        let rent = self.context.banks_client.get_rent().await?;
+
+        let mut ixs = vec![system_instruction::create_account(
+            &payer,
+            &mint.pubkey(),
+            rent.minimum_balance(space),
+            space as u64,
+            &token_program,
+        )];
+        if self.variant.meta_list.is_some() {
+            ixs.push(transfer_hook_extension::instruction::initialize(
+                &token_program,
+                &mint.pubkey(),
+                Some(payer),
+                Some(transfer_hook::ID),
+            )?);
+        }
+        ixs.push(spl_token_2022::instruction::initialize_mint2(
+            &token_program,
+            &mint.pubkey(),
+            &payer,
+            None,
+            DECIMALS,
+        )?);
+        self.process(&ixs, &[&mint]).await?;
+        Ok(mint.pubkey())
+    }
+
+    /// Data of the accounts the extra accounts of a transfer are resolved from
+    async fn snapshot(&mut self) -> Result<HashMap<Pubkey, Vec<u8>>> {
+        let mut accounts = HashMap::new();
+        for address in [
+            self.mint,
+            get_extra_account_metas_address(&self.mint),
+            self.source,
+        ] {
+            if let Some(account) = self.context.banks_client.get_account(address).await? {
+                accounts.insert(address, account.data);
+            }
+        }
+        Ok(accounts)
+    }
+
+    async fn process(&mut self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<()> {
+        let transaction = self.transaction(instructions, signers);
+        self.context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .context("setting up the fixture")
+    }
+
+    fn transaction(&self, instructions: &[Instruction], signers: &[&Keypair]) -> Transaction {
+        let instructions: Vec<_> = std::iter::once(
+            ComputeBudgetInstruction::set_compute_unit_limit(COMPUTE_UNIT_LIMIT),
+        )
+        .chain(instructions.iter().cloned())
+        .collect();
+        let signers: Vec<&Keypair> = std::iter::once(&self.context.payer)
+            .chain(signers.iter().copied())
+            .collect();
+        Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&self.context.payer.pubkey()),
+            &signers,
+            self.context.last_blockhash,
+        )
+    }
+}
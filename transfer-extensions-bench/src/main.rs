@@ -0,0 +1,108 @@
+//! Measures the compute units and heap used by `multi_transfers`, `multi_transfers_heap` and
+//! `batch_transfers` with 1 to `--max-legs` legs, for every extra account meta list the hook
+//! builds, and writes one JSON or CSV row per transaction.
+//!
+//! Transactions are simulated on an in-process cluster running the programs of
+//! `--program-dir`. Heap usage is only reported by programs built with the `heap-usage`
+//! feature: `anchor build -- --features heap-usage`.
+
+mod fixture;
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::{Parser, ValueEnum};
+use serde::Serialize;
+
+use crate::fixture::{Fixture, Variant};
+
+/// Compute unit and heap benchmark of the transfer-extensions transfer paths
+#[derive(Parser)]
+#[command(name = "transfer-extensions-bench", version)]
+struct Args {
+    /// Largest number of legs of a batch
+    #[arg(long, default_value_t = 8, value_parser = clap::value_parser!(u8).range(1..))]
+    max_legs: u8,
+
+    #[arg(long, value_enum, default_value_t = Format::Json)]
+    format: Format,
+
+    /// Report file [default: stdout]
+    #[arg(long)]
+    output: Option<PathBuf>,
+
+    /// Directory of the program binaries
+    #[arg(long, default_value = concat!(env!("CARGO_MANIFEST_DIR"), "/../target/deploy"))]
+    program_dir: PathBuf,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Json,
+    Csv,
+}
+
+/// One simulated transaction
+#[derive(Serialize)]
+pub struct Measurement {
+    pub instruction: &'static str,
+    pub variant: &'static str,
+    /// Extra accounts the hook resolves for one transfer, hook program and meta list excluded
+    pub extra_metas: usize,
+    pub legs: usize,
+    /// Distinct accounts of the transaction
+    pub accounts: usize,
+    /// Serialized size in bytes, transactions above `PACKET_DATA_SIZE` can't be sent
+    pub transaction_size: usize,
+    pub fits_packet: bool,
+    /// Compute units of the whole transaction, compute budget instruction included
+    pub compute_units: u64,
+    /// Heap bytes used by the outer instruction, `None` without the `heap-usage` feature
+    pub heap_used: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    if let Err(error) = run(Args::parse()).await {
+        eprintln!("Error: {:#}", error);
+        std::process::exit(1);
+    }
+}
+
+async fn run(args: Args) -> Result<()> {
+    std::env::set_var("SBF_OUT_DIR", &args.program_dir);
+
+    let mut measurements = vec![];
+    for variant in Variant::ALL {
+        let mut fixture = Fixture::new(variant, args.max_legs.into()).await?;
+        measurements.push(fixture.multi_transfers().await?);
+        measurements.push(fixture.multi_transfers_heap().await?);
+        for legs in 1..=args.max_legs.into() {
+            measurements.push(fixture.batch_transfers(legs).await?);
+        }
+    }
+
+    let mut writer: Box<dyn Write> = match &args.output {
+        Some(path) => {
+            Box::new(File::create(path).with_context(|| format!("creating {}", path.display()))?)
+        }
+        None => Box::new(io::stdout()),
+    };
+    match args.format {
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut writer, &measurements)?;
+            writeln!(writer)?;
+        }
+        Format::Csv => {
+            let mut writer = csv::Writer::from_writer(writer);
+            for measurement in &measurements {
+                writer.serialize(measurement)?;
+            }
+            writer.flush()?;
+        }
+    }
+    Ok(())
+}
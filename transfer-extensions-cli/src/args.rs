@@ -0,0 +1,179 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use transfer_extensions_client::transfer_extensions::sol_sdk::FeeMode;
+use transfer_extensions_client::transfer_hook::{self, instructions::CounterLayout};
+
+/// Administers transfer-extensions mints and counters
+#[derive(Parser)]
+#[command(name = "transfer-extensions-cli", version)]
+pub struct Cli {
+    /// JSON-RPC endpoint
+    #[arg(long, global = true, default_value = "http://127.0.0.1:8899")]
+    pub url: String,
+
+    /// Fee payer and authority [default: ~/.config/solana/id.json]
+    #[arg(long, global = true)]
+    pub keypair: Option<PathBuf>,
+
+    /// Compute unit limit of every transaction
+    #[arg(long, global = true)]
+    pub compute_unit_limit: Option<u32>,
+
+    /// Sign the transactions and print the signatures without sending
+    #[arg(long, global = true, requires = "blockhash")]
+    pub sign_only: bool,
+
+    /// Print every transaction, base64 encoded
+    #[arg(long, global = true)]
+    pub dump_transaction: bool,
+
+    /// Recent blockhash, required with --sign-only
+    #[arg(long, global = true)]
+    pub blockhash: Option<Hash>,
+
+    /// Account data to read instead of the cluster, a JSON object of address to base64 data
+    #[arg(long, global = true)]
+    pub accounts: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Create a Token-2022 mint with the transfer hook extension
+    CreateMint {
+        #[arg(long)]
+        decimals: u8,
+        /// Keypair of the mint account [default: new keypair]
+        #[arg(long)]
+        mint_keypair: Option<PathBuf>,
+        #[arg(long, default_value_t = transfer_hook::ID)]
+        hook_program_id: Pubkey,
+        /// Lamports of the mint account, required with --sign-only or --accounts
+        /// [default: rent exemption read from the cluster]
+        #[arg(long)]
+        lamports: Option<u64>,
+    },
+    /// Initialize the extra account meta list of a mint
+    InitMetaList {
+        #[arg(long)]
+        mint: Pubkey,
+        /// Record transfer stats in the hook, with wallet stats of these subjects
+        #[arg(long, value_enum)]
+        counters: Option<Layout>,
+        /// Accrue royalties in the hook
+        #[arg(long)]
+        royalties: bool,
+        /// Enforce the owner policies of the source owners in the hook
+        #[arg(long)]
+        owner_policy: bool,
+        /// Only deliver to wallets on the allowlist of the mint
+        #[arg(long)]
+        allowlist: bool,
+        /// Enforce the lockups of the source owners in the hook
+        #[arg(long)]
+        lockups: bool,
+        /// Rebuild the existing meta list with these modules, creating the hook config
+        /// of meta lists initialized before it
+        #[arg(long)]
+        update: bool,
+    },
+    /// Pause the transfers of a hooked mint, signed by the keypair as authority of its hook config
+    Pause {
+        #[arg(long)]
+        mint: Pubkey,
+    },
+    /// Resume the transfers of a paused mint
+    Resume {
+        #[arg(long)]
+        mint: Pubkey,
+    },
+    /// Initialize counters, existing ones are skipped
+    #[command(subcommand)]
+    InitCounters(Counters),
+    /// Move the legacy in and out counters of subjects into their stats, signed by the keypair
+    /// as mint authority. Subjects without legacy counters or with stats already are skipped
+    MigrateLegacyCounters {
+        #[arg(long)]
+        mint: Pubkey,
+        /// Token accounts, owner wallets or the mint itself
+        #[arg(long = "subject", required = true)]
+        subjects: Vec<Pubkey>,
+    },
+    /// Transfer from a CSV file of `destination token account,amount[,gross|net]` rows,
+    /// amounts in base units
+    BatchTransfer {
+        #[arg(long)]
+        mint: Pubkey,
+        /// Transfers to make
+        #[arg(long)]
+        csv: PathBuf,
+        /// Source token account [default: associated token account of the keypair]
+        #[arg(long)]
+        source: Option<Pubkey>,
+        #[arg(long, default_value_t = anchor_spl::token_2022::ID)]
+        token_program: Pubkey,
+        /// Transfers per transaction
+        #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u8).range(1..))]
+        batch_size: u8,
+    },
+    /// Print decoded transfer-extensions accounts
+    Show {
+        addresses: Vec<Pubkey>,
+        /// Show the stats of this mint
+        #[arg(long)]
+        mint: Option<Pubkey>,
+        /// Show the stats of these wallets or token accounts in the mint
+        #[arg(long = "subject", requires = "mint")]
+        subjects: Vec<Pubkey>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum Counters {
+    /// Global program data and global stats shards
+    Global {
+        /// Shard to initialize [default: all]
+        #[arg(long = "shard")]
+        shards: Vec<u8>,
+    },
+    /// Stats of a mint
+    Mint {
+        #[arg(long)]
+        mint: Pubkey,
+    },
+    /// Stats of wallets or token accounts in a mint
+    Wallet {
+        #[arg(long)]
+        mint: Pubkey,
+        #[arg(long = "subject", required = true)]
+        subjects: Vec<Pubkey>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Layout {
+    TokenAccount,
+    Owner,
+}
+
+impl From<Layout> for CounterLayout {
+    fn from(layout: Layout) -> Self {
+        match layout {
+            Layout::TokenAccount => CounterLayout::TokenAccount,
+            Layout::Owner => CounterLayout::Owner,
+        }
+    }
+}
+
+pub fn parse_fee_mode(value: &str) -> anyhow::Result<FeeMode> {
+    match value {
+        "" | "gross" => Ok(FeeMode::Gross),
+        "net" => Ok(FeeMode::Net),
+        _ => anyhow::bail!("invalid fee mode {}, expected gross or net", value),
+    }
+}
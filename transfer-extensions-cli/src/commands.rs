@@ -0,0 +1,411 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{transfer_hook, ExtensionType},
+    state::Mint,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signer};
+use solana_sdk::system_instruction;
+use transfer_extensions_client::accounts::ProgramAccount;
+use transfer_extensions_client::instructions;
+use transfer_extensions_client::pda::*;
+use transfer_extensions_client::resolve::{
+    batch_transfers_with_extra_accounts, AccountDataResult, AccountFetcher, BatchTransfer,
+};
+use transfer_extensions_client::transfer_extensions::sol_sdk::FeeMode;
+use transfer_extensions_client::transfer_hook::state::{HookModules, GLOBAL_STATS_SHARDS};
+
+use crate::args::{parse_fee_mode, Command, Counters};
+
+/// Initialization instructions packed in one transaction
+const INITIALIZATIONS_PER_TRANSACTION: usize = 8;
+
+/// Migrations packed in one transaction, each has four accounts of its own
+const MIGRATIONS_PER_TRANSACTION: usize = 4;
+
+/// Account data source, the cluster or a snapshot file
+pub enum Accounts<'a> {
+    Rpc(&'a RpcClient),
+    Snapshot(HashMap<Pubkey, Vec<u8>>),
+}
+
+impl Accounts<'_> {
+    /// Reads a JSON object of address to base64 encoded account data
+    pub fn load_snapshot(path: &Path) -> Result<Self> {
+        let file = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let entries: HashMap<String, String> = serde_json::from_str(&file)
+            .with_context(|| format!("invalid account snapshot {}", path.display()))?;
+        let accounts = entries
+            .iter()
+            .map(|(address, data)| Ok((address.parse()?, BASE64.decode(data)?)))
+            .collect::<Result<_>>()?;
+        Ok(Self::Snapshot(accounts))
+    }
+
+    fn exists(&self, address: &Pubkey) -> Result<bool> {
+        self.get_account_data(address)
+            .map(|data| data.is_some())
+            .map_err(|error| anyhow!(error))
+    }
+}
+
+impl AccountFetcher for Accounts<'_> {
+    fn get_account_data(&self, address: &Pubkey) -> AccountDataResult {
+        match self {
+            Self::Rpc(rpc) => Ok(rpc
+                .get_account_with_commitment(address, rpc.commitment())?
+                .value
+                .map(|account| account.data)),
+            Self::Snapshot(accounts) => accounts.get_account_data(address),
+        }
+    }
+}
+
+/// Transactions of a command and the signers they need besides the payer
+#[derive(Default)]
+pub struct Plan {
+    pub transactions: Vec<Vec<Instruction>>,
+    pub signers: Vec<Keypair>,
+}
+
+pub fn plan(command: &Command, payer: &Pubkey, accounts: &Accounts) -> Result<Plan> {
+    match command {
+        Command::CreateMint {
+            decimals,
+            mint_keypair,
+            hook_program_id,
+            lamports,
+        } => {
+            let mint = match mint_keypair {
+                Some(path) => read_keypair_file(path)
+                    .map_err(|error| anyhow!("failed to read {}: {}", path.display(), error))?,
+                None => Keypair::new(),
+            };
+            println!("Mint: {}", mint.pubkey());
+            Ok(Plan {
+                transactions: vec![create_mint(
+                    payer,
+                    &mint.pubkey(),
+                    *decimals,
+                    hook_program_id,
+                    *lamports,
+                    accounts,
+                )?],
+                signers: vec![mint],
+            })
+        }
+        Command::InitMetaList {
+            mint,
+            counters,
+            royalties,
+            owner_policy,
+            allowlist,
+            lockups,
+            update,
+        } => {
+            let modules = HookModules {
+                counters: counters.map(Into::into),
+                royalties: *royalties,
+                owner_policy: *owner_policy,
+                allowlist: *allowlist,
+                lockups: *lockups,
+            };
+            let instruction = if *update {
+                instructions::update_extra_account_meta_list(payer, mint, modules)
+            } else {
+                instructions::initialize_extra_account_meta_list(payer, mint, modules)
+            };
+            Ok(Plan {
+                transactions: vec![vec![instruction]],
+                ..Plan::default()
+            })
+        }
+        Command::Pause { mint } => Ok(Plan {
+            transactions: vec![vec![instructions::set_paused(payer, mint, true)]],
+            ..Plan::default()
+        }),
+        Command::Resume { mint } => Ok(Plan {
+            transactions: vec![vec![instructions::set_paused(payer, mint, false)]],
+            ..Plan::default()
+        }),
+        Command::InitCounters(counters) => init_counters(counters, payer, accounts),
+        Command::MigrateLegacyCounters { mint, subjects } => {
+            migrate_legacy_counters(mint, subjects, payer, accounts)
+        }
+        Command::BatchTransfer {
+            mint,
+            csv,
+            source,
+            token_program,
+            batch_size,
+        } => {
+            let source = source.unwrap_or_else(|| {
+                get_associated_token_address_with_program_id(payer, mint, token_program)
+            });
+            let transfers = read_transfers(csv)?
+                .into_iter()
+                .map(|(destination_account, amount, fee_mode)| BatchTransfer {
+                    source_account: source,
+                    mint: *mint,
+                    destination_account,
+                    token_program: *token_program,
+                    amount,
+                    fee_mode,
+                })
+                .collect::<Vec<_>>();
+            let transactions = transfers
+                .chunks(*batch_size as usize)
+                .map(|batch| {
+                    batch_transfers_with_extra_accounts(accounts, payer, batch)
+                        .map(|instruction| vec![instruction])
+                        .map_err(|error| anyhow!(error))
+                })
+                .collect::<Result<_>>()?;
+            Ok(Plan {
+                transactions,
+                ..Plan::default()
+            })
+        }
+        Command::Show { .. } => Ok(Plan::default()),
+    }
+}
+
+fn create_mint(
+    payer: &Pubkey,
+    mint: &Pubkey,
+    decimals: u8,
+    hook_program_id: &Pubkey,
+    lamports: Option<u64>,
+    accounts: &Accounts,
+) -> Result<Vec<Instruction>> {
+    let space = ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferHook])?;
+    let lamports = match (lamports, accounts) {
+        (Some(lamports), _) => lamports,
+        (None, Accounts::Rpc(rpc)) => rpc.get_minimum_balance_for_rent_exemption(space)?,
+        (None, Accounts::Snapshot(_)) => {
+            bail!("--lamports is required when the rent can't be read from the cluster")
+        }
+    };
+    Ok(vec![
+        system_instruction::create_account(
+            payer,
+            mint,
+            lamports,
+            space as u64,
+            &spl_token_2022::ID,
+        ),
+        transfer_hook::instruction::initialize(
+            &spl_token_2022::ID,
+            mint,
+            Some(*payer),
+            Some(*hook_program_id),
+        )?,
+        spl_token_2022::instruction::initialize_mint2(
+            &spl_token_2022::ID,
+            mint,
+            payer,
+            None,
+            decimals,
+        )?,
+    ])
+}
+
+/// Initializes the counters that do not exist yet
+fn init_counters(counters: &Counters, payer: &Pubkey, accounts: &Accounts) -> Result<Plan> {
+    let mut initializations = vec![];
+    match counters {
+        Counters::Global { shards } => {
+            if !accounts.exists(&get_global_program_data_address())? {
+                initializations.push(instructions::initialize_program_data(payer));
+            }
+            let shards = if shards.is_empty() {
+                (0..GLOBAL_STATS_SHARDS).map(|shard| shard as u8).collect()
+            } else {
+                shards.clone()
+            };
+            for shard in shards {
+                if !accounts.exists(&get_global_stats_shard_address(shard))? {
+                    initializations.push(instructions::initialize_global_stats_shard(payer, shard));
+                }
+            }
+        }
+        Counters::Mint { mint } => {
+            if !accounts.exists(&get_mint_stats_address(mint))? {
+                initializations.push(instructions::initialize_transfer_stats(payer, mint, mint));
+            }
+        }
+        Counters::Wallet { mint, subjects } => {
+            for subject in subjects {
+                if !accounts.exists(&get_transfer_stats_address(subject, mint))? {
+                    initializations.push(instructions::initialize_transfer_stats(
+                        payer, subject, mint,
+                    ));
+                }
+            }
+        }
+    }
+    Ok(Plan {
+        transactions: initializations
+            .chunks(INITIALIZATIONS_PER_TRANSACTION)
+            .map(<[Instruction]>::to_vec)
+            .collect(),
+        ..Plan::default()
+    })
+}
+
+/// Migrates the legacy counters of the subjects without stats. Owner counters are derived
+/// from the owner and the mint, token account and mint counters from the subject alone.
+fn migrate_legacy_counters(
+    mint: &Pubkey,
+    subjects: &[Pubkey],
+    payer: &Pubkey,
+    accounts: &Accounts,
+) -> Result<Plan> {
+    let mut migrations = vec![];
+    for subject in subjects {
+        if accounts.exists(&get_transfer_stats_address(subject, mint))? {
+            continue;
+        }
+        for (counter_in, counter_out) in [
+            get_legacy_owner_counter_addresses(subject, mint),
+            get_legacy_counter_addresses(subject),
+        ] {
+            if accounts.exists(&counter_in)? && accounts.exists(&counter_out)? {
+                migrations.push(instructions::migrate_legacy_counters(
+                    payer,
+                    subject,
+                    mint,
+                    &counter_in,
+                    &counter_out,
+                ));
+                break;
+            }
+        }
+    }
+    Ok(Plan {
+        transactions: migrations
+            .chunks(MIGRATIONS_PER_TRANSACTION)
+            .map(<[Instruction]>::to_vec)
+            .collect(),
+        ..Plan::default()
+    })
+}
+
+/// Rows of `destination,amount[,fee mode]`, `#` lines are comments
+fn read_transfers(path: &Path) -> Result<Vec<(Pubkey, u64, FeeMode)>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .comment(Some(b'#'))
+        .trim(csv::Trim::All)
+        .from_path(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    reader
+        .records()
+        .map(|record| {
+            let record = record?;
+            let row = || -> Result<_> {
+                if !(2..=3).contains(&record.len()) {
+                    bail!("expected destination,amount[,fee mode]");
+                }
+                Ok((
+                    record[0].parse()?,
+                    record[1].parse()?,
+                    parse_fee_mode(record.get(2).unwrap_or_default())?,
+                ))
+            };
+            row().with_context(|| match record.position() {
+                Some(position) => format!("{}:{}", path.display(), position.line()),
+                None => path.display().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Prints the decoded state of `transfer_extensions` accounts
+pub fn show(addresses: &[Pubkey], accounts: &Accounts) -> Result<()> {
+    for address in addresses {
+        let Some(data) = accounts
+            .get_account_data(address)
+            .map_err(|error| anyhow!(error))?
+        else {
+            println!("{}: not found", address);
+            continue;
+        };
+        let account = ProgramAccount::decode(&data)
+            .map_err(|error| anyhow!("failed to decode {}: {}", address, error))?;
+        println!("{}: {}", address, describe(&account));
+    }
+    Ok(())
+}
+
+fn describe(account: &ProgramAccount) -> String {
+    match account {
+        ProgramAccount::GlobalProgramData(data) => format!(
+            "GlobalProgramData\n  transfers count: {}",
+            data.transfers_count
+        ),
+        ProgramAccount::GlobalStatsShard(shard) => format!(
+            "GlobalStatsShard\n  shard: {}\n  transfers count: {}\n  volume: {}",
+            shard.shard, shard.transfers_count, shard.volume
+        ),
+        ProgramAccount::TransferStats(stats) => format!(
+            "TransferStats\n  subject: {}\n  mint: {}\n  transfers in: {}\n  transfers out: {}\n  volume in: {}\n  volume out: {}",
+            stats.subject,
+            stats.mint,
+            stats.transfers_in,
+            stats.transfers_out,
+            stats.volume_in,
+            stats.volume_out
+        ),
+        ProgramAccount::WalletCounterIn(counter) => format!(
+            "WalletCounterIn (legacy)\n  owner: {}\n  transfers count: {}",
+            counter.owner, counter.transfers_count
+        ),
+        ProgramAccount::WalletCounterOut(counter) => format!(
+            "WalletCounterOut (legacy)\n  owner: {}\n  transfers count: {}",
+            counter.owner, counter.transfers_count
+        ),
+        ProgramAccount::MintCounterIn(counter) => format!(
+            "MintCounterIn (legacy)\n  mint: {}\n  transfers count: {}",
+            counter.mint, counter.transfers_count
+        ),
+        ProgramAccount::MintCounterOut(counter) => format!(
+            "MintCounterOut (legacy)\n  mint: {}\n  transfers count: {}",
+            counter.mint, counter.transfers_count
+        ),
+        ProgramAccount::HookConfig(config) => format!(
+            "HookConfig\n  mint: {}\n  authority: {}\n  modules: {:?}\n  paused: {}",
+            config.mint, config.authority, config.modules, config.paused
+        ),
+        ProgramAccount::AllowlistEntry(entry) => format!(
+            "AllowlistEntry\n  wallet: {}\n  mint: {}",
+            entry.wallet, entry.mint
+        ),
+        ProgramAccount::Lockup(lockup) => format!(
+            "Lockup\n  wallet: {}\n  mint: {}\n  locked amount: {}\n  release time: {}",
+            lockup.wallet, lockup.mint, lockup.locked_amount, lockup.release_time
+        ),
+        ProgramAccount::Escrow(_) => "Escrow".to_string(),
+        ProgramAccount::PaymentStream(_) => "PaymentStream".to_string(),
+        ProgramAccount::ScheduledTransfer(_) => "ScheduledTransfer".to_string(),
+        ProgramAccount::Splitter(_) => "Splitter".to_string(),
+        ProgramAccount::Distributor(_) => "Distributor".to_string(),
+        ProgramAccount::ClaimBitmap(_) => "ClaimBitmap".to_string(),
+        ProgramAccount::RoyaltyConfig(_) => "RoyaltyConfig".to_string(),
+        ProgramAccount::RoyaltyDebt(_) => "RoyaltyDebt".to_string(),
+        ProgramAccount::OwnerPolicy(policy) => format!(
+            "OwnerPolicy\n  wallet: {}\n  mint: {}\n  max transfer amount: {}",
+            policy.wallet, policy.mint, policy.max_transfer_amount
+        ),
+    }
+}
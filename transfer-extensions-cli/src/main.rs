@@ -0,0 +1,123 @@
+//! Administers `transfer_extensions` mints and counters: creates hook-enabled mints,
+//! initializes the extra account meta list and the counters, migrates the legacy counters,
+//! runs batch transfers from a CSV file and prints decoded counter state.
+//!
+//! With `--sign-only` no cluster is needed: transactions are signed against `--blockhash`
+//! and account data is read from an `--accounts` snapshot.
+
+mod args;
+mod commands;
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use clap::Parser;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+use transfer_extensions_client::pda::{get_mint_stats_address, get_transfer_stats_address};
+
+use crate::args::{Cli, Command};
+use crate::commands::{plan, show, Accounts, Plan};
+
+fn main() {
+    if let Err(error) = run(Cli::parse()) {
+        eprintln!("Error: {:#}", error);
+        std::process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    let rpc = (!cli.sign_only)
+        .then(|| RpcClient::new_with_commitment(cli.url.clone(), CommitmentConfig::confirmed()));
+    let accounts = match (&cli.accounts, &rpc) {
+        (Some(path), _) => Accounts::load_snapshot(path)?,
+        (None, Some(rpc)) => Accounts::Rpc(rpc),
+        (None, None) => Accounts::Snapshot(Default::default()),
+    };
+
+    if let Command::Show {
+        addresses,
+        mint,
+        subjects,
+    } = &cli.command
+    {
+        let mut addresses = addresses.clone();
+        if let Some(mint) = mint {
+            addresses.push(get_mint_stats_address(mint));
+            addresses.extend(
+                subjects
+                    .iter()
+                    .map(|subject| get_transfer_stats_address(subject, mint)),
+            );
+        }
+        return show(&addresses, &accounts);
+    }
+
+    let keypair = match &cli.keypair {
+        Some(path) => path.clone(),
+        None => PathBuf::from(std::env::var("HOME").context("HOME is not set, pass --keypair")?)
+            .join(".config/solana/id.json"),
+    };
+    let payer = read_keypair_file(&keypair)
+        .map_err(|error| anyhow!("failed to read keypair {}: {}", keypair.display(), error))?;
+    let plan = plan(&cli.command, &payer.pubkey(), &accounts)?;
+    if plan.transactions.is_empty() {
+        println!("Nothing to do");
+        return Ok(());
+    }
+    process(&cli, rpc.as_ref(), &payer, plan)
+}
+
+/// Signs the planned transactions, then sends them or prints them with `--sign-only`
+fn process(cli: &Cli, rpc: Option<&RpcClient>, payer: &Keypair, plan: Plan) -> Result<()> {
+    let blockhash = match (cli.blockhash, rpc) {
+        (Some(blockhash), _) => blockhash,
+        (None, Some(rpc)) => rpc.get_latest_blockhash()?,
+        (None, None) => unreachable!("--sign-only requires --blockhash"),
+    };
+    let signers: Vec<&dyn Signer> = std::iter::once(payer as &dyn Signer)
+        .chain(plan.signers.iter().map(|signer| signer as &dyn Signer))
+        .collect();
+
+    for instructions in plan.transactions {
+        let instructions: Vec<_> = cli
+            .compute_unit_limit
+            .map(ComputeBudgetInstruction::set_compute_unit_limit)
+            .into_iter()
+            .chain(instructions)
+            .collect();
+        let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+        transaction.try_sign(&signers, blockhash)?;
+
+        if cli.dump_transaction {
+            println!(
+                "Transaction: {}",
+                BASE64.encode(bincode::serialize(&transaction)?)
+            );
+        }
+        match rpc {
+            Some(rpc) => {
+                println!(
+                    "Signature: {}",
+                    rpc.send_and_confirm_transaction(&transaction)?
+                );
+            }
+            None => {
+                println!("Blockhash: {}", blockhash);
+                for (signer, signature) in transaction
+                    .message
+                    .account_keys
+                    .iter()
+                    .zip(transaction.signatures.iter())
+                {
+                    println!("Signer: {}={}", signer, signature);
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,433 @@
+//! Runs the CLI with `--sign-only` against `--accounts` snapshots, no cluster involved
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::solana_program::system_instruction::SystemInstruction;
+use anchor_lang::{system_program, AccountSerialize, AnchorDeserialize, Space};
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token_2022::spl_token_2022::{self, state::Mint};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{write_keypair_file, Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+use transfer_extensions_client::pda::*;
+use transfer_extensions_client::transfer_extensions::states::{MintCounterOut, RESERVED_SPACE};
+use transfer_extensions_client::transfer_extensions::{self, sol_sdk::FeeMode};
+use transfer_extensions_client::transfer_hook::{
+    self,
+    state::{HookConfig, HookModules},
+};
+
+/// Files of one test, removed when dropped
+struct TestDir(PathBuf);
+
+impl TestDir {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "transfer-extensions-cli-{}-{}",
+            std::process::id(),
+            name
+        ));
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    fn write(&self, name: &str, contents: &str) -> PathBuf {
+        let path = self.0.join(name);
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    /// Keypair file of a new payer
+    fn keypair(&self) -> (Keypair, PathBuf) {
+        let keypair = Keypair::new();
+        let path = self.0.join("id.json");
+        write_keypair_file(&keypair, &path).unwrap();
+        (keypair, path)
+    }
+
+    /// `--accounts` snapshot of `accounts`
+    fn snapshot(&self, accounts: &[(Pubkey, Vec<u8>)]) -> PathBuf {
+        let entries: HashMap<String, String> = accounts
+            .iter()
+            .map(|(address, data)| (address.to_string(), BASE64.encode(data)))
+            .collect();
+        self.write("accounts.json", &serde_json::to_string(&entries).unwrap())
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Runs the CLI offline with the `keypair` and `accounts` files, dumping the transactions
+fn run_sign_only(keypair: &Path, accounts: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_transfer-extensions-cli"))
+        .arg("--sign-only")
+        .arg("--dump-transaction")
+        .args(["--blockhash", &Hash::new_unique().to_string()])
+        .arg("--keypair")
+        .arg(keypair)
+        .arg("--accounts")
+        .arg(accounts)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// Transactions printed by a successful run
+fn transactions(output: &Output) -> Vec<Transaction> {
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout.clone())
+        .unwrap()
+        .lines()
+        .filter_map(|line| line.strip_prefix("Transaction: "))
+        .map(|transaction| bincode::deserialize(&BASE64.decode(transaction).unwrap()).unwrap())
+        .collect()
+}
+
+/// Token-2022 mint without extensions, its transfers resolve no hook accounts
+fn mint_data() -> Vec<u8> {
+    let mut data = vec![0; Mint::LEN];
+    Mint {
+        decimals: 6,
+        is_initialized: true,
+        ..Mint::default()
+    }
+    .pack_into_slice(&mut data);
+    data
+}
+
+/// Data of `account` allocated with `space` bytes
+fn account_data(account: &impl AccountSerialize, space: usize) -> Vec<u8> {
+    let mut data = vec![];
+    account.try_serialize(&mut data).unwrap();
+    data.resize(space, 0);
+    data
+}
+
+#[test]
+fn batch_transfer_signs_the_csv_rows_offline() {
+    let dir = TestDir::new("batch-transfer");
+    let (payer, keypair) = dir.keypair();
+    let mint = Pubkey::new_unique();
+    let accounts = dir.snapshot(&[(mint, mint_data())]);
+    let destinations = [
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    ];
+    let csv = dir.write(
+        "transfers.csv",
+        &format!(
+            "# destination,amount,fee mode\n{}, 100\n  {},200,net\n{},300 , gross\n",
+            destinations[0], destinations[1], destinations[2]
+        ),
+    );
+
+    let output = run_sign_only(
+        &keypair,
+        &accounts,
+        &[
+            "batch-transfer",
+            "--mint",
+            &mint.to_string(),
+            "--csv",
+            csv.to_str().unwrap(),
+            "--batch-size",
+            "2",
+        ],
+    );
+    let transactions = transactions(&output);
+    assert_eq!(transactions.len(), 2);
+
+    let mut transfers = vec![];
+    for transaction in &transactions {
+        transaction.verify().unwrap();
+        assert_eq!(transaction.message.account_keys[0], payer.pubkey());
+        let [instruction] = transaction.message.instructions.as_slice() else {
+            panic!("expected a single batch_transfers instruction");
+        };
+        let keys = &transaction.message.account_keys;
+        assert_eq!(
+            keys[instruction.program_id_index as usize],
+            transfer_extensions::ID
+        );
+        let legs = transfer_extensions::instruction::BatchTransfers::deserialize(
+            &mut &instruction.data[8..],
+        )
+        .unwrap()
+        .legs;
+        // the signer, then the leg accounts
+        let leg_account = |index: u8| keys[instruction.accounts[1 + index as usize] as usize];
+        for leg in legs {
+            assert_eq!(
+                leg_account(leg.source_index),
+                get_associated_token_address_with_program_id(
+                    &payer.pubkey(),
+                    &mint,
+                    &spl_token_2022::ID
+                )
+            );
+            assert_eq!(leg_account(leg.mint_index), mint);
+            transfers.push((leg_account(leg.destination_index), leg.amount, leg.fee_mode));
+        }
+    }
+    assert_eq!(
+        transfers,
+        vec![
+            (destinations[0], 100, FeeMode::Gross),
+            (destinations[1], 200, FeeMode::Net),
+            (destinations[2], 300, FeeMode::Gross),
+        ]
+    );
+}
+
+#[test]
+fn batch_transfer_reports_the_invalid_csv_row() {
+    let dir = TestDir::new("invalid-csv");
+    let (_, keypair) = dir.keypair();
+    let mint = Pubkey::new_unique();
+    let accounts = dir.snapshot(&[(mint, mint_data())]);
+
+    for (row, error) in [
+        ("100", "expected destination,amount[,fee mode]"),
+        (
+            &format!("{},100,half", Pubkey::new_unique()),
+            "invalid fee mode half",
+        ),
+        (&format!("{},-1", Pubkey::new_unique()), "invalid digit"),
+    ] {
+        let csv = dir.write(
+            "transfers.csv",
+            &format!("# comment\n{},1\n{}\n", Pubkey::new_unique(), row),
+        );
+        let output = run_sign_only(
+            &keypair,
+            &accounts,
+            &[
+                "batch-transfer",
+                "--mint",
+                &mint.to_string(),
+                "--csv",
+                csv.to_str().unwrap(),
+            ],
+        );
+        assert!(!output.status.success());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(
+            stderr.contains(&format!("{}:3", csv.display())) && stderr.contains(error),
+            "{}",
+            stderr
+        );
+    }
+}
+
+#[test]
+fn init_counters_skips_existing_accounts() {
+    let dir = TestDir::new("init-counters");
+    let (_, keypair) = dir.keypair();
+    let mint = Pubkey::new_unique();
+    let [existing, missing] = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let accounts = dir.snapshot(&[
+        (get_mint_stats_address(&mint), vec![0; 8]),
+        (get_transfer_stats_address(&existing, &mint), vec![0; 8]),
+        (get_global_program_data_address(), vec![0; 8]),
+        (get_global_stats_shard_address(1), vec![0; 8]),
+    ]);
+
+    let output = run_sign_only(
+        &keypair,
+        &accounts,
+        &["init-counters", "mint", "--mint", &mint.to_string()],
+    );
+    assert!(transactions(&output).is_empty());
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("Nothing to do"));
+
+    let output = run_sign_only(
+        &keypair,
+        &accounts,
+        &[
+            "init-counters",
+            "wallet",
+            "--mint",
+            &mint.to_string(),
+            "--subject",
+            &existing.to_string(),
+            "--subject",
+            &missing.to_string(),
+        ],
+    );
+    let [transaction] = transactions(&output).try_into().unwrap();
+    transaction.verify().unwrap();
+    let keys = &transaction.message.account_keys;
+    assert_eq!(transaction.message.instructions.len(), 1);
+    assert!(keys.contains(&get_transfer_stats_address(&missing, &mint)));
+    assert!(!keys.contains(&get_transfer_stats_address(&existing, &mint)));
+
+    let output = run_sign_only(
+        &keypair,
+        &accounts,
+        &["init-counters", "global", "--shard", "1", "--shard", "2"],
+    );
+    let [transaction] = transactions(&output).try_into().unwrap();
+    let keys = &transaction.message.account_keys;
+    assert_eq!(transaction.message.instructions.len(), 1);
+    assert_eq!(
+        keys[transaction.message.instructions[0].program_id_index as usize],
+        transfer_hook::ID
+    );
+    assert!(keys.contains(&get_global_stats_shard_address(2)));
+    assert!(!keys.contains(&get_global_stats_shard_address(1)));
+}
+
+#[test]
+fn create_mint_requires_lamports_offline() {
+    let dir = TestDir::new("create-mint");
+    let (_, keypair) = dir.keypair();
+    let accounts = dir.snapshot(&[]);
+
+    let output = run_sign_only(&keypair, &accounts, &["create-mint", "--decimals", "6"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("--lamports is required"));
+
+    let output = run_sign_only(
+        &keypair,
+        &accounts,
+        &["create-mint", "--decimals", "6", "--lamports", "1234"],
+    );
+    let [transaction] = transactions(&output).try_into().unwrap();
+    let create_account = &transaction.message.instructions[0];
+    assert_eq!(
+        transaction.message.account_keys[create_account.program_id_index as usize],
+        system_program::ID
+    );
+    let SystemInstruction::CreateAccount { lamports, .. } =
+        bincode::deserialize(&create_account.data).unwrap()
+    else {
+        panic!("expected the mint account creation first");
+    };
+    assert_eq!(lamports, 1234);
+}
+
+#[test]
+fn migrate_legacy_counters_skips_migrated_subjects() {
+    let dir = TestDir::new("migrate-legacy-counters");
+    let (_, keypair) = dir.keypair();
+    let mint = Pubkey::new_unique();
+    let [owner, token_account, migrated, without_counters] = [
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    ];
+    let owner_counters = get_legacy_owner_counter_addresses(&owner, &mint);
+    let token_account_counters = get_legacy_counter_addresses(&token_account);
+    let migrated_counters = get_legacy_counter_addresses(&migrated);
+    let accounts = dir.snapshot(&[
+        (owner_counters.0, vec![0; 8]),
+        (owner_counters.1, vec![0; 8]),
+        (token_account_counters.0, vec![0; 8]),
+        (token_account_counters.1, vec![0; 8]),
+        (migrated_counters.0, vec![0; 8]),
+        (migrated_counters.1, vec![0; 8]),
+        (get_transfer_stats_address(&migrated, &mint), vec![0; 8]),
+    ]);
+
+    let mut args = vec![
+        "migrate-legacy-counters".to_string(),
+        "--mint".to_string(),
+        mint.to_string(),
+    ];
+    for subject in [owner, token_account, migrated, without_counters] {
+        args.extend(["--subject".to_string(), subject.to_string()]);
+    }
+    let args: Vec<_> = args.iter().map(String::as_str).collect();
+    let output = run_sign_only(&keypair, &accounts, &args);
+    let [transaction] = transactions(&output).try_into().unwrap();
+    transaction.verify().unwrap();
+    let keys = &transaction.message.account_keys;
+    assert_eq!(transaction.message.instructions.len(), 2);
+    for counter in [
+        owner_counters.0,
+        owner_counters.1,
+        token_account_counters.0,
+        token_account_counters.1,
+    ] {
+        assert!(keys.contains(&counter));
+    }
+    assert!(!keys.contains(&migrated_counters.0));
+    assert!(!keys.contains(&without_counters));
+}
+
+#[test]
+fn show_describes_hook_configs_and_legacy_counters() {
+    let dir = TestDir::new("show");
+    let (_, keypair) = dir.keypair();
+    let mint = Pubkey::new_unique();
+    let hook_config = account_data(
+        &HookConfig {
+            mint,
+            authority: Pubkey::new_unique(),
+            modules: HookModules {
+                counters: None,
+                royalties: true,
+                owner_policy: false,
+                allowlist: false,
+                lockups: false,
+            },
+            paused: true,
+            version: 1,
+            reserved: [0; RESERVED_SPACE],
+        },
+        8 + HookConfig::INIT_SPACE,
+    );
+    let counter = account_data(
+        &MintCounterOut {
+            transfers_count: 7,
+            mint,
+            version: 1,
+            reserved: [0; RESERVED_SPACE],
+        },
+        8 + MintCounterOut::INIT_SPACE,
+    );
+    let [hook_config_address, counter_address] = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let accounts = dir.snapshot(&[
+        (hook_config_address, hook_config),
+        (counter_address, counter),
+    ]);
+
+    let output = run_sign_only(
+        &keypair,
+        &accounts,
+        &[
+            "show",
+            &hook_config_address.to_string(),
+            &counter_address.to_string(),
+        ],
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("HookConfig"), "{}", stdout);
+    assert!(stdout.contains("paused: true"), "{}", stdout);
+    assert!(stdout.contains("MintCounterOut (legacy)"), "{}", stdout);
+    assert!(stdout.contains("transfers count: 7"), "{}", stdout);
+}
@@ -0,0 +1,77 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use transfer_extensions::{errors::TransferExtensionsError, states::*};
+use transfer_hook::state::{
+    AllowlistEntry, GlobalStatsShard, HookConfig, Lockup, RoyaltyDebt, TransferStats,
+};
+
+/// Decodes a state account stored with either a legacy or the current layout,
+/// fields missing in a legacy account are read as zeroes (as `load_versioned` does on-chain)
+pub fn decode_account<T: Versioned>(data: &[u8]) -> Result<T> {
+    let space = 8 + T::INIT_SPACE;
+    if data.len() >= space {
+        return T::try_deserialize(&mut &data[..]);
+    }
+    require_gte!(data.len(), T::LEGACY_SPACE, ErrorCode::AccountDidNotDeserialize);
+    let mut padded = data.to_vec();
+    padded.resize(space, 0);
+    T::try_deserialize(&mut &padded[..])
+}
+
+/// Any state account of `transfer_extensions`, including the legacy counters still to migrate,
+/// or a state account of the transfer hook program
+pub enum ProgramAccount {
+    GlobalProgramData(GlobalProgramData),
+    WalletCounterIn(WalletCounterIn),
+    WalletCounterOut(WalletCounterOut),
+    MintCounterIn(MintCounterIn),
+    MintCounterOut(MintCounterOut),
+    GlobalStatsShard(GlobalStatsShard),
+    TransferStats(TransferStats),
+    HookConfig(HookConfig),
+    AllowlistEntry(AllowlistEntry),
+    Lockup(Lockup),
+    Escrow(Escrow),
+    PaymentStream(PaymentStream),
+    ScheduledTransfer(ScheduledTransfer),
+    Splitter(Splitter),
+    Distributor(Distributor),
+    ClaimBitmap(Box<ClaimBitmap>),
+    RoyaltyConfig(RoyaltyConfig),
+    RoyaltyDebt(RoyaltyDebt),
+    OwnerPolicy(OwnerPolicy),
+}
+
+impl ProgramAccount {
+    /// Decodes an account owned by `transfer_extensions` or by the transfer hook program,
+    /// its type is taken from the discriminator
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let discriminator: [u8; 8] = data
+            .get(..8)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(ErrorCode::AccountDiscriminatorNotFound)?;
+
+        Ok(match discriminator {
+            GlobalProgramData::DISCRIMINATOR => Self::GlobalProgramData(decode_account(data)?),
+            WalletCounterIn::DISCRIMINATOR => Self::WalletCounterIn(decode_account(data)?),
+            WalletCounterOut::DISCRIMINATOR => Self::WalletCounterOut(decode_account(data)?),
+            MintCounterIn::DISCRIMINATOR => Self::MintCounterIn(decode_account(data)?),
+            MintCounterOut::DISCRIMINATOR => Self::MintCounterOut(decode_account(data)?),
+            GlobalStatsShard::DISCRIMINATOR => Self::GlobalStatsShard(decode_account(data)?),
+            TransferStats::DISCRIMINATOR => Self::TransferStats(decode_account(data)?),
+            HookConfig::DISCRIMINATOR => Self::HookConfig(decode_account(data)?),
+            AllowlistEntry::DISCRIMINATOR => Self::AllowlistEntry(decode_account(data)?),
+            Lockup::DISCRIMINATOR => Self::Lockup(decode_account(data)?),
+            Escrow::DISCRIMINATOR => Self::Escrow(decode_account(data)?),
+            PaymentStream::DISCRIMINATOR => Self::PaymentStream(decode_account(data)?),
+            ScheduledTransfer::DISCRIMINATOR => Self::ScheduledTransfer(decode_account(data)?),
+            Splitter::DISCRIMINATOR => Self::Splitter(decode_account(data)?),
+            Distributor::DISCRIMINATOR => Self::Distributor(decode_account(data)?),
+            ClaimBitmap::DISCRIMINATOR => Self::ClaimBitmap(Box::new(decode_account(data)?)),
+            RoyaltyConfig::DISCRIMINATOR => Self::RoyaltyConfig(decode_account(data)?),
+            RoyaltyDebt::DISCRIMINATOR => Self::RoyaltyDebt(decode_account(data)?),
+            OwnerPolicy::DISCRIMINATOR => Self::OwnerPolicy(decode_account(data)?),
+            _ => return err!(TransferExtensionsError::UnknownAccountType),
+        })
+    }
+}
@@ -0,0 +1,792 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{system_program, InstructionData};
+use anchor_spl::associated_token;
+use transfer_extensions::instructions::{NetPosition, SettlementLeg, TransferLeg};
+use transfer_extensions::sol_sdk::FeeMode;
+use transfer_extensions::states::{ExecutionTime, ReleaseCondition, SplitShare};
+use transfer_extensions::{accounts, instruction};
+use transfer_hook::state::HookModules;
+
+use crate::pda::*;
+
+fn build(
+    program_id: Pubkey,
+    accounts: impl ToAccountMetas,
+    data: impl InstructionData,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    let mut accounts = accounts.to_account_metas(None);
+    accounts.extend(remaining_accounts);
+    Instruction {
+        program_id,
+        accounts,
+        data: data.data(),
+    }
+}
+
+fn transfer_extensions_instruction(
+    accounts: impl ToAccountMetas,
+    data: impl InstructionData,
+) -> Instruction {
+    build(transfer_extensions::ID, accounts, data, vec![])
+}
+
+fn transfer_hook_instruction(
+    accounts: impl ToAccountMetas,
+    data: impl InstructionData,
+) -> Instruction {
+    build(transfer_hook::ID, accounts, data, vec![])
+}
+
+pub fn initialize_program_data(payer: &Pubkey) -> Instruction {
+    transfer_extensions_instruction(
+        accounts::InitializeProgramData {
+            program_counter: get_global_program_data_address(),
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeProgramData {},
+    )
+}
+
+pub fn migrate_account(payer: &Pubkey, account: &Pubkey) -> Instruction {
+    transfer_extensions_instruction(
+        accounts::MigrateAccount {
+            account: *account,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::MigrateAccount {},
+    )
+}
+
+fn multi_transfers_accounts(
+    signer: &Pubkey,
+    source_account: &Pubkey,
+    destination_account_1: &Pubkey,
+    destination_account_2: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> accounts::MultiTransfers {
+    accounts::MultiTransfers {
+        source_account: *source_account,
+        destination_account_1: *destination_account_1,
+        destination_account_2: *destination_account_2,
+        mint: *mint,
+        signer: *signer,
+        token_program: *token_program,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn multi_transfers(
+    signer: &Pubkey,
+    source_account: &Pubkey,
+    destination_account_1: &Pubkey,
+    destination_account_2: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    amount1: u64,
+    amount2: u64,
+    fee_mode1: FeeMode,
+    fee_mode2: FeeMode,
+) -> Instruction {
+    transfer_extensions_instruction(
+        multi_transfers_accounts(
+            signer,
+            source_account,
+            destination_account_1,
+            destination_account_2,
+            mint,
+            token_program,
+        ),
+        instruction::MultiTransfers {
+            amount1,
+            amount2,
+            fee_mode1,
+            fee_mode2,
+        },
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn multi_transfers_heap(
+    signer: &Pubkey,
+    source_account: &Pubkey,
+    destination_account_1: &Pubkey,
+    destination_account_2: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    amount1: u64,
+    amount2: u64,
+) -> Instruction {
+    transfer_extensions_instruction(
+        multi_transfers_accounts(
+            signer,
+            source_account,
+            destination_account_1,
+            destination_account_2,
+            mint,
+            token_program,
+        ),
+        instruction::MultiTransfersHeap { amount1, amount2 },
+    )
+}
+
+/// `leg_accounts` are the accounts the leg indexes point to
+pub fn batch_transfers(
+    signer: &Pubkey,
+    legs: Vec<TransferLeg>,
+    leg_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    build(
+        transfer_extensions::ID,
+        accounts::BatchTransfers { signer: *signer },
+        instruction::BatchTransfers { legs },
+        leg_accounts,
+    )
+}
+
+/// `leg_accounts` are the accounts the leg and net position indexes point to,
+/// leg authorities included
+pub fn settle(
+    legs: Vec<SettlementLeg>,
+    net_positions: Vec<NetPosition>,
+    leg_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    build(
+        transfer_extensions::ID,
+        accounts::Settle {},
+        instruction::Settle {
+            legs,
+            net_positions,
+        },
+        leg_accounts,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_escrow(
+    maker: &Pubkey,
+    maker_token_account: &Pubkey,
+    mint: &Pubkey,
+    receiver: &Pubkey,
+    token_program: &Pubkey,
+    id: u64,
+    amount: u64,
+    condition: ReleaseCondition,
+) -> Instruction {
+    let escrow = get_escrow_address(maker, id);
+    transfer_extensions_instruction(
+        accounts::CreateEscrow {
+            escrow,
+            vault: get_vault_address(&escrow, mint, token_program),
+            maker_token_account: *maker_token_account,
+            mint: *mint,
+            receiver: *receiver,
+            maker: *maker,
+            token_program: *token_program,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::CreateEscrow {
+            id,
+            amount,
+            condition,
+        },
+    )
+}
+
+pub fn release_escrow(
+    authority: &Pubkey,
+    maker: &Pubkey,
+    id: u64,
+    receiver_token_account: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    let escrow = get_escrow_address(maker, id);
+    transfer_extensions_instruction(
+        accounts::ReleaseEscrow {
+            escrow,
+            vault: get_vault_address(&escrow, mint, token_program),
+            receiver_token_account: *receiver_token_account,
+            mint: *mint,
+            maker: *maker,
+            authority: *authority,
+            token_program: *token_program,
+        },
+        instruction::ReleaseEscrow {},
+    )
+}
+
+pub fn cancel_escrow(
+    authority: &Pubkey,
+    maker: &Pubkey,
+    id: u64,
+    maker_token_account: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    let escrow = get_escrow_address(maker, id);
+    transfer_extensions_instruction(
+        accounts::CancelEscrow {
+            escrow,
+            vault: get_vault_address(&escrow, mint, token_program),
+            maker_token_account: *maker_token_account,
+            mint: *mint,
+            maker: *maker,
+            authority: *authority,
+            token_program: *token_program,
+        },
+        instruction::CancelEscrow {},
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_stream(
+    sender: &Pubkey,
+    sender_token_account: &Pubkey,
+    mint: &Pubkey,
+    recipient: &Pubkey,
+    token_program: &Pubkey,
+    id: u64,
+    rate_per_second: u64,
+    start: i64,
+    end: i64,
+) -> Instruction {
+    let stream = get_payment_stream_address(sender, id);
+    transfer_extensions_instruction(
+        accounts::CreateStream {
+            stream,
+            vault: get_vault_address(&stream, mint, token_program),
+            sender_token_account: *sender_token_account,
+            mint: *mint,
+            recipient: *recipient,
+            sender: *sender,
+            token_program: *token_program,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::CreateStream {
+            id,
+            rate_per_second,
+            start,
+            end,
+        },
+    )
+}
+
+pub fn withdraw_from_stream(
+    recipient: &Pubkey,
+    sender: &Pubkey,
+    id: u64,
+    recipient_token_account: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    let stream = get_payment_stream_address(sender, id);
+    transfer_extensions_instruction(
+        accounts::WithdrawFromStream {
+            stream,
+            vault: get_vault_address(&stream, mint, token_program),
+            recipient_token_account: *recipient_token_account,
+            mint: *mint,
+            recipient: *recipient,
+            token_program: *token_program,
+        },
+        instruction::WithdrawFromStream {},
+    )
+}
+
+pub fn cancel_stream(
+    sender: &Pubkey,
+    id: u64,
+    sender_token_account: &Pubkey,
+    recipient_token_account: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    let stream = get_payment_stream_address(sender, id);
+    transfer_extensions_instruction(
+        accounts::CancelStream {
+            stream,
+            vault: get_vault_address(&stream, mint, token_program),
+            sender_token_account: *sender_token_account,
+            recipient_token_account: *recipient_token_account,
+            mint: *mint,
+            sender: *sender,
+            token_program: *token_program,
+        },
+        instruction::CancelStream {},
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_scheduled_transfer(
+    creator: &Pubkey,
+    creator_token_account: &Pubkey,
+    destination_account: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    id: u64,
+    amount: u64,
+    tip: u64,
+    execution_time: ExecutionTime,
+) -> Instruction {
+    let scheduled_transfer = get_scheduled_transfer_address(creator, id);
+    transfer_extensions_instruction(
+        accounts::CreateScheduledTransfer {
+            scheduled_transfer,
+            vault: get_vault_address(&scheduled_transfer, mint, token_program),
+            creator_token_account: *creator_token_account,
+            destination_account: *destination_account,
+            mint: *mint,
+            creator: *creator,
+            token_program: *token_program,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::CreateScheduledTransfer {
+            id,
+            amount,
+            tip,
+            execution_time,
+        },
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_scheduled_transfer(
+    cranker: &Pubkey,
+    creator: &Pubkey,
+    id: u64,
+    destination_account: &Pubkey,
+    cranker_token_account: &Pubkey,
+    creator_token_account: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    let scheduled_transfer = get_scheduled_transfer_address(creator, id);
+    transfer_extensions_instruction(
+        accounts::ExecuteScheduledTransfer {
+            scheduled_transfer,
+            vault: get_vault_address(&scheduled_transfer, mint, token_program),
+            destination_account: *destination_account,
+            cranker_token_account: *cranker_token_account,
+            creator_token_account: *creator_token_account,
+            mint: *mint,
+            creator: *creator,
+            cranker: *cranker,
+            token_program: *token_program,
+        },
+        instruction::ExecuteScheduledTransfer {},
+    )
+}
+
+pub fn cancel_scheduled_transfer(
+    creator: &Pubkey,
+    id: u64,
+    creator_token_account: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    let scheduled_transfer = get_scheduled_transfer_address(creator, id);
+    transfer_extensions_instruction(
+        accounts::CancelScheduledTransfer {
+            scheduled_transfer,
+            vault: get_vault_address(&scheduled_transfer, mint, token_program),
+            creator_token_account: *creator_token_account,
+            mint: *mint,
+            creator: *creator,
+            token_program: *token_program,
+        },
+        instruction::CancelScheduledTransfer {},
+    )
+}
+
+pub fn initialize_splitter(
+    authority: &Pubkey,
+    mint: &Pubkey,
+    id: u64,
+    shares: Vec<SplitShare>,
+    remainder_index: u8,
+) -> Instruction {
+    transfer_extensions_instruction(
+        accounts::InitializeSplitter {
+            splitter: get_splitter_address(authority, id),
+            mint: *mint,
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeSplitter {
+            id,
+            shares,
+            remainder_index,
+        },
+    )
+}
+
+/// `leg_accounts` start with the source account, followed by the mint,
+/// the recipient token accounts and the token program
+pub fn distribute(
+    signer: &Pubkey,
+    splitter: &Pubkey,
+    amount: u64,
+    leg_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    build(
+        transfer_extensions::ID,
+        accounts::Distribute {
+            splitter: *splitter,
+            signer: *signer,
+        },
+        instruction::Distribute { amount },
+        leg_accounts,
+    )
+}
+
+pub fn initialize_distributor(
+    authority: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    id: u64,
+    merkle_root: [u8; 32],
+) -> Instruction {
+    let distributor = get_distributor_address(authority, id);
+    transfer_extensions_instruction(
+        accounts::InitializeDistributor {
+            distributor,
+            vault: get_vault_address(&distributor, mint, token_program),
+            mint: *mint,
+            authority: *authority,
+            token_program: *token_program,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeDistributor { id, merkle_root },
+    )
+}
+
+pub fn initialize_claim_bitmap(payer: &Pubkey, distributor: &Pubkey, chunk: u64) -> Instruction {
+    transfer_extensions_instruction(
+        accounts::InitializeClaimBitmap {
+            claim_bitmap: get_claim_bitmap_address(distributor, chunk),
+            distributor: *distributor,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeClaimBitmap { chunk },
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn claim(
+    claimant: &Pubkey,
+    distributor: &Pubkey,
+    claimant_token_account: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    index: u64,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+) -> Instruction {
+    transfer_extensions_instruction(
+        accounts::Claim {
+            distributor: *distributor,
+            claim_bitmap: get_leaf_claim_bitmap_address(distributor, index),
+            vault: get_vault_address(distributor, mint, token_program),
+            claimant_token_account: *claimant_token_account,
+            mint: *mint,
+            claimant: *claimant,
+            token_program: *token_program,
+        },
+        instruction::Claim {
+            index,
+            amount,
+            proof,
+        },
+    )
+}
+
+pub fn initialize_royalty_config(
+    authority: &Pubkey,
+    mint: &Pubkey,
+    creator_vault: &Pubkey,
+    bps: u16,
+) -> Instruction {
+    transfer_extensions_instruction(
+        accounts::InitializeRoyaltyConfig {
+            royalty_config: get_royalty_config_address(mint),
+            mint: *mint,
+            creator_vault: *creator_vault,
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeRoyaltyConfig { bps },
+    )
+}
+
+pub fn settle_royalties(
+    wallet: &Pubkey,
+    payer_token_account: &Pubkey,
+    creator_vault: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    transfer_extensions_instruction(
+        accounts::SettleRoyalties {
+            royalty_config: get_royalty_config_address(mint),
+            royalty_debt: get_royalty_debt_address(wallet, mint),
+            payer_token_account: *payer_token_account,
+            creator_vault: *creator_vault,
+            mint: *mint,
+            wallet: *wallet,
+            token_program: *token_program,
+        },
+        instruction::SettleRoyalties {},
+    )
+}
+
+pub fn initialize_owner_policy(
+    authority: &Pubkey,
+    wallet: &Pubkey,
+    mint: &Pubkey,
+    max_transfer_amount: u64,
+) -> Instruction {
+    transfer_extensions_instruction(
+        accounts::InitializeOwnerPolicy {
+            owner_policy: get_owner_policy_address(wallet, mint),
+            mint: *mint,
+            wallet: *wallet,
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeOwnerPolicy {
+            max_transfer_amount,
+        },
+    )
+}
+
+pub fn update_owner_policy(
+    authority: &Pubkey,
+    wallet: &Pubkey,
+    mint: &Pubkey,
+    max_transfer_amount: u64,
+) -> Instruction {
+    transfer_extensions_instruction(
+        accounts::UpdateOwnerPolicy {
+            owner_policy: get_owner_policy_address(wallet, mint),
+            authority: *authority,
+        },
+        instruction::UpdateOwnerPolicy {
+            max_transfer_amount,
+        },
+    )
+}
+
+/// `payer` must be the mint authority
+pub fn initialize_extra_account_meta_list(
+    payer: &Pubkey,
+    mint: &Pubkey,
+    modules: HookModules,
+) -> Instruction {
+    build(
+        transfer_hook::ID,
+        transfer_hook::accounts::InitializeExtraAccountMetaList {
+            extra_metas_account: get_extra_account_metas_address(mint),
+            hook_config: get_hook_config_address(mint),
+            mint: *mint,
+            account_manager_program: transfer_extensions::ID,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        transfer_hook::instruction::InitializeExtraAccountMetaList { modules },
+        vec![],
+    )
+}
+
+/// `payer` must be the mint authority, the hook config is created if the meta list predates it
+pub fn update_extra_account_meta_list(
+    payer: &Pubkey,
+    mint: &Pubkey,
+    modules: HookModules,
+) -> Instruction {
+    build(
+        transfer_hook::ID,
+        transfer_hook::accounts::UpdateExtraAccountMetaList {
+            extra_metas_account: get_extra_account_metas_address(mint),
+            hook_config: get_hook_config_address(mint),
+            mint: *mint,
+            account_manager_program: transfer_extensions::ID,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        transfer_hook::instruction::UpdateExtraAccountMetaList { modules },
+        vec![],
+    )
+}
+
+pub fn set_paused(authority: &Pubkey, mint: &Pubkey, paused: bool) -> Instruction {
+    build(
+        transfer_hook::ID,
+        transfer_hook::accounts::SetPaused {
+            hook_config: get_hook_config_address(mint),
+            authority: *authority,
+        },
+        transfer_hook::instruction::SetPaused { paused },
+        vec![],
+    )
+}
+
+pub fn add_to_allowlist(authority: &Pubkey, wallet: &Pubkey, mint: &Pubkey) -> Instruction {
+    transfer_hook_instruction(
+        transfer_hook::accounts::AddToAllowlist {
+            allowlist_entry: get_allowlist_entry_address(wallet, mint),
+            hook_config: get_hook_config_address(mint),
+            mint: *mint,
+            wallet: *wallet,
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        transfer_hook::instruction::AddToAllowlist {},
+    )
+}
+
+pub fn remove_from_allowlist(authority: &Pubkey, wallet: &Pubkey, mint: &Pubkey) -> Instruction {
+    transfer_hook_instruction(
+        transfer_hook::accounts::RemoveFromAllowlist {
+            allowlist_entry: get_allowlist_entry_address(wallet, mint),
+            hook_config: get_hook_config_address(mint),
+            authority: *authority,
+        },
+        transfer_hook::instruction::RemoveFromAllowlist {},
+    )
+}
+
+pub fn set_lockup(
+    authority: &Pubkey,
+    wallet: &Pubkey,
+    mint: &Pubkey,
+    locked_amount: u64,
+    release_time: i64,
+) -> Instruction {
+    transfer_hook_instruction(
+        transfer_hook::accounts::SetLockup {
+            lockup: get_lockup_address(wallet, mint),
+            hook_config: get_hook_config_address(mint),
+            mint: *mint,
+            wallet: *wallet,
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        transfer_hook::instruction::SetLockup {
+            locked_amount,
+            release_time,
+        },
+    )
+}
+
+pub fn initialize_global_stats_shard(payer: &Pubkey, shard: u8) -> Instruction {
+    transfer_hook_instruction(
+        transfer_hook::accounts::InitializeGlobalStatsShard {
+            global_stats_shard: get_global_stats_shard_address(shard),
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        transfer_hook::instruction::InitializeGlobalStatsShard { shard },
+    )
+}
+
+/// Sums the stats of the given shards, read from the simulated return data
+pub fn aggregate_global_stats(shards: &[u8]) -> Instruction {
+    build(
+        transfer_hook::ID,
+        transfer_hook::accounts::AggregateGlobalStats {
+            global_program_data: get_global_program_data_address(),
+        },
+        transfer_hook::instruction::AggregateGlobalStats {},
+        shards
+            .iter()
+            .map(|shard| AccountMeta::new_readonly(get_global_stats_shard_address(*shard), false))
+            .collect(),
+    )
+}
+
+pub fn initialize_transfer_stats(payer: &Pubkey, subject: &Pubkey, mint: &Pubkey) -> Instruction {
+    transfer_hook_instruction(
+        transfer_hook::accounts::InitializeTransferStats {
+            transfer_stats: get_transfer_stats_address(subject, mint),
+            mint: *mint,
+            subject: *subject,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        transfer_hook::instruction::InitializeTransferStats {},
+    )
+}
+
+/// Only callable by transfer_extensions for legacy Token program transfers,
+/// which signs with its transfer recorder
+pub fn record_transfer(
+    source_account: &Pubkey,
+    destination_account: &Pubkey,
+    source_stats: &Pubkey,
+    destination_stats: &Pubkey,
+    mint: &Pubkey,
+    global_stats_shard: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    transfer_hook_instruction(
+        transfer_hook::accounts::RecordTransfer {
+            source_stats: *source_stats,
+            destination_stats: *destination_stats,
+            mint_stats: get_mint_stats_address(mint),
+            global_stats_shard: *global_stats_shard,
+            source_account: *source_account,
+            destination_account: *destination_account,
+            mint: *mint,
+            transfer_recorder: get_transfer_recorder_address(),
+        },
+        transfer_hook::instruction::RecordTransfer { amount },
+    )
+}
+
+/// `payer` must be the mint authority, `counter_in` and `counter_out` are the legacy counters
+/// of `subject`, see [`get_legacy_counter_addresses`] and [`get_legacy_owner_counter_addresses`]
+pub fn migrate_legacy_counters(
+    payer: &Pubkey,
+    subject: &Pubkey,
+    mint: &Pubkey,
+    counter_in: &Pubkey,
+    counter_out: &Pubkey,
+) -> Instruction {
+    transfer_hook_instruction(
+        transfer_hook::accounts::MigrateLegacyCounters {
+            transfer_stats: get_transfer_stats_address(subject, mint),
+            counter_in: *counter_in,
+            counter_out: *counter_out,
+            subject: *subject,
+            mint: *mint,
+            hook_authority: get_hook_authority_address(),
+            account_manager_program: transfer_extensions::ID,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        transfer_hook::instruction::MigrateLegacyCounters {},
+    )
+}
+
+pub fn initialize_royalty_debt(payer: &Pubkey, wallet: &Pubkey, mint: &Pubkey) -> Instruction {
+    transfer_hook_instruction(
+        transfer_hook::accounts::InitializeRoyaltyDebt {
+            royalty_debt: get_royalty_debt_address(wallet, mint),
+            mint: *mint,
+            wallet: *wallet,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        transfer_hook::instruction::InitializeRoyaltyDebt {},
+    )
+}
@@ -0,0 +1,14 @@
+//! Off-chain client of the `transfer_extensions` and `transfer_hook` programs:
+//! instruction builders, PDA derivation and account decoders.
+//!
+//! Builders return the instruction with its named accounts only. Transfer hook extra accounts
+//! and the accounts of batch legs are appended by the caller, or resolved from fetched
+//! account data with [`resolve`].
+
+pub mod accounts;
+pub mod instructions;
+pub mod pda;
+pub mod resolve;
+
+pub use transfer_extensions;
+pub use transfer_hook;
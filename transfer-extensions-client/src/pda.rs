@@ -0,0 +1,125 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use transfer_extensions::seeds::*;
+use transfer_extensions::states::CLAIMS_PER_BITMAP;
+use transfer_hook::instructions::META_LIST_ACCOUNT_SEED;
+use transfer_hook::state::{ALLOWLIST_ENTRY_SEED, HOOK_CONFIG_SEED, LOCKUP_SEED};
+
+fn find_address(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &transfer_extensions::ID).0
+}
+
+fn find_hook_address(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &transfer_hook::ID).0
+}
+
+pub fn get_global_program_data_address() -> Pubkey {
+    find_address(&[GLOBAL_PROGRAM_DATA_SEED])
+}
+
+pub fn get_global_stats_shard_address(shard: u8) -> Pubkey {
+    find_hook_address(&[GLOBAL_STATS_SHARD_SEED, &[shard]])
+}
+
+/// Shard the hook records the transfers of `owner` in
+pub fn get_owner_global_stats_shard_address(owner: &Pubkey) -> Pubkey {
+    get_global_stats_shard_address(owner.to_bytes()[0])
+}
+
+/// Stats of a token account, a token account owner or, with `subject == mint`, the mint
+pub fn get_transfer_stats_address(subject: &Pubkey, mint: &Pubkey) -> Pubkey {
+    find_hook_address(&[TRANSFER_STATS_SEED, subject.as_ref(), mint.as_ref()])
+}
+
+pub fn get_mint_stats_address(mint: &Pubkey) -> Pubkey {
+    get_transfer_stats_address(mint, mint)
+}
+
+/// PDA signing the stats of legacy Token program transfers
+pub fn get_transfer_recorder_address() -> Pubkey {
+    find_address(&[TRANSFER_RECORDER_SEED])
+}
+
+/// Legacy in and out counters of a token account or, with `subject == mint`, of the mint
+pub fn get_legacy_counter_addresses(subject: &Pubkey) -> (Pubkey, Pubkey) {
+    (
+        find_address(&[COUNTER_IN_SEED, subject.as_ref()]),
+        find_address(&[COUNTER_OUT_SEED, subject.as_ref()]),
+    )
+}
+
+/// Legacy in and out counters of a token account owner
+pub fn get_legacy_owner_counter_addresses(owner: &Pubkey, mint: &Pubkey) -> (Pubkey, Pubkey) {
+    (
+        find_address(&[COUNTER_IN_SEED, owner.as_ref(), mint.as_ref()]),
+        find_address(&[COUNTER_OUT_SEED, owner.as_ref(), mint.as_ref()]),
+    )
+}
+
+pub fn get_escrow_address(maker: &Pubkey, id: u64) -> Pubkey {
+    find_address(&[ESCROW_SEED, maker.as_ref(), &id.to_le_bytes()])
+}
+
+pub fn get_payment_stream_address(sender: &Pubkey, id: u64) -> Pubkey {
+    find_address(&[PAYMENT_STREAM_SEED, sender.as_ref(), &id.to_le_bytes()])
+}
+
+pub fn get_scheduled_transfer_address(creator: &Pubkey, id: u64) -> Pubkey {
+    find_address(&[SCHEDULED_TRANSFER_SEED, creator.as_ref(), &id.to_le_bytes()])
+}
+
+pub fn get_splitter_address(authority: &Pubkey, id: u64) -> Pubkey {
+    find_address(&[SPLITTER_SEED, authority.as_ref(), &id.to_le_bytes()])
+}
+
+pub fn get_distributor_address(authority: &Pubkey, id: u64) -> Pubkey {
+    find_address(&[DISTRIBUTOR_SEED, authority.as_ref(), &id.to_le_bytes()])
+}
+
+pub fn get_claim_bitmap_address(distributor: &Pubkey, chunk: u64) -> Pubkey {
+    find_address(&[CLAIM_BITMAP_SEED, distributor.as_ref(), &chunk.to_le_bytes()])
+}
+
+/// Claim bitmap chunk tracking the leaf at `index`
+pub fn get_leaf_claim_bitmap_address(distributor: &Pubkey, index: u64) -> Pubkey {
+    get_claim_bitmap_address(distributor, index / CLAIMS_PER_BITMAP)
+}
+
+pub fn get_royalty_config_address(mint: &Pubkey) -> Pubkey {
+    find_address(&[ROYALTY_CONFIG_SEED, mint.as_ref()])
+}
+
+pub fn get_royalty_debt_address(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+    find_hook_address(&[ROYALTY_DEBT_SEED, wallet.as_ref(), mint.as_ref()])
+}
+
+pub fn get_owner_policy_address(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+    find_address(&[OWNER_POLICY_SEED, wallet.as_ref(), mint.as_ref()])
+}
+
+/// Vault of an escrow, payment stream, scheduled transfer or distributor:
+/// the associated token account of the PDA
+pub fn get_vault_address(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, mint, token_program)
+}
+
+/// PDA of the transfer hook program signing the closing of the legacy counters it migrated
+pub fn get_hook_authority_address() -> Pubkey {
+    find_hook_address(&[HOOK_AUTHORITY_SEED])
+}
+
+pub fn get_extra_account_metas_address(mint: &Pubkey) -> Pubkey {
+    find_hook_address(&[META_LIST_ACCOUNT_SEED, mint.as_ref()])
+}
+
+pub fn get_hook_config_address(mint: &Pubkey) -> Pubkey {
+    find_hook_address(&[HOOK_CONFIG_SEED, mint.as_ref()])
+}
+
+pub fn get_allowlist_entry_address(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+    find_hook_address(&[ALLOWLIST_ENTRY_SEED, wallet.as_ref(), mint.as_ref()])
+}
+
+pub fn get_lockup_address(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+    find_hook_address(&[LOCKUP_SEED, wallet.as_ref(), mint.as_ref()])
+}
@@ -0,0 +1,309 @@
+//! Off-chain resolution of the transfer hook extra accounts.
+//!
+//! Account data is read through [`AccountFetcher`], implemented by RPC clients of the caller
+//! and by an in-memory `HashMap<Pubkey, Vec<u8>>` for tests and simulations.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::solana_program::sysvar::{self, clock::Clock};
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        transfer_fee::TransferFeeConfig, transfer_hook::TransferHook, BaseStateWithExtensions,
+        StateWithExtensions,
+    },
+};
+use spl_transfer_hook_interface::offchain;
+pub use spl_transfer_hook_interface::offchain::{AccountDataResult, AccountFetchError};
+use transfer_extensions::instructions::TransferLeg;
+use transfer_extensions::sol_sdk::{calculate_transfer_amounts, FeeMode};
+
+use crate::instructions;
+use crate::pda::*;
+
+/// Source of account data, `Ok(None)` when the account does not exist
+pub trait AccountFetcher {
+    fn get_account_data(&self, address: &Pubkey) -> AccountDataResult;
+}
+
+impl AccountFetcher for HashMap<Pubkey, Vec<u8>> {
+    fn get_account_data(&self, address: &Pubkey) -> AccountDataResult {
+        Ok(self.get(address).cloned())
+    }
+}
+
+fn fetch_existing(
+    fetcher: &impl AccountFetcher,
+    address: &Pubkey,
+) -> Result<Vec<u8>, AccountFetchError> {
+    fetcher
+        .get_account_data(address)?
+        .ok_or_else(|| format!("account {} not found", address).into())
+}
+
+/// Transfer hook program of a Token-2022 mint, `None` when the hook is unset
+pub fn get_transfer_hook_program_id(
+    fetcher: &impl AccountFetcher,
+    mint: &Pubkey,
+) -> Result<Option<Pubkey>, AccountFetchError> {
+    let data = fetch_existing(fetcher, mint)?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    Ok(mint
+        .get_extension::<TransferHook>()
+        .ok()
+        .and_then(|hook| Option::<Pubkey>::from(hook.program_id)))
+}
+
+/// Appends the hook program extra accounts of one transfer leg to `instruction`,
+/// in the order `sol_sdk` expects them: resolved metas, hook program, validation account.
+/// Does nothing when the mint has no transfer hook.
+pub fn add_extra_account_metas_for_transfer(
+    fetcher: &impl AccountFetcher,
+    instruction: &mut Instruction,
+    source_account: &Pubkey,
+    mint: &Pubkey,
+    destination_account: &Pubkey,
+    authority: &Pubkey,
+    amount: u64,
+) -> Result<(), AccountFetchError> {
+    let Some(hook_program_id) = get_transfer_hook_program_id(fetcher, mint)? else {
+        return Ok(());
+    };
+    block_on(offchain::add_extra_account_metas_for_execute(
+        instruction,
+        &hook_program_id,
+        source_account,
+        mint,
+        destination_account,
+        authority,
+        amount,
+        |address| std::future::ready(fetcher.get_account_data(&address)),
+    ))
+}
+
+/// Amount the hook program is executed with for a leg of `amount` in `fee_mode`.
+/// Token-2022 passes the amount leaving the source, so `FeeMode::Net` legs of mints with
+/// a transfer fee add the fee of the current epoch, read from the Clock sysvar account.
+pub fn get_hook_amount(
+    fetcher: &impl AccountFetcher,
+    mint: &Pubkey,
+    amount: u64,
+    fee_mode: FeeMode,
+) -> Result<u64, AccountFetchError> {
+    if fee_mode == FeeMode::Gross {
+        return Ok(amount);
+    }
+    let data = fetch_existing(fetcher, mint)?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    let Ok(config) = mint.get_extension::<TransferFeeConfig>() else {
+        return Ok(amount);
+    };
+    let clock: Clock = bincode::deserialize(&fetch_existing(fetcher, &sysvar::clock::ID)?)?;
+    let (transfer_amount, _fee) =
+        calculate_transfer_amounts(config, clock.epoch, amount, fee_mode)?;
+    Ok(transfer_amount)
+}
+
+/// `multi_transfers` with its `remaining_accounts` resolved.
+/// For Token-2022 mints these are the hook extra accounts of both legs,
+/// for legacy Token mints the stats accounts of the token accounts and the owner shard,
+/// followed by the transfer hook program and the transfer recorder.
+/// Hook accounts are resolved with the [`get_hook_amount`] of each leg.
+#[allow(clippy::too_many_arguments)]
+pub fn multi_transfers_with_extra_accounts(
+    fetcher: &impl AccountFetcher,
+    signer: &Pubkey,
+    source_account: &Pubkey,
+    destination_account_1: &Pubkey,
+    destination_account_2: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    amount1: u64,
+    amount2: u64,
+    fee_mode1: FeeMode,
+    fee_mode2: FeeMode,
+) -> Result<Instruction, AccountFetchError> {
+    let mut instruction = instructions::multi_transfers(
+        signer,
+        source_account,
+        destination_account_1,
+        destination_account_2,
+        mint,
+        token_program,
+        amount1,
+        amount2,
+        fee_mode1,
+        fee_mode2,
+    );
+
+    if *token_program == anchor_spl::token::ID {
+        let data = fetch_existing(fetcher, source_account)?;
+        let source = spl_token_2022::state::Account::unpack(&data)?;
+        instruction.accounts.extend(
+            [
+                get_transfer_stats_address(source_account, mint),
+                get_transfer_stats_address(destination_account_1, mint),
+                get_transfer_stats_address(destination_account_2, mint),
+                get_mint_stats_address(mint),
+                get_owner_global_stats_shard_address(&source.owner),
+            ]
+            .map(|address| AccountMeta::new(address, false)),
+        );
+        instruction.accounts.extend([
+            AccountMeta::new_readonly(transfer_hook::ID, false),
+            AccountMeta::new_readonly(get_transfer_recorder_address(), false),
+        ]);
+        return Ok(instruction);
+    }
+
+    for (destination_account, amount, fee_mode) in [
+        (destination_account_1, amount1, fee_mode1),
+        (destination_account_2, amount2, fee_mode2),
+    ] {
+        add_extra_account_metas_for_transfer(
+            fetcher,
+            &mut instruction,
+            source_account,
+            mint,
+            destination_account,
+            signer,
+            get_hook_amount(fetcher, mint, amount, fee_mode)?,
+        )?;
+    }
+    Ok(instruction)
+}
+
+/// Transfer of a `batch_transfers` instruction, moved by the batch signer
+#[derive(Clone, Copy, Debug)]
+pub struct BatchTransfer {
+    pub source_account: Pubkey,
+    pub mint: Pubkey,
+    pub destination_account: Pubkey,
+    /// Owner of the mint, Token-2022 or legacy Token program
+    pub token_program: Pubkey,
+    pub amount: u64,
+    pub fee_mode: FeeMode,
+}
+
+/// `batch_transfers` with the accounts of all legs resolved.
+/// Accounts shared between legs, hook extra accounts included, are passed once.
+/// Legacy Token legs get the stats accounts of their token accounts, recorded through the
+/// transfer hook program with the transfer recorder. The hook accounts of Token-2022 legs
+/// are resolved with their [`get_hook_amount`] for any hook program.
+pub fn batch_transfers_with_extra_accounts(
+    fetcher: &impl AccountFetcher,
+    signer: &Pubkey,
+    transfers: &[BatchTransfer],
+) -> Result<Instruction, AccountFetchError> {
+    let mut pool = AccountPool::default();
+    let mut legs = Vec::with_capacity(transfers.len());
+    for transfer in transfers {
+        let source_index = pool.index_of(AccountMeta::new(transfer.source_account, false))?;
+        let mint_index = pool.index_of(AccountMeta::new_readonly(transfer.mint, false))?;
+        let destination_index =
+            pool.index_of(AccountMeta::new(transfer.destination_account, false))?;
+        pool.index_of(AccountMeta::new_readonly(transfer.token_program, false))?;
+
+        let stats_indexes = if transfer.token_program == anchor_spl::token::ID {
+            let data = fetch_existing(fetcher, &transfer.source_account)?;
+            let source = spl_token_2022::state::Account::unpack(&data)?;
+            let mut indexes = [0; 4];
+            for (index, address) in indexes.iter_mut().zip([
+                get_transfer_stats_address(&transfer.source_account, &transfer.mint),
+                get_transfer_stats_address(&transfer.destination_account, &transfer.mint),
+                get_mint_stats_address(&transfer.mint),
+                get_owner_global_stats_shard_address(&source.owner),
+            ]) {
+                *index = pool.index_of(AccountMeta::new(address, false))?;
+            }
+            pool.index_of(AccountMeta::new_readonly(transfer_hook::ID, false))?;
+            pool.index_of(AccountMeta::new_readonly(get_transfer_recorder_address(), false))?;
+            Some(indexes)
+        } else {
+            let mut leg = Instruction::new_with_bytes(
+                transfer_extensions::ID,
+                &[],
+                vec![
+                    AccountMeta::new(transfer.source_account, false),
+                    AccountMeta::new_readonly(transfer.mint, false),
+                    AccountMeta::new(transfer.destination_account, false),
+                    AccountMeta::new_readonly(*signer, true),
+                ],
+            );
+            add_extra_account_metas_for_transfer(
+                fetcher,
+                &mut leg,
+                &transfer.source_account,
+                &transfer.mint,
+                &transfer.destination_account,
+                signer,
+                get_hook_amount(fetcher, &transfer.mint, transfer.amount, transfer.fee_mode)?,
+            )?;
+            for meta in leg.accounts.drain(4..) {
+                pool.index_of(meta)?;
+            }
+            None
+        };
+
+        legs.push(TransferLeg {
+            source_index,
+            mint_index,
+            destination_index,
+            amount: transfer.amount,
+            fee_mode: transfer.fee_mode,
+            stats_indexes,
+        });
+    }
+    Ok(instructions::batch_transfers(signer, legs, pool.0))
+}
+
+/// `remaining_accounts` of a batch, each key once with the union of its privileges
+#[derive(Default)]
+struct AccountPool(Vec<AccountMeta>);
+
+impl AccountPool {
+    fn index_of(&mut self, meta: AccountMeta) -> Result<u8, AccountFetchError> {
+        let index = match self.0.iter_mut().position(|x| x.pubkey == meta.pubkey) {
+            Some(index) => {
+                self.0[index].is_writable |= meta.is_writable;
+                self.0[index].is_signer |= meta.is_signer;
+                index
+            }
+            None => {
+                self.0.push(meta);
+                self.0.len() - 1
+            }
+        };
+        u8::try_from(index).map_err(|_| "too many accounts in the batch".into())
+    }
+}
+
+/// Polls `future` once, account fetches are synchronous so the resolution never suspends
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    match future
+        .as_mut()
+        .poll(&mut Context::from_waker(&noop_waker()))
+    {
+        Poll::Ready(output) => output,
+        Poll::Pending => unreachable!("account fetches are ready on first poll"),
+    }
+}
+
+/// Waker that is never woken, `Waker::noop` needs Rust 1.85
+fn noop_waker() -> Waker {
+    const VTABLE: RawWakerVTable = RawWakerVTable::new(
+        |_| RawWaker::new(std::ptr::null(), &VTABLE),
+        |_| {},
+        |_| {},
+        |_| {},
+    );
+    // SAFETY: the vtable functions ignore the data pointer
+    unsafe { Waker::from_raw(RawWaker::new(std::ptr::null(), &VTABLE)) }
+}
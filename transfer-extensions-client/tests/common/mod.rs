@@ -0,0 +1,581 @@
+//! In-process cluster running the `transfer_extensions` and `transfer_hook` programs and the
+//! `seeds_hook` and `ata_hook` reference hooks, loaded from `target/deploy` (run `anchor build` first) or from `SBF_OUT_DIR`.
+
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::instruction::{Instruction, InstructionError};
+use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::{
+    get_associated_token_address_with_program_id,
+    spl_associated_token_account::instruction::create_associated_token_account,
+};
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        transfer_fee, transfer_hook as transfer_hook_extension, ExtensionType, StateWithExtensions,
+    },
+    state::{Account, Mint},
+};
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use solana_sdk::account::{Account as SolanaAccount, AccountSharedData};
+use solana_sdk::clock::Clock;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::{Transaction, TransactionError};
+use transfer_extensions_client::accounts::decode_account;
+use transfer_extensions_client::instructions;
+use transfer_extensions_client::pda::*;
+use transfer_extensions_client::resolve::add_extra_account_metas_for_transfer;
+use transfer_extensions_client::transfer_extensions;
+use transfer_extensions_client::transfer_extensions::states::Versioned;
+use transfer_extensions_client::transfer_hook::{
+    self,
+    instructions::CounterLayout,
+    state::{HookModules, TransferStats},
+};
+
+pub const DECIMALS: u8 = 9;
+
+/// Lamports given to every wallet, enough to pay the rent of the accounts it creates
+const WALLET_LAMPORTS: u64 = 1_000_000_000;
+
+/// Transfers of hook-enabled mints run two CPIs deep, above the default limit
+const COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+pub struct TestContext {
+    pub context: ProgramTestContext,
+}
+
+/// Token-2022 mint with the transfer hook, three funded wallets holding an associated token
+/// account each, with stats, shards and meta list initialized
+pub struct HookMint {
+    pub mint: Pubkey,
+    pub wallets: [Keypair; 3],
+    pub token_accounts: [Pubkey; 3],
+}
+
+impl TestContext {
+    pub async fn start() -> Self {
+        if std::env::var("BPF_OUT_DIR").is_err() && std::env::var("SBF_OUT_DIR").is_err() {
+            std::env::set_var(
+                "SBF_OUT_DIR",
+                concat!(env!("CARGO_MANIFEST_DIR"), "/../target/deploy"),
+            );
+        }
+        let mut program_test = ProgramTest::default();
+        program_test.prefer_bpf(true);
+        for (name, program_id) in [
+            ("transfer_extensions", transfer_extensions::ID),
+            ("transfer_hook", transfer_hook::ID),
+            ("seeds_hook", seeds_hook::ID),
+            ("ata_hook", ata_hook::ID),
+        ] {
+            assert_program_is_built(name);
+            program_test.add_program(name, program_id, None);
+        }
+
+        let mut test = Self {
+            context: program_test.start_with_context().await,
+        };
+        let payer = test.payer();
+        test.process(&[instructions::initialize_program_data(&payer)], &[])
+            .await
+            .unwrap();
+        test
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.context.payer.pubkey()
+    }
+
+    /// Signs with the payer and `signers` against a new blockhash,
+    /// so that a failed transaction can be sent again
+    pub async fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), TransactionError> {
+        let transaction = self.transaction(instructions, signers).await;
+        self.context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(|error| error.unwrap())
+    }
+
+    /// Return data of the last instruction, the transaction is only simulated
+    pub async fn simulate(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Vec<u8> {
+        let transaction = self.transaction(instructions, signers).await;
+        let simulation = self
+            .context
+            .banks_client
+            .simulate_transaction(transaction)
+            .await
+            .unwrap();
+        simulation.result.unwrap().unwrap();
+        simulation
+            .simulation_details
+            .and_then(|details| details.return_data)
+            .map(|return_data| return_data.data)
+            .unwrap_or_default()
+    }
+
+    async fn transaction(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Transaction {
+        let blockhash = self.context.get_new_latest_blockhash().await.unwrap();
+
+        let instructions: Vec<_> = std::iter::once(
+            ComputeBudgetInstruction::set_compute_unit_limit(COMPUTE_UNIT_LIMIT),
+        )
+        .chain(instructions.iter().cloned())
+        .collect();
+        let signers: Vec<&Keypair> = std::iter::once(&self.context.payer)
+            .chain(signers.iter().copied())
+            .collect();
+        Transaction::new_signed_with_payer(&instructions, Some(&self.payer()), &signers, blockhash)
+    }
+
+    pub async fn clock(&mut self) -> Clock {
+        self.context.banks_client.get_sysvar().await.unwrap()
+    }
+
+    pub async fn account_data(&mut self, address: &Pubkey) -> Option<Vec<u8>> {
+        self.context
+            .banks_client
+            .get_account(*address)
+            .await
+            .unwrap()
+            .map(|account| account.data)
+    }
+
+    /// Account data of `addresses` that exist, to resolve extra accounts from
+    pub async fn snapshot(&mut self, addresses: &[Pubkey]) -> HashMap<Pubkey, Vec<u8>> {
+        let mut accounts = HashMap::new();
+        for address in addresses {
+            if let Some(data) = self.account_data(address).await {
+                accounts.insert(*address, data);
+            }
+        }
+        accounts
+    }
+
+    /// Stores a rent-exempt account, e.g. one no instruction creates anymore
+    pub async fn set_account(&mut self, address: &Pubkey, owner: &Pubkey, data: Vec<u8>) {
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let account = SolanaAccount {
+            lamports: rent.minimum_balance(data.len()),
+            data,
+            owner: *owner,
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.context
+            .set_account(address, &AccountSharedData::from(account));
+    }
+
+    pub async fn state<T: Versioned>(&mut self, address: &Pubkey) -> T {
+        let data = self
+            .account_data(address)
+            .await
+            .unwrap_or_else(|| panic!("account {} not found", address));
+        decode_account(&data).unwrap()
+    }
+
+    pub async fn stats(&mut self, subject: &Pubkey, mint: &Pubkey) -> TransferStats {
+        self.state(&get_transfer_stats_address(subject, mint)).await
+    }
+
+    pub async fn balance(&mut self, token_account: &Pubkey) -> u64 {
+        let data = self.account_data(token_account).await.unwrap();
+        StateWithExtensions::<Account>::unpack(&data)
+            .unwrap()
+            .base
+            .amount
+    }
+
+    pub async fn new_wallet(&mut self) -> Keypair {
+        let wallet = Keypair::new();
+        let payer = self.payer();
+        self.process(
+            &[system_instruction::transfer(
+                &payer,
+                &wallet.pubkey(),
+                WALLET_LAMPORTS,
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+        wallet
+    }
+
+    /// Mint with the mint authority of the payer, Token-2022 mints get `extensions` initialized
+    /// with the transfer hook of `transfer_hook` and a transfer fee of `transfer_fee_bps`
+    pub async fn create_mint(
+        &mut self,
+        token_program: &Pubkey,
+        extensions: &[ExtensionType],
+        transfer_fee_bps: u16,
+    ) -> Pubkey {
+        self.create_mint_with_hook_program(
+            token_program,
+            extensions,
+            transfer_fee_bps,
+            &transfer_hook::ID,
+        )
+        .await
+    }
+
+    /// [`Self::create_mint`] with the transfer hook of `hook_program_id`
+    pub async fn create_mint_with_hook_program(
+        &mut self,
+        token_program: &Pubkey,
+        extensions: &[ExtensionType],
+        transfer_fee_bps: u16,
+        hook_program_id: &Pubkey,
+    ) -> Pubkey {
+        let mint = Keypair::new();
+        let payer = self.payer();
+        let space = ExtensionType::try_calculate_account_len::<Mint>(extensions).unwrap();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+
+        let mut ixs = vec![system_instruction::create_account(
+            &payer,
+            &mint.pubkey(),
+            rent.minimum_balance(space),
+            space as u64,
+            token_program,
+        )];
+        for extension in extensions {
+            ixs.push(match extension {
+                ExtensionType::TransferHook => transfer_hook_extension::instruction::initialize(
+                    token_program,
+                    &mint.pubkey(),
+                    Some(payer),
+                    Some(*hook_program_id),
+                )
+                .unwrap(),
+                ExtensionType::TransferFeeConfig => {
+                    transfer_fee::instruction::initialize_transfer_fee_config(
+                        token_program,
+                        &mint.pubkey(),
+                        Some(&payer),
+                        Some(&payer),
+                        transfer_fee_bps,
+                        u64::MAX,
+                    )
+                    .unwrap()
+                }
+                _ => panic!(
+                    "create_mint only initializes the TransferHook and TransferFeeConfig extensions, got {:?}",
+                    extension
+                ),
+            });
+        }
+        ixs.push(
+            spl_token_2022::instruction::initialize_mint2(
+                token_program,
+                &mint.pubkey(),
+                &payer,
+                None,
+                DECIMALS,
+            )
+            .unwrap(),
+        );
+        self.process(&ixs, &[&mint]).await.unwrap();
+        mint.pubkey()
+    }
+
+    pub async fn create_token_account(
+        &mut self,
+        owner: &Pubkey,
+        mint: &Pubkey,
+        token_program: &Pubkey,
+    ) -> Pubkey {
+        let payer = self.payer();
+        self.process(
+            &[create_associated_token_account(
+                &payer,
+                owner,
+                mint,
+                token_program,
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+        get_associated_token_address_with_program_id(owner, mint, token_program)
+    }
+
+    pub async fn mint_to(
+        &mut self,
+        mint: &Pubkey,
+        token_account: &Pubkey,
+        token_program: &Pubkey,
+        amount: u64,
+    ) {
+        let payer = self.payer();
+        self.process(
+            &[spl_token_2022::instruction::mint_to(
+                token_program,
+                mint,
+                token_account,
+                &payer,
+                &[],
+                amount,
+            )
+            .unwrap()],
+            &[],
+        )
+        .await
+        .unwrap();
+    }
+
+    pub async fn initialize_stats(&mut self, subjects: &[Pubkey], mint: &Pubkey) {
+        let payer = self.payer();
+        let ixs: Vec<_> = subjects
+            .iter()
+            .map(|subject| instructions::initialize_transfer_stats(&payer, subject, mint))
+            .collect();
+        self.process(&ixs, &[]).await.unwrap();
+    }
+
+    /// Initializes the global stats shard transfers of `owner` are recorded in, if missing
+    pub async fn initialize_shard_of(&mut self, owner: &Pubkey) {
+        if self
+            .account_data(&get_owner_global_stats_shard_address(owner))
+            .await
+            .is_none()
+        {
+            let payer = self.payer();
+            self.process(
+                &[instructions::initialize_global_stats_shard(
+                    &payer,
+                    owner.to_bytes()[0],
+                )],
+                &[],
+            )
+            .await
+            .unwrap();
+        }
+    }
+
+    /// Stats and shard the hook updates for transfers out of the vault of the PDA `owner`
+    pub async fn initialize_vault_stats(&mut self, owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+        let vault = get_vault_address(owner, mint, &spl_token_2022::ID);
+        self.initialize_stats(&[vault], mint).await;
+        self.initialize_shard_of(owner).await;
+        vault
+    }
+
+    pub async fn initialize_meta_list(&mut self, mint: &Pubkey, modules: HookModules) {
+        let payer = self.payer();
+        self.process(
+            &[instructions::initialize_extra_account_meta_list(
+                &payer, mint, modules,
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+    }
+
+    /// Meta list of a mint hooked to the `seeds_hook` or `ata_hook` reference program
+    pub async fn initialize_reference_meta_list(
+        &mut self,
+        hook_program_id: &Pubkey,
+        mint: &Pubkey,
+    ) {
+        let payer = self.payer();
+        let extra_metas_account =
+            spl_transfer_hook_interface::get_extra_account_metas_address(mint, hook_program_id);
+        let (accounts, data) = if *hook_program_id == seeds_hook::ID {
+            (
+                seeds_hook::accounts::InitializeExtraAccountMetaList {
+                    extra_metas_account,
+                    mint: *mint,
+                    payer,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+                seeds_hook::instruction::InitializeExtraAccountMetaList {}.data(),
+            )
+        } else {
+            (
+                ata_hook::accounts::InitializeExtraAccountMetaList {
+                    extra_metas_account,
+                    mint: *mint,
+                    payer,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+                ata_hook::instruction::InitializeExtraAccountMetaList {}.data(),
+            )
+        };
+        self.process(
+            &[Instruction::new_with_bytes(
+                *hook_program_id,
+                &data,
+                accounts,
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+    }
+
+    /// Hook mint with a transfer fee of `transfer_fee_bps` when non zero,
+    /// the first wallet holds `supply`
+    pub async fn create_hook_mint(&mut self, transfer_fee_bps: u16, supply: u64) -> HookMint {
+        let extensions: &[ExtensionType] = if transfer_fee_bps > 0 {
+            &[
+                ExtensionType::TransferHook,
+                ExtensionType::TransferFeeConfig,
+            ]
+        } else {
+            &[ExtensionType::TransferHook]
+        };
+        let mint = self
+            .create_mint(&spl_token_2022::ID, extensions, transfer_fee_bps)
+            .await;
+
+        let mut wallets = vec![];
+        let mut token_accounts = vec![];
+        for _ in 0..3 {
+            let wallet = self.new_wallet().await;
+            token_accounts.push(
+                self.create_token_account(&wallet.pubkey(), &mint, &spl_token_2022::ID)
+                    .await,
+            );
+            self.initialize_shard_of(&wallet.pubkey()).await;
+            wallets.push(wallet);
+        }
+        let subjects: Vec<_> = std::iter::once(mint)
+            .chain(token_accounts.iter().copied())
+            .collect();
+        self.initialize_stats(&subjects, &mint).await;
+        self.initialize_meta_list(&mint, counters(CounterLayout::TokenAccount))
+            .await;
+        self.mint_to(&mint, &token_accounts[0], &spl_token_2022::ID, supply)
+            .await;
+
+        HookMint {
+            mint,
+            wallets: wallets.try_into().unwrap(),
+            token_accounts: token_accounts.try_into().unwrap(),
+        }
+    }
+
+    /// Appends the hook extra accounts of the `(source, destination, authority, amount)`
+    /// transfers of `mint` to `instruction`, in order
+    pub async fn add_hook_accounts(
+        &mut self,
+        instruction: &mut Instruction,
+        mint: &Pubkey,
+        transfers: &[(Pubkey, Pubkey, Pubkey, u64)],
+    ) {
+        for (source, destination, authority, amount) in transfers {
+            let accounts = self
+                .snapshot(&[
+                    *mint,
+                    get_extra_account_metas_address(mint),
+                    *source,
+                    *destination,
+                ])
+                .await;
+            add_extra_account_metas_for_transfer(
+                &accounts,
+                instruction,
+                source,
+                mint,
+                destination,
+                authority,
+                *amount,
+            )
+            .unwrap();
+        }
+    }
+
+    /// Plain Token-2022 `transfer_checked`, the transfer hook fires from the token program
+    pub async fn transfer(
+        &mut self,
+        source: &Pubkey,
+        mint: &Pubkey,
+        destination: &Pubkey,
+        authority: &Keypair,
+        amount: u64,
+    ) -> Result<(), TransactionError> {
+        let mut instruction = spl_token_2022::instruction::transfer_checked(
+            &spl_token_2022::ID,
+            source,
+            mint,
+            destination,
+            &authority.pubkey(),
+            &[],
+            amount,
+            DECIMALS,
+        )
+        .unwrap();
+        self.add_hook_accounts(
+            &mut instruction,
+            mint,
+            &[(*source, *destination, authority.pubkey(), amount)],
+        )
+        .await;
+        self.process(&[instruction], &[authority]).await
+    }
+}
+
+/// The programs are loaded from their `.so`, which `cargo test` doesn't build
+fn assert_program_is_built(name: &str) {
+    let out_dir = std::env::var("SBF_OUT_DIR")
+        .or_else(|_| std::env::var("BPF_OUT_DIR"))
+        .unwrap();
+    let path = std::path::Path::new(&out_dir).join(format!("{}.so", name));
+    assert!(
+        path.exists(),
+        "{} not found, run `anchor build` before the tests",
+        path.display()
+    );
+}
+
+/// Writable, non signer metas of `addresses`
+pub fn writable(addresses: &[Pubkey]) -> Vec<AccountMeta> {
+    addresses
+        .iter()
+        .map(|address| AccountMeta::new(*address, false))
+        .collect()
+}
+
+/// Custom program error code of a failed transaction
+/// Hook modules only recording transfer stats
+pub fn counters(layout: CounterLayout) -> HookModules {
+    HookModules {
+        counters: Some(layout),
+        ..HookModules::default()
+    }
+}
+
+pub fn custom_error(result: Result<(), TransactionError>) -> u32 {
+    match result {
+        Err(TransactionError::InstructionError(_, InstructionError::Custom(code))) => code,
+        other => panic!("expected a custom program error, got {:?}", other),
+    }
+}
+
+pub fn transfer_extensions_error(
+    error: transfer_extensions::errors::TransferExtensionsError,
+) -> u32 {
+    error.into()
+}
@@ -0,0 +1,338 @@
+//! Transfers of mints hooked to the `seeds_hook` and `ata_hook` reference programs,
+//! resolved from their meta lists alone, see `common`
+
+mod common;
+
+use std::collections::HashMap;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        transfer_fee::TransferFeeConfig, BaseStateWithExtensions, ExtensionType,
+        StateWithExtensions,
+    },
+    state::{Account, Mint},
+};
+use ata_hook::errors::AtaHookError;
+use common::*;
+use seeds_hook::instructions::AMOUNT_SEED;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::{system_instruction, sysvar};
+use spl_transfer_hook_interface::get_extra_account_metas_address;
+use transfer_extensions_client::resolve::{
+    batch_transfers_with_extra_accounts, multi_transfers_with_extra_accounts, BatchTransfer,
+};
+use transfer_extensions_client::transfer_extensions::sol_sdk::{
+    calculate_transfer_amounts, FeeMode,
+};
+
+const TOKEN: u64 = 10u64.pow(DECIMALS as u32);
+
+/// Token-2022 mint of `hook_program_id` with a transfer fee of `transfer_fee_bps` when non
+/// zero and an associated token account per wallet, the first one holding `supply`
+async fn create_reference_mint(
+    test: &mut TestContext,
+    hook_program_id: &Pubkey,
+    transfer_fee_bps: u16,
+    wallets: &[Pubkey],
+    supply: u64,
+) -> (Pubkey, Vec<Pubkey>) {
+    let extensions: &[ExtensionType] = if transfer_fee_bps > 0 {
+        &[
+            ExtensionType::TransferHook,
+            ExtensionType::TransferFeeConfig,
+        ]
+    } else {
+        &[ExtensionType::TransferHook]
+    };
+    let mint = test
+        .create_mint_with_hook_program(
+            &spl_token_2022::ID,
+            extensions,
+            transfer_fee_bps,
+            hook_program_id,
+        )
+        .await;
+    test.initialize_reference_meta_list(hook_program_id, &mint)
+        .await;
+
+    let mut token_accounts = vec![];
+    for wallet in wallets {
+        token_accounts.push(
+            test.create_token_account(wallet, &mint, &spl_token_2022::ID)
+                .await,
+        );
+    }
+    test.mint_to(&mint, &token_accounts[0], &spl_token_2022::ID, supply)
+        .await;
+    (mint, token_accounts)
+}
+
+async fn new_wallets(test: &mut TestContext) -> [Keypair; 3] {
+    [
+        test.new_wallet().await,
+        test.new_wallet().await,
+        test.new_wallet().await,
+    ]
+}
+
+/// Accounts the extra accounts of transfers between `token_accounts` of `mint` are resolved from
+async fn reference_snapshot(
+    test: &mut TestContext,
+    hook_program_id: &Pubkey,
+    mint: &Pubkey,
+    token_accounts: &[Pubkey],
+) -> HashMap<Pubkey, Vec<u8>> {
+    let addresses: Vec<_> = [
+        *mint,
+        get_extra_account_metas_address(mint, hook_program_id),
+        sysvar::clock::ID,
+    ]
+    .into_iter()
+    .chain(token_accounts.iter().copied())
+    .collect();
+    test.snapshot(&addresses).await
+}
+
+fn amount_address(amount: u64) -> Pubkey {
+    Pubkey::find_program_address(&[AMOUNT_SEED, &amount.to_le_bytes()], &seeds_hook::ID).0
+}
+
+#[tokio::test]
+async fn batch_transfers_through_seeds_hook() {
+    let mut test = TestContext::start().await;
+    let wallets = new_wallets(&mut test).await;
+    let owners = wallets.each_ref().map(|wallet| wallet.pubkey());
+    let (mint, token_accounts) =
+        create_reference_mint(&mut test, &seeds_hook::ID, 0, &owners, 100 * TOKEN).await;
+
+    let transfers = [1, 2].map(|index| BatchTransfer {
+        source_account: token_accounts[0],
+        mint,
+        destination_account: token_accounts[index],
+        token_program: spl_token_2022::ID,
+        amount: index as u64 * TOKEN,
+        fee_mode: FeeMode::Gross,
+    });
+    let accounts = reference_snapshot(&mut test, &seeds_hook::ID, &mint, &token_accounts).await;
+    let instruction =
+        batch_transfers_with_extra_accounts(&accounts, &owners[0], &transfers).unwrap();
+    test.process(&[instruction], &[&wallets[0]]).await.unwrap();
+
+    assert_eq!(test.balance(&token_accounts[0]).await, 97 * TOKEN);
+    assert_eq!(test.balance(&token_accounts[1]).await, TOKEN);
+    assert_eq!(test.balance(&token_accounts[2]).await, 2 * TOKEN);
+}
+
+#[tokio::test]
+async fn batch_transfers_through_seeds_hook_with_net_fee_mode() {
+    let mut test = TestContext::start().await;
+    let fee_bps = 100;
+    let wallets = new_wallets(&mut test).await;
+    let owners = wallets.each_ref().map(|wallet| wallet.pubkey());
+    let (mint, token_accounts) =
+        create_reference_mint(&mut test, &seeds_hook::ID, fee_bps, &owners, 100 * TOKEN).await;
+
+    let transfers =
+        [(1, FeeMode::Net), (2, FeeMode::Gross)].map(|(index, fee_mode)| BatchTransfer {
+            source_account: token_accounts[0],
+            mint,
+            destination_account: token_accounts[index],
+            token_program: spl_token_2022::ID,
+            amount: 2 * TOKEN,
+            fee_mode,
+        });
+    let accounts = reference_snapshot(&mut test, &seeds_hook::ID, &mint, &token_accounts).await;
+    let instruction =
+        batch_transfers_with_extra_accounts(&accounts, &owners[0], &transfers).unwrap();
+
+    // the hook of the net leg is executed with the gross amount
+    let mint_data = test.account_data(&mint).await.unwrap();
+    let mint_state = StateWithExtensions::<Mint>::unpack(&mint_data).unwrap();
+    let (net_leg_amount, _) = calculate_transfer_amounts(
+        mint_state.get_extension::<TransferFeeConfig>().unwrap(),
+        test.clock().await.epoch,
+        2 * TOKEN,
+        FeeMode::Net,
+    )
+    .unwrap();
+    assert!(net_leg_amount > 2 * TOKEN);
+    for amount in [net_leg_amount, 2 * TOKEN] {
+        assert!(instruction
+            .accounts
+            .iter()
+            .any(|meta| meta.pubkey == amount_address(amount)));
+    }
+    test.process(&[instruction], &[&wallets[0]]).await.unwrap();
+
+    assert_eq!(test.balance(&token_accounts[1]).await, 2 * TOKEN);
+    let fee = 2 * TOKEN * fee_bps as u64 / 10_000;
+    assert_eq!(test.balance(&token_accounts[2]).await, 2 * TOKEN - fee);
+}
+
+#[tokio::test]
+async fn multi_transfers_through_seeds_hook_with_net_fee_mode() {
+    let mut test = TestContext::start().await;
+    let fee_bps = 100;
+    let wallets = new_wallets(&mut test).await;
+    let owners = wallets.each_ref().map(|wallet| wallet.pubkey());
+    let (mint, token_accounts) =
+        create_reference_mint(&mut test, &seeds_hook::ID, fee_bps, &owners, 100 * TOKEN).await;
+
+    let accounts = reference_snapshot(&mut test, &seeds_hook::ID, &mint, &token_accounts).await;
+    let instruction = multi_transfers_with_extra_accounts(
+        &accounts,
+        &owners[0],
+        &token_accounts[0],
+        &token_accounts[1],
+        &token_accounts[2],
+        &mint,
+        &spl_token_2022::ID,
+        TOKEN,
+        2 * TOKEN,
+        FeeMode::Net,
+        FeeMode::Gross,
+    )
+    .unwrap();
+    test.process(&[instruction], &[&wallets[0]]).await.unwrap();
+
+    assert_eq!(test.balance(&token_accounts[1]).await, TOKEN);
+    let fee_2 = 2 * TOKEN * fee_bps as u64 / 10_000;
+    assert_eq!(test.balance(&token_accounts[2]).await, 2 * TOKEN - fee_2);
+}
+
+#[tokio::test]
+async fn batch_transfers_through_ata_hook() {
+    let mut test = TestContext::start().await;
+    let wallets = new_wallets(&mut test).await;
+    let owners = wallets.each_ref().map(|wallet| wallet.pubkey());
+    let (mint, token_accounts) =
+        create_reference_mint(&mut test, &ata_hook::ID, 0, &owners, 100 * TOKEN).await;
+
+    let transfers = [1, 2].map(|index| BatchTransfer {
+        source_account: token_accounts[0],
+        mint,
+        destination_account: token_accounts[index],
+        token_program: spl_token_2022::ID,
+        amount: TOKEN,
+        fee_mode: FeeMode::Gross,
+    });
+    let accounts = reference_snapshot(&mut test, &ata_hook::ID, &mint, &token_accounts).await;
+    let instruction =
+        batch_transfers_with_extra_accounts(&accounts, &owners[0], &transfers).unwrap();
+    test.process(&[instruction], &[&wallets[0]]).await.unwrap();
+
+    assert_eq!(test.balance(&token_accounts[1]).await, TOKEN);
+    assert_eq!(test.balance(&token_accounts[2]).await, TOKEN);
+}
+
+#[tokio::test]
+async fn batch_transfers_through_ata_hook_to_other_token_account_fail() {
+    let mut test = TestContext::start().await;
+    let wallets = new_wallets(&mut test).await;
+    let owners = wallets.each_ref().map(|wallet| wallet.pubkey());
+    let (mint, token_accounts) =
+        create_reference_mint(&mut test, &ata_hook::ID, 0, &owners[..1], 100 * TOKEN).await;
+
+    let destination = Keypair::new();
+    let payer = test.payer();
+    let space =
+        ExtensionType::try_calculate_account_len::<Account>(&[ExtensionType::TransferHookAccount])
+            .unwrap();
+    let rent = test.context.banks_client.get_rent().await.unwrap();
+    test.process(
+        &[
+            system_instruction::create_account(
+                &payer,
+                &destination.pubkey(),
+                rent.minimum_balance(space),
+                space as u64,
+                &spl_token_2022::ID,
+            ),
+            spl_token_2022::instruction::initialize_account3(
+                &spl_token_2022::ID,
+                &destination.pubkey(),
+                &mint,
+                &owners[1],
+            )
+            .unwrap(),
+        ],
+        &[&destination],
+    )
+    .await
+    .unwrap();
+    let destination = destination.pubkey();
+
+    let transfers = [BatchTransfer {
+        source_account: token_accounts[0],
+        mint,
+        destination_account: destination,
+        token_program: spl_token_2022::ID,
+        amount: TOKEN,
+        fee_mode: FeeMode::Gross,
+    }];
+    let accounts = reference_snapshot(
+        &mut test,
+        &ata_hook::ID,
+        &mint,
+        &[token_accounts[0], destination],
+    )
+    .await;
+    let instruction =
+        batch_transfers_with_extra_accounts(&accounts, &owners[0], &transfers).unwrap();
+    assert_eq!(
+        custom_error(test.process(&[instruction], &[&wallets[0]]).await),
+        u32::from(AtaHookError::NotAssociatedTokenAccount)
+    );
+    assert_eq!(test.balance(&destination).await, 0);
+}
+
+#[tokio::test]
+async fn batch_transfers_across_hook_programs() {
+    let mut test = TestContext::start().await;
+    let hook_mint = test.create_hook_mint(0, 100 * TOKEN).await;
+    let signer = &hook_mint.wallets[0];
+    let owners = [signer.pubkey(), hook_mint.wallets[1].pubkey()];
+    let (seeds_mint, seeds_accounts) =
+        create_reference_mint(&mut test, &seeds_hook::ID, 100, &owners, 100 * TOKEN).await;
+    let (ata_mint, ata_accounts) =
+        create_reference_mint(&mut test, &ata_hook::ID, 0, &owners, 100 * TOKEN).await;
+
+    let leg = |mint, token_accounts: &[Pubkey], fee_mode| BatchTransfer {
+        source_account: token_accounts[0],
+        mint,
+        destination_account: token_accounts[1],
+        token_program: spl_token_2022::ID,
+        amount: TOKEN,
+        fee_mode,
+    };
+    let transfers = [
+        leg(hook_mint.mint, &hook_mint.token_accounts, FeeMode::Gross),
+        leg(seeds_mint, &seeds_accounts, FeeMode::Net),
+        leg(ata_mint, &ata_accounts, FeeMode::Gross),
+    ];
+    let mut accounts = test
+        .snapshot(&[
+            hook_mint.mint,
+            transfer_extensions_client::pda::get_extra_account_metas_address(&hook_mint.mint),
+            hook_mint.token_accounts[0],
+        ])
+        .await;
+    accounts
+        .extend(reference_snapshot(&mut test, &seeds_hook::ID, &seeds_mint, &seeds_accounts).await);
+    accounts.extend(reference_snapshot(&mut test, &ata_hook::ID, &ata_mint, &ata_accounts).await);
+    let instruction =
+        batch_transfers_with_extra_accounts(&accounts, &signer.pubkey(), &transfers).unwrap();
+    test.process(&[instruction], &[signer]).await.unwrap();
+
+    assert_eq!(test.balance(&hook_mint.token_accounts[1]).await, TOKEN);
+    assert_eq!(test.balance(&seeds_accounts[1]).await, TOKEN);
+    assert_eq!(test.balance(&ata_accounts[1]).await, TOKEN);
+    assert_eq!(
+        test.stats(&hook_mint.token_accounts[0], &hook_mint.mint)
+            .await
+            .transfers_out,
+        1
+    );
+}
@@ -0,0 +1,342 @@
+use std::collections::HashMap;
+
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::solana_program::sysvar::{self, clock::Clock};
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        transfer_fee::{TransferFee, TransferFeeConfig},
+        transfer_hook::TransferHook,
+        BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut,
+    },
+    state::{Account, AccountState, Mint},
+};
+use spl_tlv_account_resolution::state::ExtraAccountMetaList;
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+use transfer_extensions_client::pda::*;
+use transfer_extensions_client::resolve::{
+    batch_transfers_with_extra_accounts, multi_transfers_with_extra_accounts, BatchTransfer,
+};
+use transfer_extensions_client::transfer_extensions::sol_sdk::FeeMode;
+use transfer_extensions_client::transfer_hook::{
+    self, instructions::CounterLayout, state::HookModules,
+};
+
+fn hooked_mint_data(hook_program_id: &Pubkey) -> Vec<u8> {
+    let mut data =
+        vec![
+            0;
+            ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferHook])
+                .unwrap()
+        ];
+    let mut mint = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+    let hook = mint.init_extension::<TransferHook>(true).unwrap();
+    hook.program_id = Some(*hook_program_id).try_into().unwrap();
+    mint.base = Mint {
+        decimals: 6,
+        is_initialized: true,
+        ..Mint::default()
+    };
+    mint.pack_base();
+    mint.init_account_type().unwrap();
+    data
+}
+
+/// Mint of `hook_program_id` with a transfer fee of `transfer_fee_bps`, without maximum fee
+fn hooked_fee_mint_data(hook_program_id: &Pubkey, transfer_fee_bps: u16) -> Vec<u8> {
+    let mut data = vec![
+        0;
+        ExtensionType::try_calculate_account_len::<Mint>(&[
+            ExtensionType::TransferHook,
+            ExtensionType::TransferFeeConfig,
+        ])
+        .unwrap()
+    ];
+    let mut mint = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+    let hook = mint.init_extension::<TransferHook>(true).unwrap();
+    hook.program_id = Some(*hook_program_id).try_into().unwrap();
+    let config = mint.init_extension::<TransferFeeConfig>(true).unwrap();
+    let fee = TransferFee {
+        epoch: 0.into(),
+        maximum_fee: u64::MAX.into(),
+        transfer_fee_basis_points: transfer_fee_bps.into(),
+    };
+    config.older_transfer_fee = fee;
+    config.newer_transfer_fee = fee;
+    mint.base = Mint {
+        decimals: 6,
+        is_initialized: true,
+        ..Mint::default()
+    };
+    mint.pack_base();
+    mint.init_account_type().unwrap();
+    data
+}
+
+fn token_account_data(mint: &Pubkey, owner: &Pubkey) -> Vec<u8> {
+    let mut data = vec![0; Account::LEN];
+    Account {
+        mint: *mint,
+        owner: *owner,
+        amount: 1_000,
+        state: AccountState::Initialized,
+        ..Account::default()
+    }
+    .pack_into_slice(&mut data);
+    data
+}
+
+fn meta_list_data(modules: HookModules) -> Vec<u8> {
+    let metas = transfer_hook::instructions::get_extra_account_metas(
+        &transfer_extensions_client::transfer_extensions::ID,
+        &modules,
+    )
+    .unwrap();
+    let mut data = vec![0; ExtraAccountMetaList::size_of(metas.len()).unwrap()];
+    ExtraAccountMetaList::init::<ExecuteInstruction>(&mut data, &metas).unwrap();
+    data
+}
+
+#[test]
+fn resolves_hook_accounts_of_both_legs() {
+    let owner = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let source = Pubkey::new_unique();
+    let destination_1 = Pubkey::new_unique();
+    let destination_2 = Pubkey::new_unique();
+
+    let accounts = HashMap::from([
+        (mint, hooked_mint_data(&transfer_hook::ID)),
+        (source, token_account_data(&mint, &owner)),
+        (
+            get_extra_account_metas_address(&mint),
+            meta_list_data(HookModules {
+                counters: Some(CounterLayout::TokenAccount),
+                ..HookModules::default()
+            }),
+        ),
+    ]);
+
+    let instruction = multi_transfers_with_extra_accounts(
+        &accounts,
+        &owner,
+        &source,
+        &destination_1,
+        &destination_2,
+        &mint,
+        &spl_token_2022::ID,
+        100,
+        200,
+        FeeMode::Gross,
+        FeeMode::Net,
+    )
+    .unwrap();
+
+    let leg_accounts = |destination: &Pubkey| {
+        vec![
+            AccountMeta::new_readonly(get_hook_config_address(&mint), false),
+            AccountMeta::new(get_transfer_stats_address(&source, &mint), false),
+            AccountMeta::new(get_transfer_stats_address(destination, &mint), false),
+            AccountMeta::new(get_mint_stats_address(&mint), false),
+            AccountMeta::new(get_owner_global_stats_shard_address(&owner), false),
+            AccountMeta::new_readonly(transfer_hook::ID, false),
+            AccountMeta::new_readonly(get_extra_account_metas_address(&mint), false),
+        ]
+    };
+    let remaining_accounts = &instruction.accounts[6..];
+    assert_eq!(remaining_accounts[..7], leg_accounts(&destination_1));
+    assert_eq!(remaining_accounts[7..], leg_accounts(&destination_2));
+}
+
+#[test]
+fn pause_only_meta_list_resolves_the_hook_config() {
+    let owner = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let source = Pubkey::new_unique();
+    let destination = Pubkey::new_unique();
+
+    let accounts = HashMap::from([
+        (mint, hooked_mint_data(&transfer_hook::ID)),
+        (source, token_account_data(&mint, &owner)),
+        (
+            get_extra_account_metas_address(&mint),
+            meta_list_data(HookModules::default()),
+        ),
+    ]);
+
+    let instruction = multi_transfers_with_extra_accounts(
+        &accounts,
+        &owner,
+        &source,
+        &destination,
+        &destination,
+        &mint,
+        &spl_token_2022::ID,
+        100,
+        200,
+        FeeMode::Gross,
+        FeeMode::Gross,
+    )
+    .unwrap();
+
+    let leg_accounts = [
+        AccountMeta::new_readonly(get_hook_config_address(&mint), false),
+        AccountMeta::new_readonly(transfer_hook::ID, false),
+        AccountMeta::new_readonly(get_extra_account_metas_address(&mint), false),
+    ];
+    assert_eq!(
+        instruction.accounts[6..],
+        [leg_accounts.clone(), leg_accounts].concat()
+    );
+}
+
+#[test]
+fn missing_meta_list_is_an_error() {
+    let owner = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let source = Pubkey::new_unique();
+    let accounts = HashMap::from([
+        (mint, hooked_mint_data(&transfer_hook::ID)),
+        (source, token_account_data(&mint, &owner)),
+    ]);
+
+    assert!(multi_transfers_with_extra_accounts(
+        &accounts,
+        &owner,
+        &source,
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &mint,
+        &spl_token_2022::ID,
+        100,
+        200,
+        FeeMode::Gross,
+        FeeMode::Gross,
+    )
+    .is_err());
+}
+
+#[test]
+fn resolves_hook_accounts_of_net_legs_with_the_gross_amount() {
+    let owner = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let source = Pubkey::new_unique();
+    let destination = Pubkey::new_unique();
+    let destination_owner = Pubkey::new_unique();
+    let meta_list =
+        spl_transfer_hook_interface::get_extra_account_metas_address(&mint, &seeds_hook::ID);
+
+    let metas = seeds_hook::instructions::get_extra_account_metas().unwrap();
+    let mut meta_list_data = vec![0; ExtraAccountMetaList::size_of(metas.len()).unwrap()];
+    ExtraAccountMetaList::init::<ExecuteInstruction>(&mut meta_list_data, &metas).unwrap();
+    let accounts = HashMap::from([
+        (mint, hooked_fee_mint_data(&seeds_hook::ID, 100)),
+        (source, token_account_data(&mint, &owner)),
+        (destination, token_account_data(&mint, &destination_owner)),
+        (meta_list, meta_list_data),
+        (
+            sysvar::clock::ID,
+            bincode::serialize(&Clock::default()).unwrap(),
+        ),
+    ]);
+
+    let leg = |amount, fee_mode| BatchTransfer {
+        source_account: source,
+        mint,
+        destination_account: destination,
+        token_program: spl_token_2022::ID,
+        amount,
+        fee_mode,
+    };
+    let hook_accounts = |amount: u64| {
+        let find = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &seeds_hook::ID).0;
+        let owner_pda = find(&[seeds_hook::instructions::OWNER_SEED, owner.as_ref()]);
+        vec![
+            AccountMeta::new_readonly(
+                find(&[seeds_hook::instructions::AMOUNT_SEED, &amount.to_le_bytes()]),
+                false,
+            ),
+            AccountMeta::new_readonly(owner_pda, false),
+            AccountMeta::new_readonly(destination_owner, false),
+            AccountMeta::new_readonly(
+                find(&[
+                    seeds_hook::instructions::ROUTE_SEED,
+                    owner_pda.as_ref(),
+                    destination_owner.as_ref(),
+                ]),
+                false,
+            ),
+            AccountMeta::new_readonly(seeds_hook::ID, false),
+            AccountMeta::new_readonly(meta_list, false),
+        ]
+    };
+
+    // 99 net of the 1% fee leave 100 from the source
+    for (fee_mode, hook_amount) in [(FeeMode::Net, 100), (FeeMode::Gross, 99)] {
+        let instruction =
+            batch_transfers_with_extra_accounts(&accounts, &owner, &[leg(99, fee_mode)]).unwrap();
+        // after the signer, source, mint, destination and token program
+        assert_eq!(instruction.accounts[5..], hook_accounts(hook_amount));
+    }
+
+    let mut accounts = accounts;
+    accounts.remove(&sysvar::clock::ID);
+    assert!(
+        batch_transfers_with_extra_accounts(&accounts, &owner, &[leg(99, FeeMode::Net)]).is_err()
+    );
+}
+
+#[test]
+fn legacy_token_legs_record_stats_through_the_hook_program() {
+    let owner = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let source = Pubkey::new_unique();
+    let destination = Pubkey::new_unique();
+    let accounts = HashMap::from([(source, token_account_data(&mint, &owner))]);
+
+    let instruction = batch_transfers_with_extra_accounts(
+        &accounts,
+        &owner,
+        &[BatchTransfer {
+            source_account: source,
+            mint,
+            destination_account: destination,
+            token_program: anchor_spl::token::ID,
+            amount: 100,
+            fee_mode: FeeMode::Gross,
+        }],
+    )
+    .unwrap();
+
+    // after the signer, source, mint, destination and token program
+    assert_eq!(
+        instruction.accounts[5..],
+        [
+            AccountMeta::new(get_transfer_stats_address(&source, &mint), false),
+            AccountMeta::new(get_transfer_stats_address(&destination, &mint), false),
+            AccountMeta::new(get_mint_stats_address(&mint), false),
+            AccountMeta::new(get_owner_global_stats_shard_address(&owner), false),
+            AccountMeta::new_readonly(transfer_hook::ID, false),
+            AccountMeta::new_readonly(get_transfer_recorder_address(), false),
+        ]
+    );
+}
+
+#[test]
+fn transfer_extensions_mirrors_the_hook_program() {
+    use anchor_lang::Discriminator;
+    use transfer_extensions_client::transfer_extensions::instructions::{
+        RECORD_TRANSFER_DISCRIMINATOR, ROYALTY_DEBT_DISCRIMINATOR, TRANSFER_HOOK_PROGRAM_ID,
+    };
+
+    assert_eq!(TRANSFER_HOOK_PROGRAM_ID, transfer_hook::ID);
+    assert_eq!(
+        RECORD_TRANSFER_DISCRIMINATOR,
+        transfer_hook::instruction::RecordTransfer::DISCRIMINATOR
+    );
+    assert_eq!(
+        ROYALTY_DEBT_DISCRIMINATOR,
+        transfer_hook::state::RoyaltyDebt::DISCRIMINATOR
+    );
+}
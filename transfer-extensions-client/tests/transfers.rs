@@ -0,0 +1,1213 @@
+//! Counters and transfer instructions against the deployed programs, see `common`
+
+mod common;
+
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::{AccountSerialize, AnchorDeserialize};
+use anchor_spl::token_2022::spl_token_2022::{self, extension::ExtensionType};
+use common::*;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::sysvar;
+use spl_tlv_account_resolution::state::ExtraAccountMetaList;
+use spl_transfer_hook_interface::error::TransferHookError;
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+use transfer_extensions_client::instructions;
+use transfer_extensions_client::pda::*;
+use transfer_extensions_client::resolve::{
+    batch_transfers_with_extra_accounts, multi_transfers_with_extra_accounts, BatchTransfer,
+};
+use transfer_extensions_client::transfer_extensions;
+use transfer_extensions_client::transfer_extensions::errors::TransferExtensionsError;
+use transfer_extensions_client::transfer_extensions::instructions::{
+    NetPosition, SettlementLeg, TransferLeg,
+};
+use transfer_extensions_client::transfer_extensions::sol_sdk::FeeMode;
+use transfer_extensions_client::transfer_extensions::states::{
+    MintCounterIn, MintCounterOut, OwnerPolicy, WalletCounterIn, WalletCounterOut,
+    CURRENT_ACCOUNT_VERSION, RESERVED_SPACE,
+};
+use transfer_extensions_client::transfer_hook::{
+    errors::HookError,
+    instructions::{CounterLayout, GlobalStats},
+    state::{GlobalStatsShard, HookConfig, HookModules},
+};
+
+const TOKEN: u64 = 10u64.pow(DECIMALS as u32);
+
+#[tokio::test]
+async fn transfer_hook_updates_counters() {
+    let mut test = TestContext::start().await;
+    let HookMint {
+        mint,
+        wallets,
+        token_accounts: [source, destination, _],
+    } = test.create_hook_mint(0, 100 * TOKEN).await;
+
+    test.transfer(&source, &mint, &destination, &wallets[0], 3 * TOKEN)
+        .await
+        .unwrap();
+    test.transfer(&source, &mint, &destination, &wallets[0], 2 * TOKEN)
+        .await
+        .unwrap();
+
+    let source_stats = test.stats(&source, &mint).await;
+    assert_eq!(source_stats.transfers_out, 2);
+    assert_eq!(source_stats.volume_out, 5 * TOKEN as u128);
+    assert_eq!(source_stats.transfers_in, 0);
+    let destination_stats = test.stats(&destination, &mint).await;
+    assert_eq!(destination_stats.transfers_in, 2);
+    assert_eq!(destination_stats.volume_in, 5 * TOKEN as u128);
+    let mint_stats = test.stats(&mint, &mint).await;
+    assert_eq!(mint_stats.transfers_in, 2);
+    assert_eq!(mint_stats.transfers_out, 2);
+
+    let shard_address = get_owner_global_stats_shard_address(&wallets[0].pubkey());
+    let shard: GlobalStatsShard = test.state(&shard_address).await;
+    assert_eq!(shard.transfers_count, 2);
+    assert_eq!(shard.volume, 5 * TOKEN as u128);
+
+    let return_data = test
+        .simulate(&[instructions::aggregate_global_stats(&[shard.shard])], &[])
+        .await;
+    let global_stats = GlobalStats::try_from_slice(&return_data).unwrap();
+    assert_eq!(global_stats.shards_count, 1);
+    assert_eq!(global_stats.transfers_count, 2);
+    assert_eq!(global_stats.volume, 5 * TOKEN as u128);
+}
+
+#[tokio::test]
+async fn transfer_hook_updates_owner_counters() {
+    let mut test = TestContext::start().await;
+    let mint = test
+        .create_mint(&spl_token_2022::ID, &[ExtensionType::TransferHook], 0)
+        .await;
+    let sender = test.new_wallet().await;
+    let recipient = test.new_wallet().await;
+    let source = test
+        .create_token_account(&sender.pubkey(), &mint, &spl_token_2022::ID)
+        .await;
+    let destination = test
+        .create_token_account(&recipient.pubkey(), &mint, &spl_token_2022::ID)
+        .await;
+    test.initialize_stats(&[mint, sender.pubkey(), recipient.pubkey()], &mint)
+        .await;
+    test.initialize_shard_of(&sender.pubkey()).await;
+    test.initialize_meta_list(&mint, counters(CounterLayout::Owner))
+        .await;
+    test.mint_to(&mint, &source, &spl_token_2022::ID, 10 * TOKEN)
+        .await;
+
+    test.transfer(&source, &mint, &destination, &sender, TOKEN)
+        .await
+        .unwrap();
+
+    assert_eq!(test.stats(&sender.pubkey(), &mint).await.transfers_out, 1);
+    assert_eq!(test.stats(&recipient.pubkey(), &mint).await.transfers_in, 1);
+}
+
+#[tokio::test]
+async fn transfer_hook_updates_owner_counters_of_the_mint_once() {
+    let mut test = TestContext::start().await;
+    let mint = test
+        .create_mint(&spl_token_2022::ID, &[ExtensionType::TransferHook], 0)
+        .await;
+    let sender = test.new_wallet().await;
+    let source = test
+        .create_token_account(&sender.pubkey(), &mint, &spl_token_2022::ID)
+        .await;
+    // owned by the mint, its owner stats are the mint stats
+    let destination = test
+        .create_token_account(&mint, &mint, &spl_token_2022::ID)
+        .await;
+    test.initialize_stats(&[mint, sender.pubkey()], &mint).await;
+    test.initialize_shard_of(&sender.pubkey()).await;
+    test.initialize_meta_list(&mint, counters(CounterLayout::Owner))
+        .await;
+    test.mint_to(&mint, &source, &spl_token_2022::ID, 10 * TOKEN)
+        .await;
+
+    test.transfer(&source, &mint, &destination, &sender, TOKEN)
+        .await
+        .unwrap();
+
+    let mint_stats = test.stats(&mint, &mint).await;
+    assert_eq!((mint_stats.transfers_out, mint_stats.transfers_in), (1, 2));
+    assert_eq!(mint_stats.volume_in, 2 * TOKEN as u128);
+}
+
+#[tokio::test]
+async fn owner_policy_limits_transfers_of_the_owner() {
+    let mut test = TestContext::start().await;
+    let mint = test
+        .create_mint(&spl_token_2022::ID, &[ExtensionType::TransferHook], 0)
+        .await;
+    let sender = test.new_wallet().await;
+    let recipient = test.new_wallet().await;
+    let source = test
+        .create_token_account(&sender.pubkey(), &mint, &spl_token_2022::ID)
+        .await;
+    let destination = test
+        .create_token_account(&recipient.pubkey(), &mint, &spl_token_2022::ID)
+        .await;
+    test.initialize_stats(&[mint, source, destination], &mint)
+        .await;
+    test.initialize_shard_of(&sender.pubkey()).await;
+    test.initialize_shard_of(&recipient.pubkey()).await;
+    test.initialize_meta_list(
+        &mint,
+        HookModules {
+            counters: Some(CounterLayout::TokenAccount),
+            royalties: false,
+            owner_policy: true,
+            allowlist: false,
+            lockups: false,
+        },
+    )
+    .await;
+    test.mint_to(&mint, &source, &spl_token_2022::ID, 10 * TOKEN)
+        .await;
+
+    let payer = test.payer();
+    assert_eq!(
+        custom_error(
+            test.process(
+                &[instructions::initialize_owner_policy(
+                    &sender.pubkey(),
+                    &sender.pubkey(),
+                    &mint,
+                    u64::MAX,
+                )],
+                &[&sender],
+            )
+            .await
+        ),
+        transfer_extensions_error(TransferExtensionsError::NotMintAuthority)
+    );
+    test.process(
+        &[instructions::initialize_owner_policy(
+            &payer,
+            &sender.pubkey(),
+            &mint,
+            2 * TOKEN,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    test.transfer(&source, &mint, &destination, &sender, 2 * TOKEN)
+        .await
+        .unwrap();
+    assert_eq!(
+        custom_error(
+            test.transfer(&source, &mint, &destination, &sender, 3 * TOKEN)
+                .await
+        ),
+        transfer_extensions_error(TransferExtensionsError::OwnerPolicyLimitExceeded)
+    );
+    // wallets without a policy aren't limited
+    test.transfer(&destination, &mint, &source, &recipient, 2 * TOKEN)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        custom_error(
+            test.process(
+                &[instructions::update_owner_policy(
+                    &sender.pubkey(),
+                    &sender.pubkey(),
+                    &mint,
+                    u64::MAX,
+                )],
+                &[&sender],
+            )
+            .await
+        ),
+        u32::from(anchor_lang::error::ErrorCode::ConstraintHasOne)
+    );
+    test.process(
+        &[instructions::update_owner_policy(
+            &payer,
+            &sender.pubkey(),
+            &mint,
+            5 * TOKEN,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    test.transfer(&source, &mint, &destination, &sender, 3 * TOKEN)
+        .await
+        .unwrap();
+
+    let policy: OwnerPolicy = test
+        .state(&get_owner_policy_address(&sender.pubkey(), &mint))
+        .await;
+    assert_eq!(policy.max_transfer_amount, 5 * TOKEN);
+    assert_eq!(test.stats(&source, &mint).await.transfers_out, 2);
+}
+
+#[tokio::test]
+async fn allowlist_limits_the_recipients() {
+    let mut test = TestContext::start().await;
+    let mint = test
+        .create_mint(&spl_token_2022::ID, &[ExtensionType::TransferHook], 0)
+        .await;
+    let sender = test.new_wallet().await;
+    let recipient = test.new_wallet().await;
+    let source = test
+        .create_token_account(&sender.pubkey(), &mint, &spl_token_2022::ID)
+        .await;
+    let destination = test
+        .create_token_account(&recipient.pubkey(), &mint, &spl_token_2022::ID)
+        .await;
+    test.initialize_meta_list(
+        &mint,
+        HookModules {
+            allowlist: true,
+            ..HookModules::default()
+        },
+    )
+    .await;
+    test.mint_to(&mint, &source, &spl_token_2022::ID, 10 * TOKEN)
+        .await;
+
+    assert_eq!(
+        custom_error(
+            test.transfer(&source, &mint, &destination, &sender, TOKEN)
+                .await
+        ),
+        u32::from(HookError::DestinationNotAllowlisted)
+    );
+    assert_eq!(
+        custom_error(
+            test.process(
+                &[instructions::add_to_allowlist(
+                    &sender.pubkey(),
+                    &recipient.pubkey(),
+                    &mint,
+                )],
+                &[&sender],
+            )
+            .await
+        ),
+        u32::from(anchor_lang::error::ErrorCode::ConstraintHasOne)
+    );
+
+    let payer = test.payer();
+    test.process(
+        &[instructions::add_to_allowlist(
+            &payer,
+            &recipient.pubkey(),
+            &mint,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    test.transfer(&source, &mint, &destination, &sender, TOKEN)
+        .await
+        .unwrap();
+    assert_eq!(test.balance(&destination).await, TOKEN);
+
+    test.process(
+        &[instructions::remove_from_allowlist(
+            &payer,
+            &recipient.pubkey(),
+            &mint,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        custom_error(
+            test.transfer(&source, &mint, &destination, &sender, TOKEN)
+                .await
+        ),
+        u32::from(HookError::DestinationNotAllowlisted)
+    );
+}
+
+#[tokio::test]
+async fn lockup_keeps_the_locked_amount_until_the_release() {
+    let mut test = TestContext::start().await;
+    let mint = test
+        .create_mint(&spl_token_2022::ID, &[ExtensionType::TransferHook], 0)
+        .await;
+    let sender = test.new_wallet().await;
+    let recipient = test.new_wallet().await;
+    let source = test
+        .create_token_account(&sender.pubkey(), &mint, &spl_token_2022::ID)
+        .await;
+    let destination = test
+        .create_token_account(&recipient.pubkey(), &mint, &spl_token_2022::ID)
+        .await;
+    test.initialize_meta_list(
+        &mint,
+        HookModules {
+            lockups: true,
+            ..HookModules::default()
+        },
+    )
+    .await;
+    test.mint_to(&mint, &source, &spl_token_2022::ID, 10 * TOKEN)
+        .await;
+
+    let now = test.clock().await.unix_timestamp;
+    assert_eq!(
+        custom_error(
+            test.process(
+                &[instructions::set_lockup(
+                    &sender.pubkey(),
+                    &sender.pubkey(),
+                    &mint,
+                    0,
+                    now,
+                )],
+                &[&sender],
+            )
+            .await
+        ),
+        u32::from(anchor_lang::error::ErrorCode::ConstraintHasOne)
+    );
+    let payer = test.payer();
+    test.process(
+        &[instructions::set_lockup(
+            &payer,
+            &sender.pubkey(),
+            &mint,
+            8 * TOKEN,
+            now + 3600,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        custom_error(
+            test.transfer(&source, &mint, &destination, &sender, 3 * TOKEN)
+                .await
+        ),
+        u32::from(HookError::TokensLocked)
+    );
+    test.transfer(&source, &mint, &destination, &sender, 2 * TOKEN)
+        .await
+        .unwrap();
+    // wallets without a lockup aren't locked
+    test.transfer(&destination, &mint, &source, &recipient, 2 * TOKEN)
+        .await
+        .unwrap();
+
+    // released
+    test.process(
+        &[instructions::set_lockup(
+            &payer,
+            &sender.pubkey(),
+            &mint,
+            8 * TOKEN,
+            now - 1,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    test.transfer(&source, &mint, &destination, &sender, 5 * TOKEN)
+        .await
+        .unwrap();
+    assert_eq!(test.balance(&source).await, 5 * TOKEN);
+}
+
+#[tokio::test]
+async fn pause_only_mint_is_paused_by_the_mint_authority() {
+    let mut test = TestContext::start().await;
+    let mint = test
+        .create_mint(&spl_token_2022::ID, &[ExtensionType::TransferHook], 0)
+        .await;
+    let sender = test.new_wallet().await;
+    let recipient = test.new_wallet().await;
+    let source = test
+        .create_token_account(&sender.pubkey(), &mint, &spl_token_2022::ID)
+        .await;
+    let destination = test
+        .create_token_account(&recipient.pubkey(), &mint, &spl_token_2022::ID)
+        .await;
+
+    assert_eq!(
+        custom_error(
+            test.process(
+                &[instructions::initialize_extra_account_meta_list(
+                    &sender.pubkey(),
+                    &mint,
+                    HookModules::default(),
+                )],
+                &[&sender],
+            )
+            .await
+        ),
+        u32::from(HookError::NotMintAuthority)
+    );
+    // no stats, royalty or policy accounts are needed without modules
+    test.initialize_meta_list(&mint, HookModules::default())
+        .await;
+    test.mint_to(&mint, &source, &spl_token_2022::ID, 10 * TOKEN)
+        .await;
+    test.transfer(&source, &mint, &destination, &sender, TOKEN)
+        .await
+        .unwrap();
+
+    let payer = test.payer();
+    assert_eq!(
+        custom_error(
+            test.process(
+                &[instructions::set_paused(&sender.pubkey(), &mint, true)],
+                &[&sender],
+            )
+            .await
+        ),
+        u32::from(anchor_lang::error::ErrorCode::ConstraintHasOne)
+    );
+    test.process(&[instructions::set_paused(&payer, &mint, true)], &[])
+        .await
+        .unwrap();
+    assert_eq!(
+        custom_error(
+            test.transfer(&source, &mint, &destination, &sender, TOKEN)
+                .await
+        ),
+        u32::from(HookError::MintPaused)
+    );
+
+    test.process(&[instructions::set_paused(&payer, &mint, false)], &[])
+        .await
+        .unwrap();
+    test.transfer(&source, &mint, &destination, &sender, TOKEN)
+        .await
+        .unwrap();
+    assert_eq!(test.balance(&destination).await, 2 * TOKEN);
+}
+
+#[tokio::test]
+async fn meta_list_is_reconfigured_by_the_mint_authority() {
+    let mut test = TestContext::start().await;
+    let payer = test.payer();
+    let mint = test
+        .create_mint(&spl_token_2022::ID, &[ExtensionType::TransferHook], 0)
+        .await;
+    let sender = test.new_wallet().await;
+    let recipient = test.new_wallet().await;
+    let source = test
+        .create_token_account(&sender.pubkey(), &mint, &spl_token_2022::ID)
+        .await;
+    let destination = test
+        .create_token_account(&recipient.pubkey(), &mint, &spl_token_2022::ID)
+        .await;
+    test.mint_to(&mint, &source, &spl_token_2022::ID, 10 * TOKEN)
+        .await;
+
+    // a meta list initialized before the hook config existed, without it at index 5
+    let mut legacy_meta_list = vec![0; ExtraAccountMetaList::size_of(0).unwrap()];
+    ExtraAccountMetaList::init::<ExecuteInstruction>(&mut legacy_meta_list, &[]).unwrap();
+    test.set_account(
+        &get_extra_account_metas_address(&mint),
+        &transfer_hook::ID,
+        legacy_meta_list,
+    )
+    .await;
+    assert!(test
+        .transfer(&source, &mint, &destination, &sender, TOKEN)
+        .await
+        .is_err());
+
+    assert_eq!(
+        custom_error(
+            test.process(
+                &[instructions::update_extra_account_meta_list(
+                    &sender.pubkey(),
+                    &mint,
+                    HookModules::default(),
+                )],
+                &[&sender],
+            )
+            .await
+        ),
+        u32::from(HookError::NotMintAuthority)
+    );
+    test.process(
+        &[instructions::update_extra_account_meta_list(
+            &payer,
+            &mint,
+            HookModules::default(),
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    test.transfer(&source, &mint, &destination, &sender, TOKEN)
+        .await
+        .unwrap();
+
+    // enabling the counters grows the list, the next transfer is recorded
+    test.initialize_stats(&[mint, source, destination], &mint)
+        .await;
+    test.initialize_shard_of(&sender.pubkey()).await;
+    test.process(
+        &[instructions::update_extra_account_meta_list(
+            &payer,
+            &mint,
+            counters(CounterLayout::TokenAccount),
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    test.transfer(&source, &mint, &destination, &sender, TOKEN)
+        .await
+        .unwrap();
+    assert_eq!(test.stats(&source, &mint).await.transfers_out, 1);
+    assert_eq!(test.stats(&destination, &mint).await.transfers_in, 1);
+    let hook_config: HookConfig = test.state(&get_hook_config_address(&mint)).await;
+    assert_eq!(hook_config.modules, counters(CounterLayout::TokenAccount));
+    assert_eq!(hook_config.version, CURRENT_ACCOUNT_VERSION);
+}
+
+#[tokio::test]
+async fn initializing_twice_fails() {
+    let mut test = TestContext::start().await;
+    let payer = test.payer();
+    let mint = test
+        .create_mint(&spl_token_2022::ID, &[ExtensionType::TransferHook], 0)
+        .await;
+    test.initialize_stats(&[mint], &mint).await;
+    test.initialize_meta_list(&mint, counters(CounterLayout::TokenAccount))
+        .await;
+    test.process(
+        &[instructions::initialize_global_stats_shard(&payer, 7)],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    // the system program refuses to create an account in use
+    for instruction in [
+        instructions::initialize_program_data(&payer),
+        instructions::initialize_global_stats_shard(&payer, 7),
+        instructions::initialize_transfer_stats(&payer, &mint, &mint),
+        instructions::initialize_extra_account_meta_list(
+            &payer,
+            &mint,
+            counters(CounterLayout::TokenAccount),
+        ),
+    ] {
+        assert_eq!(custom_error(test.process(&[instruction], &[]).await), 0);
+    }
+}
+
+#[tokio::test]
+async fn meta_list_of_legacy_token_mint_fails() {
+    let mut test = TestContext::start().await;
+    let payer = test.payer();
+    let mint = test.create_mint(&anchor_spl::token::ID, &[], 0).await;
+
+    let result = test
+        .process(
+            &[instructions::initialize_extra_account_meta_list(
+                &payer,
+                &mint,
+                counters(CounterLayout::TokenAccount),
+            )],
+            &[],
+        )
+        .await;
+    assert_eq!(
+        custom_error(result),
+        u32::from(anchor_lang::error::ErrorCode::ConstraintMintTokenProgram)
+    );
+}
+
+#[tokio::test]
+async fn migrating_current_account_fails() {
+    let mut test = TestContext::start().await;
+    let payer = test.payer();
+
+    let result = test
+        .process(
+            &[instructions::migrate_account(
+                &payer,
+                &get_global_program_data_address(),
+            )],
+            &[],
+        )
+        .await;
+    assert_eq!(
+        custom_error(result),
+        transfer_extensions_error(TransferExtensionsError::AccountAlreadyMigrated)
+    );
+}
+
+#[tokio::test]
+async fn legacy_stats_are_only_recorded_by_transfer_extensions() {
+    let mut test = TestContext::start().await;
+    let (mint, sender, [source, destination, _]) = create_legacy_mint(&mut test).await;
+
+    // the transfer recorder PDA can't sign outside of transfer_extensions
+    let mut instruction = instructions::record_transfer(
+        &source,
+        &destination,
+        &get_transfer_stats_address(&source, &mint),
+        &get_transfer_stats_address(&destination, &mint),
+        &mint,
+        &get_owner_global_stats_shard_address(&sender.pubkey()),
+        TOKEN,
+    );
+    for meta in instruction.accounts.iter_mut() {
+        meta.is_signer = false;
+    }
+    assert_eq!(
+        custom_error(test.process(&[instruction], &[]).await),
+        u32::from(anchor_lang::error::ErrorCode::AccountNotSigner)
+    );
+    assert_eq!(test.stats(&mint, &mint).await.transfers_in, 0);
+}
+
+/// Counter as stored by the removed counter init instructions,
+/// `legacy` ones were created before the `version` field
+fn legacy_counter_data<T: AccountSerialize>(counter: &T, legacy: bool) -> Vec<u8> {
+    let mut data = vec![];
+    counter.try_serialize(&mut data).unwrap();
+    if legacy {
+        data.truncate(8 + 8 + 32);
+    }
+    data
+}
+
+#[tokio::test]
+async fn legacy_counters_migrate_into_transfer_stats() {
+    let mut test = TestContext::start().await;
+    let payer = test.payer();
+    let mint = test
+        .create_mint(&spl_token_2022::ID, &[ExtensionType::TransferHook], 0)
+        .await;
+    let owner = Pubkey::new_unique();
+    let token_account = test
+        .create_token_account(&owner, &mint, &spl_token_2022::ID)
+        .await;
+
+    let (counter_in, counter_out) = get_legacy_counter_addresses(&token_account);
+    let data = legacy_counter_data(
+        &WalletCounterIn {
+            transfers_count: 3,
+            owner,
+            version: CURRENT_ACCOUNT_VERSION,
+            reserved: [0; RESERVED_SPACE],
+        },
+        false,
+    );
+    test.set_account(&counter_in, &transfer_extensions::ID, data)
+        .await;
+    let data = legacy_counter_data(
+        &WalletCounterOut {
+            transfers_count: 5,
+            owner,
+            ..Default::default()
+        },
+        true,
+    );
+    test.set_account(&counter_out, &transfer_extensions::ID, data)
+        .await;
+
+    let (mint_counter_in, mint_counter_out) = get_legacy_counter_addresses(&mint);
+    let data = legacy_counter_data(
+        &MintCounterIn {
+            transfers_count: 8,
+            mint,
+            ..Default::default()
+        },
+        true,
+    );
+    test.set_account(&mint_counter_in, &transfer_extensions::ID, data)
+        .await;
+    let data = legacy_counter_data(
+        &MintCounterOut {
+            transfers_count: 8,
+            mint,
+            ..Default::default()
+        },
+        true,
+    );
+    test.set_account(&mint_counter_out, &transfer_extensions::ID, data)
+        .await;
+
+    test.process(
+        &[
+            instructions::migrate_legacy_counters(
+                &payer,
+                &token_account,
+                &mint,
+                &counter_in,
+                &counter_out,
+            ),
+            instructions::migrate_legacy_counters(
+                &payer,
+                &mint,
+                &mint,
+                &mint_counter_in,
+                &mint_counter_out,
+            ),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let stats = test.stats(&token_account, &mint).await;
+    assert_eq!(stats.transfers_in, 3);
+    assert_eq!(stats.transfers_out, 5);
+    assert_eq!(stats.volume_in, 0);
+    let mint_stats = test.stats(&mint, &mint).await;
+    assert_eq!(mint_stats.transfers_in, 8);
+    assert_eq!(mint_stats.transfers_out, 8);
+    // the rent of the counters went back to the mint authority
+    for counter in [counter_in, counter_out, mint_counter_in, mint_counter_out] {
+        assert!(test.account_data(&counter).await.is_none());
+    }
+}
+
+#[tokio::test]
+async fn migrating_counters_of_another_mint_fails() {
+    let mut test = TestContext::start().await;
+    let payer = test.payer();
+    let mut mints = vec![];
+    for _ in 0..2 {
+        mints.push(
+            test.create_mint(&spl_token_2022::ID, &[ExtensionType::TransferHook], 0)
+                .await,
+        );
+    }
+    let token_account = test
+        .create_token_account(&Pubkey::new_unique(), &mints[0], &spl_token_2022::ID)
+        .await;
+    let (counter_in, counter_out) = get_legacy_counter_addresses(&token_account);
+    for counter in [counter_in, counter_out] {
+        let data = legacy_counter_data(&WalletCounterIn::default(), true);
+        test.set_account(&counter, &transfer_extensions::ID, data)
+            .await;
+    }
+
+    // the hook authority of the second mint only closes counters of the second mint
+    let result = test
+        .process(
+            &[instructions::migrate_legacy_counters(
+                &payer,
+                &token_account,
+                &mints[1],
+                &counter_in,
+                &counter_out,
+            )],
+            &[],
+        )
+        .await;
+    assert_eq!(
+        custom_error(result),
+        transfer_extensions_error(TransferExtensionsError::InvalidLegacyCounter)
+    );
+}
+
+#[tokio::test]
+async fn multi_transfers_with_net_and_gross_fee_modes() {
+    let mut test = TestContext::start().await;
+    let fee_bps = 100;
+    let HookMint {
+        mint,
+        wallets,
+        token_accounts: [source, destination_1, destination_2],
+    } = test.create_hook_mint(fee_bps, 100 * TOKEN).await;
+    let signer = wallets[0].pubkey();
+
+    let accounts = test
+        .snapshot(&[
+            mint,
+            get_extra_account_metas_address(&mint),
+            source,
+            sysvar::clock::ID,
+        ])
+        .await;
+    let instruction = multi_transfers_with_extra_accounts(
+        &accounts,
+        &signer,
+        &source,
+        &destination_1,
+        &destination_2,
+        &mint,
+        &spl_token_2022::ID,
+        TOKEN,
+        2 * TOKEN,
+        FeeMode::Net,
+        FeeMode::Gross,
+    )
+    .unwrap();
+    test.process(&[instruction], &[&wallets[0]]).await.unwrap();
+
+    assert_eq!(test.balance(&destination_1).await, TOKEN);
+    let fee_2 = 2 * TOKEN * fee_bps as u64 / 10_000;
+    assert_eq!(test.balance(&destination_2).await, 2 * TOKEN - fee_2);
+    assert_eq!(test.stats(&source, &mint).await.transfers_out, 2);
+    assert_eq!(test.stats(&destination_1, &mint).await.transfers_in, 1);
+    assert_eq!(test.stats(&destination_2, &mint).await.transfers_in, 1);
+}
+
+#[tokio::test]
+async fn multi_transfers_of_zero_fails() {
+    let mut test = TestContext::start().await;
+    let HookMint {
+        mint,
+        wallets,
+        token_accounts: [source, destination_1, destination_2],
+    } = test.create_hook_mint(0, 100 * TOKEN).await;
+    let signer = wallets[0].pubkey();
+
+    for (amount1, amount2) in [(0, TOKEN), (TOKEN, 0)] {
+        let instruction = instructions::multi_transfers(
+            &signer,
+            &source,
+            &destination_1,
+            &destination_2,
+            &mint,
+            &spl_token_2022::ID,
+            amount1,
+            amount2,
+            FeeMode::Gross,
+            FeeMode::Gross,
+        );
+        assert_eq!(
+            custom_error(test.process(&[instruction], &[&wallets[0]]).await),
+            transfer_extensions_error(TransferExtensionsError::AmountMustBeGreaterThanZero)
+        );
+    }
+}
+
+#[tokio::test]
+async fn multi_transfers_without_extra_accounts_fails() {
+    let mut test = TestContext::start().await;
+    let HookMint {
+        mint,
+        wallets,
+        token_accounts: [source, destination_1, destination_2],
+    } = test.create_hook_mint(0, 100 * TOKEN).await;
+
+    let instruction = instructions::multi_transfers(
+        &wallets[0].pubkey(),
+        &source,
+        &destination_1,
+        &destination_2,
+        &mint,
+        &spl_token_2022::ID,
+        TOKEN,
+        TOKEN,
+        FeeMode::Gross,
+        FeeMode::Gross,
+    );
+    assert_eq!(
+        custom_error(test.process(&[instruction], &[&wallets[0]]).await),
+        TransferHookError::IncorrectAccount as u32
+    );
+    assert_eq!(test.balance(&source).await, 100 * TOKEN);
+    assert_eq!(test.stats(&source, &mint).await.transfers_out, 0);
+}
+
+#[tokio::test]
+async fn multi_transfers_heap_transfers_the_first_leg() {
+    let mut test = TestContext::start().await;
+    let HookMint {
+        mint,
+        wallets,
+        token_accounts: [source, destination_1, destination_2],
+    } = test.create_hook_mint(0, 100 * TOKEN).await;
+    let signer = wallets[0].pubkey();
+
+    let mut instruction = instructions::multi_transfers_heap(
+        &signer,
+        &source,
+        &destination_1,
+        &destination_2,
+        &mint,
+        &spl_token_2022::ID,
+        TOKEN,
+        TOKEN,
+    );
+    test.add_hook_accounts(
+        &mut instruction,
+        &mint,
+        &[(source, destination_1, signer, TOKEN)],
+    )
+    .await;
+    test.process(&[instruction], &[&wallets[0]]).await.unwrap();
+
+    assert_eq!(test.balance(&destination_1).await, TOKEN);
+    assert_eq!(test.balance(&destination_2).await, 0);
+    assert_eq!(test.stats(&source, &mint).await.transfers_out, 1);
+}
+
+/// Legacy Token mint without hook, with three token accounts and their stats,
+/// the first one funded and owned by the returned wallet
+async fn create_legacy_mint(test: &mut TestContext) -> (Pubkey, Keypair, [Pubkey; 3]) {
+    let token_program = anchor_spl::token::ID;
+    let mint = test.create_mint(&token_program, &[], 0).await;
+    let sender = test.new_wallet().await;
+    let mut token_accounts = vec![];
+    for owner in [sender.pubkey(), Pubkey::new_unique(), Pubkey::new_unique()] {
+        token_accounts.push(
+            test.create_token_account(&owner, &mint, &token_program)
+                .await,
+        );
+    }
+    let [source, destination_1, destination_2]: [Pubkey; 3] = token_accounts.try_into().unwrap();
+    test.initialize_stats(&[mint, source, destination_1, destination_2], &mint)
+        .await;
+    test.initialize_shard_of(&sender.pubkey()).await;
+    test.mint_to(&mint, &source, &token_program, 100 * TOKEN)
+        .await;
+    (mint, sender, [source, destination_1, destination_2])
+}
+
+#[tokio::test]
+async fn legacy_token_multi_transfers_record_stats() {
+    let mut test = TestContext::start().await;
+    let token_program = anchor_spl::token::ID;
+    let (mint, sender, [source, destination_1, destination_2]) =
+        create_legacy_mint(&mut test).await;
+
+    // no hook fires, the stats are passed in remaining accounts
+    let instruction = instructions::multi_transfers(
+        &sender.pubkey(),
+        &source,
+        &destination_1,
+        &destination_2,
+        &mint,
+        &token_program,
+        TOKEN,
+        2 * TOKEN,
+        FeeMode::Gross,
+        FeeMode::Gross,
+    );
+    assert_eq!(
+        custom_error(test.process(&[instruction], &[&sender]).await),
+        transfer_extensions_error(TransferExtensionsError::MissingStatsAccounts)
+    );
+
+    let accounts = test.snapshot(&[source]).await;
+    let instruction = multi_transfers_with_extra_accounts(
+        &accounts,
+        &sender.pubkey(),
+        &source,
+        &destination_1,
+        &destination_2,
+        &mint,
+        &token_program,
+        TOKEN,
+        2 * TOKEN,
+        FeeMode::Gross,
+        FeeMode::Gross,
+    )
+    .unwrap();
+    test.process(&[instruction], &[&sender]).await.unwrap();
+
+    assert_eq!(test.balance(&destination_2).await, 2 * TOKEN);
+    let source_stats = test.stats(&source, &mint).await;
+    assert_eq!(source_stats.transfers_out, 2);
+    assert_eq!(source_stats.volume_out, 3 * TOKEN as u128);
+    let mint_stats = test.stats(&mint, &mint).await;
+    assert_eq!(mint_stats.transfers_in, 2);
+}
+
+#[tokio::test]
+async fn batch_transfers_of_several_mints() {
+    let mut test = TestContext::start().await;
+    let hook_mint = test.create_hook_mint(0, 100 * TOKEN).await;
+    let fee_mint = test.create_hook_mint(100, 100 * TOKEN).await;
+    // the fee mint has its own wallets, the signer must own the sources of both mints
+    let signer = &hook_mint.wallets[0];
+    let fee_source = test
+        .create_token_account(&signer.pubkey(), &fee_mint.mint, &spl_token_2022::ID)
+        .await;
+    test.initialize_stats(&[fee_source], &fee_mint.mint).await;
+    test.mint_to(
+        &fee_mint.mint,
+        &fee_source,
+        &spl_token_2022::ID,
+        100 * TOKEN,
+    )
+    .await;
+
+    let transfers = [
+        BatchTransfer {
+            source_account: hook_mint.token_accounts[0],
+            mint: hook_mint.mint,
+            destination_account: hook_mint.token_accounts[1],
+            token_program: spl_token_2022::ID,
+            amount: TOKEN,
+            fee_mode: FeeMode::Gross,
+        },
+        BatchTransfer {
+            source_account: fee_source,
+            mint: fee_mint.mint,
+            destination_account: fee_mint.token_accounts[1],
+            token_program: spl_token_2022::ID,
+            amount: 3 * TOKEN,
+            fee_mode: FeeMode::Net,
+        },
+    ];
+    let accounts = test
+        .snapshot(&[
+            hook_mint.mint,
+            get_extra_account_metas_address(&hook_mint.mint),
+            hook_mint.token_accounts[0],
+            fee_mint.mint,
+            get_extra_account_metas_address(&fee_mint.mint),
+            fee_source,
+            sysvar::clock::ID,
+        ])
+        .await;
+    let instruction =
+        batch_transfers_with_extra_accounts(&accounts, &signer.pubkey(), &transfers).unwrap();
+    test.process(&[instruction], &[signer]).await.unwrap();
+
+    assert_eq!(test.balance(&hook_mint.token_accounts[1]).await, TOKEN);
+    assert_eq!(test.balance(&fee_mint.token_accounts[1]).await, 3 * TOKEN);
+    assert_eq!(
+        test.stats(&hook_mint.token_accounts[0], &hook_mint.mint)
+            .await
+            .transfers_out,
+        1
+    );
+    assert_eq!(
+        test.stats(&fee_source, &fee_mint.mint).await.transfers_out,
+        1
+    );
+}
+
+#[tokio::test]
+async fn batch_transfers_with_invalid_legs_fail() {
+    let mut test = TestContext::start().await;
+    let HookMint {
+        mint,
+        wallets,
+        token_accounts: [source, destination, _],
+    } = test.create_hook_mint(0, 100 * TOKEN).await;
+    let signer = wallets[0].pubkey();
+    let leg = |mint_index, amount| TransferLeg {
+        source_index: 0,
+        mint_index,
+        destination_index: 2,
+        amount,
+        fee_mode: FeeMode::Gross,
+        stats_indexes: None,
+    };
+    let leg_accounts = vec![
+        AccountMeta::new(source, false),
+        AccountMeta::new_readonly(mint, false),
+        AccountMeta::new(destination, false),
+        AccountMeta::new_readonly(spl_token_2022::ID, false),
+        // owned by transfer_extensions, not by a token program
+        AccountMeta::new_readonly(get_mint_stats_address(&mint), false),
+    ];
+
+    for (legs, error) in [
+        (
+            vec![leg(1, TOKEN), leg(1, 0)],
+            TransferExtensionsError::AmountMustBeGreaterThanZero,
+        ),
+        (
+            vec![leg(4, TOKEN)],
+            TransferExtensionsError::InvalidTokenProgram,
+        ),
+        (
+            vec![leg(9, TOKEN)],
+            TransferExtensionsError::InvalidLegAccountIndex,
+        ),
+    ] {
+        let instruction = instructions::batch_transfers(&signer, legs, leg_accounts.clone());
+        assert_eq!(
+            custom_error(test.process(&[instruction], &[&wallets[0]]).await),
+            transfer_extensions_error(error)
+        );
+    }
+}
+
+#[tokio::test]
+async fn settle_legs_of_several_authorities() {
+    let mut test = TestContext::start().await;
+    let HookMint {
+        mint,
+        wallets,
+        token_accounts: [account_a, account_b, _],
+    } = test.create_hook_mint(0, 100 * TOKEN).await;
+    test.mint_to(&mint, &account_b, &spl_token_2022::ID, 100 * TOKEN)
+        .await;
+    let [wallet_a, wallet_b, _] = &wallets;
+
+    let leg = |authority_index, source_index, destination_index, amount| SettlementLeg {
+        authority_index,
+        transfer: TransferLeg {
+            source_index,
+            mint_index: 1,
+            destination_index,
+            amount,
+            fee_mode: FeeMode::Gross,
+            stats_indexes: None,
+        },
+    };
+    let legs = vec![leg(3, 0, 2, 2 * TOKEN), leg(4, 2, 0, TOKEN)];
+    let leg_accounts = vec![
+        AccountMeta::new(account_a, false),
+        AccountMeta::new_readonly(mint, false),
+        AccountMeta::new(account_b, false),
+        AccountMeta::new_readonly(wallet_a.pubkey(), true),
+        AccountMeta::new_readonly(wallet_b.pubkey(), true),
+        AccountMeta::new_readonly(spl_token_2022::ID, false),
+    ];
+    let net_positions = |delta: i64| {
+        vec![
+            NetPosition {
+                token_account_index: 0,
+                delta: -delta,
+            },
+            NetPosition {
+                token_account_index: 2,
+                delta,
+            },
+        ]
+    };
+    let hook_transfers = [
+        (account_a, account_b, wallet_a.pubkey(), 2 * TOKEN),
+        (account_b, account_a, wallet_b.pubkey(), TOKEN),
+    ];
+
+    let mut instruction = instructions::settle(
+        legs.clone(),
+        net_positions(2 * TOKEN as i64),
+        leg_accounts.clone(),
+    );
+    test.add_hook_accounts(&mut instruction, &mint, &hook_transfers)
+        .await;
+    assert_eq!(
+        custom_error(test.process(&[instruction], &[wallet_a, wallet_b]).await),
+        transfer_extensions_error(TransferExtensionsError::NetPositionMismatch)
+    );
+
+    let mut instruction =
+        instructions::settle(legs, net_positions(TOKEN as i64), leg_accounts.clone());
+    // settle has no accounts of its own, only the leg accounts
+    assert_eq!(instruction.accounts, leg_accounts);
+    test.add_hook_accounts(&mut instruction, &mint, &hook_transfers)
+        .await;
+    test.process(&[instruction], &[wallet_a, wallet_b])
+        .await
+        .unwrap();
+
+    assert_eq!(test.balance(&account_a).await, 99 * TOKEN);
+    assert_eq!(test.balance(&account_b).await, 101 * TOKEN);
+    let stats_a = test.stats(&account_a, &mint).await;
+    assert_eq!((stats_a.transfers_out, stats_a.transfers_in), (1, 1));
+}
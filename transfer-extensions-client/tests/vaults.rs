@@ -0,0 +1,826 @@
+//! Instructions holding tokens in PDA vaults against the deployed programs, see `common`
+
+mod common;
+
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::keccak;
+use anchor_spl::token_2022::spl_token_2022::{self, extension::ExtensionType};
+use common::*;
+use solana_sdk::signature::Signer;
+use transfer_extensions_client::instructions;
+use transfer_extensions_client::pda::*;
+use transfer_extensions_client::transfer_extensions::errors::TransferExtensionsError;
+use transfer_extensions_client::transfer_extensions::states::{
+    Distributor, Escrow, ExecutionTime, PaymentStream, ReleaseCondition, SplitShare,
+};
+use transfer_extensions_client::transfer_hook::{
+    instructions::CounterLayout,
+    state::{HookModules, RoyaltyDebt},
+};
+
+const TOKEN: u64 = 10u64.pow(DECIMALS as u32);
+
+#[tokio::test]
+async fn escrow_release_and_cancel() {
+    let mut test = TestContext::start().await;
+    let HookMint {
+        mint,
+        wallets,
+        token_accounts,
+    } = test.create_hook_mint(0, 100 * TOKEN).await;
+    let [maker, receiver, _] = &wallets;
+    let [maker_account, receiver_account, _] = token_accounts;
+    let token_program = spl_token_2022::ID;
+    let payer = test.payer();
+
+    let create = |id, amount, condition| {
+        instructions::create_escrow(
+            &maker.pubkey(),
+            &maker_account,
+            &mint,
+            &receiver.pubkey(),
+            &token_program,
+            id,
+            amount,
+            condition,
+        )
+    };
+    let timelock = ReleaseCondition::Timelock { release_at: 0 };
+    assert_eq!(
+        custom_error(test.process(&[create(1, 0, timelock)], &[maker]).await),
+        transfer_extensions_error(TransferExtensionsError::AmountMustBeGreaterThanZero)
+    );
+
+    // past the timelock anyone can release
+    let escrow = get_escrow_address(&maker.pubkey(), 1);
+    let vault = test.initialize_vault_stats(&escrow, &mint).await;
+    let mut instruction = create(1, 5 * TOKEN, timelock);
+    test.add_hook_accounts(
+        &mut instruction,
+        &mint,
+        &[(maker_account, vault, maker.pubkey(), 5 * TOKEN)],
+    )
+    .await;
+    test.process(&[instruction], &[maker]).await.unwrap();
+    assert_eq!(test.state::<Escrow>(&escrow).await.amount, 5 * TOKEN);
+    assert_eq!(test.balance(&vault).await, 5 * TOKEN);
+
+    let mut instruction = instructions::release_escrow(
+        &payer,
+        &maker.pubkey(),
+        1,
+        &receiver_account,
+        &mint,
+        &token_program,
+    );
+    test.add_hook_accounts(
+        &mut instruction,
+        &mint,
+        &[(vault, receiver_account, escrow, 5 * TOKEN)],
+    )
+    .await;
+    test.process(&[instruction], &[]).await.unwrap();
+    assert_eq!(test.balance(&receiver_account).await, 5 * TOKEN);
+    assert!(test.account_data(&escrow).await.is_none());
+    assert!(test.account_data(&vault).await.is_none());
+    let vault_stats = test.stats(&vault, &mint).await;
+    assert_eq!(
+        (vault_stats.transfers_in, vault_stats.transfers_out),
+        (1, 1)
+    );
+
+    // only the maker releases and only the receiver cancels without arbiter
+    let escrow = get_escrow_address(&maker.pubkey(), 2);
+    let vault = test.initialize_vault_stats(&escrow, &mint).await;
+    let mut instruction = create(2, TOKEN, ReleaseCondition::CounterpartySignature);
+    test.add_hook_accounts(
+        &mut instruction,
+        &mint,
+        &[(maker_account, vault, maker.pubkey(), TOKEN)],
+    )
+    .await;
+    test.process(&[instruction], &[maker]).await.unwrap();
+
+    let release = instructions::release_escrow(
+        &payer,
+        &maker.pubkey(),
+        2,
+        &receiver_account,
+        &mint,
+        &token_program,
+    );
+    assert_eq!(
+        custom_error(test.process(&[release], &[]).await),
+        transfer_extensions_error(TransferExtensionsError::EscrowReleaseNotAllowed)
+    );
+    let cancel = |authority: &Pubkey| {
+        instructions::cancel_escrow(
+            authority,
+            &maker.pubkey(),
+            2,
+            &maker_account,
+            &mint,
+            &token_program,
+        )
+    };
+    assert_eq!(
+        custom_error(test.process(&[cancel(&payer)], &[]).await),
+        transfer_extensions_error(TransferExtensionsError::EscrowCancelNotAllowed)
+    );
+
+    let mut instruction = cancel(&receiver.pubkey());
+    test.add_hook_accounts(
+        &mut instruction,
+        &mint,
+        &[(vault, maker_account, escrow, TOKEN)],
+    )
+    .await;
+    test.process(&[instruction], &[receiver]).await.unwrap();
+    assert_eq!(test.balance(&maker_account).await, 95 * TOKEN);
+    assert!(test.account_data(&escrow).await.is_none());
+}
+
+#[tokio::test]
+async fn stream_withdraw_and_cancel() {
+    let mut test = TestContext::start().await;
+    let HookMint {
+        mint,
+        wallets,
+        token_accounts,
+    } = test.create_hook_mint(0, 100 * TOKEN).await;
+    let [sender, recipient, _] = &wallets;
+    let [sender_account, recipient_account, _] = token_accounts;
+    let token_program = spl_token_2022::ID;
+    let rate_per_second = 1_000;
+    let now = test.clock().await.unix_timestamp;
+
+    let create = |id, start, end| {
+        instructions::create_stream(
+            &sender.pubkey(),
+            &sender_account,
+            &mint,
+            &recipient.pubkey(),
+            &token_program,
+            id,
+            rate_per_second,
+            start,
+            end,
+        )
+    };
+    assert_eq!(
+        custom_error(test.process(&[create(1, now, now)], &[sender]).await),
+        transfer_extensions_error(TransferExtensionsError::InvalidStreamSchedule)
+    );
+
+    // a running stream and one that has not started
+    for (id, start, end) in [(1, now - 100, now + 100), (2, now + 10_000, now + 10_100)] {
+        let stream = get_payment_stream_address(&sender.pubkey(), id);
+        let vault = test.initialize_vault_stats(&stream, &mint).await;
+        let mut instruction = create(id, start, end);
+        test.add_hook_accounts(
+            &mut instruction,
+            &mint,
+            &[(sender_account, vault, sender.pubkey(), 0)],
+        )
+        .await;
+        test.process(&[instruction], &[sender]).await.unwrap();
+        assert_eq!(
+            test.balance(&vault).await,
+            rate_per_second * (end - start) as u64
+        );
+    }
+
+    let withdraw = |id| {
+        instructions::withdraw_from_stream(
+            &recipient.pubkey(),
+            &sender.pubkey(),
+            id,
+            &recipient_account,
+            &mint,
+            &token_program,
+        )
+    };
+    let stream = get_payment_stream_address(&sender.pubkey(), 1);
+    let vault = get_vault_address(&stream, &mint, &token_program);
+    let mut instruction = withdraw(1);
+    test.add_hook_accounts(
+        &mut instruction,
+        &mint,
+        &[(vault, recipient_account, stream, 0)],
+    )
+    .await;
+    test.process(&[instruction], &[recipient]).await.unwrap();
+    let withdrawn = test.state::<PaymentStream>(&stream).await.withdrawn;
+    assert!(withdrawn >= 100 * rate_per_second);
+    assert_eq!(test.balance(&recipient_account).await, withdrawn);
+
+    let stream = get_payment_stream_address(&sender.pubkey(), 2);
+    let vault = get_vault_address(&stream, &mint, &token_program);
+    let mut instruction = withdraw(2);
+    test.add_hook_accounts(
+        &mut instruction,
+        &mint,
+        &[(vault, recipient_account, stream, 0)],
+    )
+    .await;
+    assert_eq!(
+        custom_error(test.process(&[instruction], &[recipient]).await),
+        transfer_extensions_error(TransferExtensionsError::NothingToWithdraw)
+    );
+
+    // nothing streamed yet, the whole vault goes back to the sender
+    let sender_balance = test.balance(&sender_account).await;
+    let mut instruction = instructions::cancel_stream(
+        &sender.pubkey(),
+        2,
+        &sender_account,
+        &recipient_account,
+        &mint,
+        &token_program,
+    );
+    test.add_hook_accounts(
+        &mut instruction,
+        &mint,
+        &[(vault, sender_account, stream, 0)],
+    )
+    .await;
+    test.process(&[instruction], &[sender]).await.unwrap();
+    assert_eq!(
+        test.balance(&sender_account).await,
+        sender_balance + 100 * rate_per_second
+    );
+    assert!(test.account_data(&stream).await.is_none());
+    assert!(test.account_data(&vault).await.is_none());
+}
+
+#[tokio::test]
+async fn scheduled_transfer_executes_when_due_and_cancels() {
+    let mut test = TestContext::start().await;
+    let HookMint {
+        mint,
+        wallets,
+        token_accounts,
+    } = test.create_hook_mint(0, 100 * TOKEN).await;
+    let creator = &wallets[0];
+    let [creator_account, destination, cranker_account] = token_accounts;
+    let token_program = spl_token_2022::ID;
+    let cranker = test.payer();
+    let (amount, tip) = (3 * TOKEN, TOKEN / 100);
+    let due_slot = test.clock().await.slot + 1_000;
+
+    let scheduled_transfer = get_scheduled_transfer_address(&creator.pubkey(), 1);
+    let vault = test
+        .initialize_vault_stats(&scheduled_transfer, &mint)
+        .await;
+    let mut instruction = instructions::create_scheduled_transfer(
+        &creator.pubkey(),
+        &creator_account,
+        &destination,
+        &mint,
+        &token_program,
+        1,
+        amount,
+        tip,
+        ExecutionTime::Slot { slot: due_slot },
+    );
+    test.add_hook_accounts(
+        &mut instruction,
+        &mint,
+        &[(creator_account, vault, creator.pubkey(), amount + tip)],
+    )
+    .await;
+    test.process(&[instruction], &[creator]).await.unwrap();
+
+    // tokens sent to the vault on top of the amount and tip go back to the creator
+    let extra = TOKEN / 10;
+    test.transfer(&creator_account, &mint, &vault, creator, extra)
+        .await
+        .unwrap();
+    let creator_balance = test.balance(&creator_account).await;
+
+    // the cranker neither owns the vault nor the destination
+    let mut instruction = instructions::execute_scheduled_transfer(
+        &cranker,
+        &creator.pubkey(),
+        1,
+        &destination,
+        &cranker_account,
+        &creator_account,
+        &mint,
+        &token_program,
+    );
+    test.add_hook_accounts(
+        &mut instruction,
+        &mint,
+        &[
+            (vault, destination, scheduled_transfer, amount),
+            (vault, cranker_account, scheduled_transfer, tip),
+            (vault, creator_account, scheduled_transfer, extra),
+        ],
+    )
+    .await;
+    assert_eq!(
+        custom_error(test.process(&[instruction.clone()], &[]).await),
+        transfer_extensions_error(TransferExtensionsError::ScheduledTransferNotDue)
+    );
+
+    test.context.warp_to_slot(due_slot).unwrap();
+    test.process(&[instruction], &[]).await.unwrap();
+    assert_eq!(test.balance(&destination).await, amount);
+    assert_eq!(test.balance(&cranker_account).await, tip);
+    assert_eq!(
+        test.balance(&creator_account).await,
+        creator_balance + extra
+    );
+    assert!(test.account_data(&scheduled_transfer).await.is_none());
+    assert!(test.account_data(&vault).await.is_none());
+    assert_eq!(test.stats(&vault, &mint).await.transfers_out, 3);
+
+    // the creator cancels a transfer which isn't due yet and gets the vault back
+    let scheduled_transfer = get_scheduled_transfer_address(&creator.pubkey(), 2);
+    let vault = test
+        .initialize_vault_stats(&scheduled_transfer, &mint)
+        .await;
+    let mut instruction = instructions::create_scheduled_transfer(
+        &creator.pubkey(),
+        &creator_account,
+        &destination,
+        &mint,
+        &token_program,
+        2,
+        amount,
+        tip,
+        ExecutionTime::Slot {
+            slot: due_slot + 1_000,
+        },
+    );
+    test.add_hook_accounts(
+        &mut instruction,
+        &mint,
+        &[(creator_account, vault, creator.pubkey(), amount + tip)],
+    )
+    .await;
+    test.process(&[instruction], &[creator]).await.unwrap();
+    let mut instruction = instructions::cancel_scheduled_transfer(
+        &creator.pubkey(),
+        2,
+        &creator_account,
+        &mint,
+        &token_program,
+    );
+    test.add_hook_accounts(
+        &mut instruction,
+        &mint,
+        &[(vault, creator_account, scheduled_transfer, amount + tip)],
+    )
+    .await;
+    test.process(&[instruction], &[creator]).await.unwrap();
+    assert_eq!(
+        test.balance(&creator_account).await,
+        creator_balance + extra
+    );
+    assert!(test.account_data(&scheduled_transfer).await.is_none());
+    assert!(test.account_data(&vault).await.is_none());
+}
+
+#[tokio::test]
+async fn splitter_distributes_by_shares() {
+    let mut test = TestContext::start().await;
+    let HookMint {
+        mint,
+        wallets,
+        token_accounts,
+    } = test.create_hook_mint(0, 100 * TOKEN).await;
+    let authority = &wallets[0];
+    let [source, recipient_1, recipient_2] = token_accounts;
+    let token_program = spl_token_2022::ID;
+    let share = |token_account, bps| SplitShare { token_account, bps };
+
+    for (shares, remainder_index, error) in [
+        (
+            vec![share(recipient_1, 3333), share(recipient_2, 6666)],
+            0,
+            TransferExtensionsError::InvalidSplitShares,
+        ),
+        (
+            vec![share(recipient_1, 3333), share(recipient_2, 6667)],
+            2,
+            TransferExtensionsError::InvalidRemainderIndex,
+        ),
+    ] {
+        let instruction = instructions::initialize_splitter(
+            &authority.pubkey(),
+            &mint,
+            1,
+            shares,
+            remainder_index,
+        );
+        assert_eq!(
+            custom_error(test.process(&[instruction], &[authority]).await),
+            transfer_extensions_error(error)
+        );
+    }
+    test.process(
+        &[instructions::initialize_splitter(
+            &authority.pubkey(),
+            &mint,
+            1,
+            vec![share(recipient_1, 3333), share(recipient_2, 6667)],
+            0,
+        )],
+        &[authority],
+    )
+    .await
+    .unwrap();
+
+    let amount = 1_000_000_001;
+    let mut instruction = instructions::distribute(
+        &authority.pubkey(),
+        &get_splitter_address(&authority.pubkey(), 1),
+        amount,
+        vec![
+            AccountMeta::new(source, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new(recipient_1, false),
+            AccountMeta::new(recipient_2, false),
+            AccountMeta::new_readonly(token_program, false),
+        ],
+    );
+    test.add_hook_accounts(
+        &mut instruction,
+        &mint,
+        &[
+            (source, recipient_1, authority.pubkey(), 0),
+            (source, recipient_2, authority.pubkey(), 0),
+        ],
+    )
+    .await;
+    test.process(&[instruction], &[authority]).await.unwrap();
+
+    // shares are rounded down, the remainder goes to the first recipient
+    let share_2 = amount * 6667 / 10_000;
+    assert_eq!(test.balance(&recipient_2).await, share_2);
+    assert_eq!(test.balance(&recipient_1).await, amount - share_2);
+    assert_eq!(test.stats(&source, &mint).await.transfers_out, 2);
+}
+
+fn leaf(index: u64, claimant: &Pubkey, amount: u64) -> [u8; 32] {
+    keccak::hashv(&[
+        &index.to_le_bytes(),
+        claimant.as_ref(),
+        &amount.to_le_bytes(),
+    ])
+    .0
+}
+
+fn parent(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+    keccak::hashv(&[&a.min(b), &a.max(b)]).0
+}
+
+#[tokio::test]
+async fn distributor_claims_with_merkle_proofs() {
+    let mut test = TestContext::start().await;
+    let HookMint {
+        mint,
+        wallets,
+        token_accounts,
+    } = test.create_hook_mint(0, 100 * TOKEN).await;
+    let [funder, claimant_1, claimant_2] = &wallets;
+    let [funder_account, claimant_1_account, claimant_2_account] = token_accounts;
+    let token_program = spl_token_2022::ID;
+    let payer = test.payer();
+
+    let claims = [
+        (claimant_1.pubkey(), 2 * TOKEN),
+        (claimant_2.pubkey(), TOKEN),
+    ];
+    let leaves = [
+        leaf(0, &claims[0].0, claims[0].1),
+        leaf(1, &claims[1].0, claims[1].1),
+    ];
+    test.process(
+        &[instructions::initialize_distributor(
+            &payer,
+            &mint,
+            &token_program,
+            1,
+            parent(leaves[0], leaves[1]),
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    let distributor = get_distributor_address(&payer, 1);
+    test.process(
+        &[instructions::initialize_claim_bitmap(
+            &payer,
+            &distributor,
+            0,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    let vault = test.initialize_vault_stats(&distributor, &mint).await;
+    test.transfer(&funder_account, &mint, &vault, funder, 3 * TOKEN)
+        .await
+        .unwrap();
+
+    let claim = |claimant: &Pubkey, claimant_account, index, amount, proof| {
+        instructions::claim(
+            claimant,
+            &distributor,
+            claimant_account,
+            &mint,
+            &token_program,
+            index,
+            amount,
+            proof,
+        )
+    };
+    let mut instruction = claim(
+        &claims[0].0,
+        &claimant_1_account,
+        0,
+        claims[0].1,
+        vec![leaves[1]],
+    );
+    test.add_hook_accounts(
+        &mut instruction,
+        &mint,
+        &[(vault, claimant_1_account, distributor, claims[0].1)],
+    )
+    .await;
+    test.process(&[instruction.clone()], &[claimant_1])
+        .await
+        .unwrap();
+    assert_eq!(test.balance(&claimant_1_account).await, claims[0].1);
+    assert_eq!(
+        test.state::<Distributor>(&distributor).await.claimed_count,
+        1
+    );
+
+    assert_eq!(
+        custom_error(test.process(&[instruction], &[claimant_1]).await),
+        transfer_extensions_error(TransferExtensionsError::AlreadyClaimed)
+    );
+    let mut instruction = claim(
+        &claims[1].0,
+        &claimant_2_account,
+        1,
+        2 * claims[1].1,
+        vec![leaves[0]],
+    );
+    test.add_hook_accounts(
+        &mut instruction,
+        &mint,
+        &[(vault, claimant_2_account, distributor, 2 * claims[1].1)],
+    )
+    .await;
+    assert_eq!(
+        custom_error(test.process(&[instruction], &[claimant_2]).await),
+        transfer_extensions_error(TransferExtensionsError::InvalidMerkleProof)
+    );
+}
+
+#[tokio::test]
+async fn royalties_accrue_in_the_hook_and_settle() {
+    let mut test = TestContext::start().await;
+    let token_program = spl_token_2022::ID;
+    let payer = test.payer();
+    let mint = test
+        .create_mint(&token_program, &[ExtensionType::TransferHook], 0)
+        .await;
+    let sender = test.new_wallet().await;
+    let mut token_accounts = vec![];
+    // the last token account is the creator vault
+    for owner in [sender.pubkey(), Pubkey::new_unique(), Pubkey::new_unique()] {
+        token_accounts.push(
+            test.create_token_account(&owner, &mint, &token_program)
+                .await,
+        );
+    }
+    let [sender_account, recipient_account, creator_vault]: [Pubkey; 3] =
+        token_accounts.try_into().unwrap();
+    test.initialize_stats(
+        &[mint, sender_account, recipient_account, creator_vault],
+        &mint,
+    )
+    .await;
+    test.initialize_shard_of(&sender.pubkey()).await;
+    test.mint_to(&mint, &sender_account, &token_program, 100 * TOKEN)
+        .await;
+
+    let royalty_bps = 250;
+    for (authority, bps, error) in [
+        (&payer, 10_001, TransferExtensionsError::InvalidRoyaltyBps),
+        (
+            &sender.pubkey(),
+            royalty_bps,
+            TransferExtensionsError::NotMintAuthority,
+        ),
+    ] {
+        let instruction =
+            instructions::initialize_royalty_config(authority, &mint, &creator_vault, bps);
+        let signers = if *authority == sender.pubkey() {
+            vec![&sender]
+        } else {
+            vec![]
+        };
+        assert_eq!(
+            custom_error(test.process(&[instruction], &signers).await),
+            transfer_extensions_error(error)
+        );
+    }
+    test.process(
+        &[
+            instructions::initialize_royalty_config(&payer, &mint, &creator_vault, royalty_bps),
+            instructions::initialize_royalty_debt(&payer, &sender.pubkey(), &mint),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+    test.initialize_meta_list(
+        &mint,
+        HookModules {
+            counters: Some(CounterLayout::TokenAccount),
+            royalties: true,
+            owner_policy: false,
+            allowlist: false,
+            lockups: false,
+        },
+    )
+    .await;
+
+    let amount = 10 * TOKEN;
+    test.transfer(&sender_account, &mint, &recipient_account, &sender, amount)
+        .await
+        .unwrap();
+    let royalty = amount * royalty_bps as u64 / 10_000;
+    let royalty_debt = get_royalty_debt_address(&sender.pubkey(), &mint);
+    assert_eq!(test.state::<RoyaltyDebt>(&royalty_debt).await.owed, royalty);
+
+    // any payment of the wallet to the creator vault settles its debt
+    let partial = royalty / 2;
+    test.transfer(&sender_account, &mint, &creator_vault, &sender, partial)
+        .await
+        .unwrap();
+    let debt = test.state::<RoyaltyDebt>(&royalty_debt).await;
+    assert_eq!((debt.owed, debt.settled), (royalty - partial, partial));
+
+    let settle = || {
+        instructions::settle_royalties(
+            &sender.pubkey(),
+            &sender_account,
+            &creator_vault,
+            &mint,
+            &token_program,
+        )
+    };
+    let mut instruction = settle();
+    test.add_hook_accounts(
+        &mut instruction,
+        &mint,
+        &[(
+            sender_account,
+            creator_vault,
+            sender.pubkey(),
+            royalty - partial,
+        )],
+    )
+    .await;
+    test.process(&[instruction], &[&sender]).await.unwrap();
+
+    // the settlement itself accrues no royalty
+    let debt = test.state::<RoyaltyDebt>(&royalty_debt).await;
+    assert_eq!((debt.owed, debt.settled), (0, royalty));
+    assert_eq!(test.balance(&creator_vault).await, royalty);
+    assert_eq!(
+        custom_error(test.process(&[settle()], &[&sender]).await),
+        transfer_extensions_error(TransferExtensionsError::NoRoyaltyOwed)
+    );
+}
+
+#[tokio::test]
+async fn royalties_settle_net_of_the_transfer_fee() {
+    let mut test = TestContext::start().await;
+    let token_program = spl_token_2022::ID;
+    let payer = test.payer();
+    let mint = test
+        .create_mint(
+            &token_program,
+            &[
+                ExtensionType::TransferHook,
+                ExtensionType::TransferFeeConfig,
+            ],
+            100,
+        )
+        .await;
+    let sender = test.new_wallet().await;
+    let mut token_accounts = vec![];
+    // the last token account is the creator vault
+    for owner in [sender.pubkey(), Pubkey::new_unique(), Pubkey::new_unique()] {
+        token_accounts.push(
+            test.create_token_account(&owner, &mint, &token_program)
+                .await,
+        );
+    }
+    let [sender_account, recipient_account, creator_vault]: [Pubkey; 3] =
+        token_accounts.try_into().unwrap();
+    test.initialize_stats(
+        &[mint, sender_account, recipient_account, creator_vault],
+        &mint,
+    )
+    .await;
+    test.initialize_shard_of(&sender.pubkey()).await;
+    test.mint_to(&mint, &sender_account, &token_program, 100 * TOKEN)
+        .await;
+    let royalty_bps = 250;
+    test.process(
+        &[instructions::initialize_royalty_config(
+            &payer,
+            &mint,
+            &creator_vault,
+            royalty_bps,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let settle = || {
+        instructions::settle_royalties(
+            &sender.pubkey(),
+            &sender_account,
+            &creator_vault,
+            &mint,
+            &token_program,
+        )
+    };
+    assert_eq!(
+        custom_error(test.process(&[settle()], &[&sender]).await),
+        transfer_extensions_error(TransferExtensionsError::RoyaltyDebtNotInitialized)
+    );
+
+    test.process(
+        &[instructions::initialize_royalty_debt(
+            &payer,
+            &sender.pubkey(),
+            &mint,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    test.initialize_meta_list(
+        &mint,
+        HookModules {
+            counters: Some(CounterLayout::TokenAccount),
+            royalties: true,
+            owner_policy: false,
+            allowlist: false,
+            lockups: false,
+        },
+    )
+    .await;
+
+    let amount = 10 * TOKEN;
+    test.transfer(&sender_account, &mint, &recipient_account, &sender, amount)
+        .await
+        .unwrap();
+    let royalty = amount * royalty_bps as u64 / 10_000;
+    let royalty_debt = get_royalty_debt_address(&sender.pubkey(), &mint);
+    assert_eq!(test.state::<RoyaltyDebt>(&royalty_debt).await.owed, royalty);
+
+    // a direct payment settles what the vault receives, the fee is withheld from it
+    let partial = royalty / 2;
+    test.transfer(&sender_account, &mint, &creator_vault, &sender, partial)
+        .await
+        .unwrap();
+    let received = test.balance(&creator_vault).await;
+    assert!(received < partial);
+    let debt = test.state::<RoyaltyDebt>(&royalty_debt).await;
+    assert_eq!((debt.owed, debt.settled), (royalty - received, received));
+
+    // settle_royalties pays the fee on top of what is owed
+    let sender_balance = test.balance(&sender_account).await;
+    let mut instruction = settle();
+    test.add_hook_accounts(
+        &mut instruction,
+        &mint,
+        &[(
+            sender_account,
+            creator_vault,
+            sender.pubkey(),
+            royalty - received,
+        )],
+    )
+    .await;
+    test.process(&[instruction], &[&sender]).await.unwrap();
+
+    let debt = test.state::<RoyaltyDebt>(&royalty_debt).await;
+    assert_eq!((debt.owed, debt.settled), (0, royalty));
+    assert_eq!(test.balance(&creator_vault).await, royalty);
+    assert!(sender_balance - test.balance(&sender_account).await > royalty - received);
+}